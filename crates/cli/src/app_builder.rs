@@ -1,23 +1,37 @@
+use anyhow::anyhow;
 use anyhow::Result;
 use clap::App;
 use clap::AppSettings;
 use delphinus_zkwasm::circuits::config::MIN_K;
+use delphinus_zkwasm::foreign::context::file::ContextFile;
 use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
 use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
 use log::info;
 use std::fs;
-use std::io::Write;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
 
 use crate::exec::exec_dry_run;
+use crate::exec::exec_suggest_phantom;
 
 use super::command::CommandBuilder;
+use super::exec::exec_circuit_info;
 use super::exec::exec_create_proof;
+use super::exec::exec_disasm;
 use super::exec::exec_image_checksum;
+#[cfg(not(feature = "uniform-circuit"))]
 use super::exec::exec_setup;
+use super::exec::exec_solidity_verifier;
+#[cfg(feature = "uniform-circuit")]
+use super::exec::exec_uniform_setup;
+use super::exec::exec_verify_bundle;
+#[cfg(not(feature = "uniform-circuit"))]
 use super::exec::exec_verify_proof;
+#[cfg(feature = "uniform-circuit")]
+use super::exec::exec_verify_uniform_proof;
+#[cfg(feature = "uniform-circuit")]
+use super::exec::lookup_image_checksum;
 
 fn load_or_generate_output_path(
     wasm_md5: &String,
@@ -36,18 +50,10 @@ fn load_or_generate_output_path(
 pub fn write_context_output(
     context_output: &Vec<u64>,
     context_out_path: Option<PathBuf>,
+    image_md5: &String,
 ) -> Result<()> {
     if let Some(path) = context_out_path {
-        let mut fd = fs::File::create(path.as_path())?;
-        fd.write_all("0x".as_bytes())?;
-
-        for value in context_output {
-            let bytes = value.to_le_bytes();
-            let s = hex::encode(bytes);
-            fd.write_all(&s.as_bytes())?;
-        }
-
-        fd.write_all(":bytes-packed".as_bytes())?;
+        ContextFile::new(Some(image_md5.clone()), context_output.clone()).write(&path)?;
     }
 
     Ok(())
@@ -73,9 +79,13 @@ pub trait AppBuilder: CommandBuilder {
 
         let app = Self::append_setup_subcommand(app);
         let app = Self::append_dry_run_subcommand(app);
+        let app = Self::append_suggest_phantom_subcommand(app);
         let app = Self::append_create_single_proof_subcommand(app);
         let app = Self::append_verify_single_proof_subcommand(app);
         let app = Self::append_image_checksum_subcommand(app);
+        let app = Self::append_disasm_subcommand(app);
+        let app = Self::append_circuit_info_subcommand(app);
+        let app = Self::append_generate_single_solidity_verifier(app);
 
         app
     }
@@ -85,6 +95,29 @@ pub trait AppBuilder: CommandBuilder {
 
         let top_matches = command.get_matches();
 
+        // The bundle carries everything but the params, the wasm image is optional.
+        if let Some(("single-verify", sub_matches)) = top_matches.subcommand() {
+            if let Some(bundle_path) = Self::parse_bundle_arg(&sub_matches) {
+                let param_dir = top_matches
+                    .get_one::<PathBuf>("param")
+                    .ok_or(anyhow!("--param is required to verify a proof bundle"))?;
+                let image = match top_matches.get_one::<PathBuf>("wasm") {
+                    Some(path) => {
+                        Some((fs::read(path)?, Self::parse_phantom_functions(&top_matches)))
+                    }
+                    None => None,
+                };
+
+                return exec_verify_bundle::<ExecutionArg, DefaultHostEnvBuilder>(
+                    Self::NAME,
+                    &bundle_path,
+                    param_dir,
+                    image,
+                    Self::parse_checksum_arg(&sub_matches),
+                );
+            }
+        }
+
         let zkwasm_k = Self::parse_zkwasm_k_arg(&top_matches).unwrap_or(MIN_K);
 
         let wasm_file_path = Self::parse_zkwasm_file_arg(&top_matches);
@@ -114,6 +147,14 @@ pub trait AppBuilder: CommandBuilder {
         fs::create_dir_all(&param_dir)?;
 
         match top_matches.subcommand() {
+            #[cfg(feature = "uniform-circuit")]
+            Some(("setup", _)) => exec_uniform_setup::<ExecutionArg, DefaultHostEnvBuilder>(
+                zkwasm_k,
+                Self::AGGREGATE_K,
+                Self::NAME,
+                &param_dir,
+            ),
+            #[cfg(not(feature = "uniform-circuit"))]
             Some(("setup", _)) => exec_setup::<ExecutionArg, DefaultHostEnvBuilder>(
                 zkwasm_k,
                 Self::AGGREGATE_K,
@@ -127,14 +168,24 @@ pub trait AppBuilder: CommandBuilder {
                 zkwasm_k,
                 wasm_binary,
                 phantom_functions,
+                &md5,
                 &output_dir,
+                &param_dir,
+            ),
+            Some(("circuit-info", _)) => exec_circuit_info(zkwasm_k),
+            Some(("disasm", _)) => exec_disasm::<ExecutionArg, DefaultHostEnvBuilder>(
+                zkwasm_k,
+                wasm_binary,
+                phantom_functions,
             ),
             Some(("dry-run", sub_matches)) => {
                 let public_inputs: Vec<u64> = Self::parse_single_public_arg(&sub_matches);
                 let private_inputs: Vec<u64> = Self::parse_single_private_arg(&sub_matches);
-                let context_in: Vec<u64> = Self::parse_context_in_arg(&sub_matches);
+                let context_in: Vec<u64> = Self::parse_context_in_arg(&sub_matches, &md5)?;
                 let context_out_path: Option<PathBuf> =
                     Self::parse_context_out_path_arg(&sub_matches);
+                let step_limit = Self::parse_step_limit_arg(&sub_matches);
+                let estimate = Self::parse_estimate_arg(&sub_matches);
                 assert!(public_inputs.len() <= Self::MAX_PUBLIC_INPUT_SIZE);
 
                 let context_output = Arc::new(Mutex::new(vec![]));
@@ -148,19 +199,42 @@ pub trait AppBuilder: CommandBuilder {
                         private_inputs,
                         context_inputs: context_in,
                         context_outputs: context_output.clone(),
+                        step_limit,
                     },
+                    estimate,
                 )?;
 
-                write_context_output(&context_output.lock().unwrap(), context_out_path)?;
+                write_context_output(&context_output.lock().unwrap(), context_out_path, &md5)?;
 
                 Ok(())
             }
+            Some(("suggest-phantom", sub_matches)) => {
+                let public_inputs: Vec<u64> = Self::parse_single_public_arg(&sub_matches);
+                let private_inputs: Vec<u64> = Self::parse_single_private_arg(&sub_matches);
+                let context_in: Vec<u64> = Self::parse_context_in_arg(&sub_matches, &md5)?;
+                assert!(public_inputs.len() <= Self::MAX_PUBLIC_INPUT_SIZE);
+
+                exec_suggest_phantom::<ExecutionArg, DefaultHostEnvBuilder>(
+                    zkwasm_k,
+                    wasm_binary,
+                    phantom_functions,
+                    ExecutionArg {
+                        public_inputs,
+                        private_inputs,
+                        context_inputs: context_in,
+                        ..Default::default()
+                    },
+                )
+            }
             Some(("single-prove", sub_matches)) => {
                 let public_inputs: Vec<u64> = Self::parse_single_public_arg(&sub_matches);
                 let private_inputs: Vec<u64> = Self::parse_single_private_arg(&sub_matches);
-                let context_in: Vec<u64> = Self::parse_context_in_arg(&sub_matches);
+                let context_in: Vec<u64> = Self::parse_context_in_arg(&sub_matches, &md5)?;
                 let context_out_path: Option<PathBuf> =
                     Self::parse_context_out_path_arg(&sub_matches);
+                let step_limit = Self::parse_step_limit_arg(&sub_matches);
+                let hash = Self::parse_transcript_hash_arg(&sub_matches);
+                let audit = Self::parse_audit_arg(&sub_matches);
 
                 let context_out = Arc::new(Mutex::new(vec![]));
 
@@ -178,13 +252,52 @@ pub trait AppBuilder: CommandBuilder {
                         private_inputs,
                         context_inputs: context_in,
                         context_outputs: context_out.clone(),
+                        step_limit,
                     },
+                    hash,
+                    audit,
                 )?;
 
-                write_context_output(&context_out.lock().unwrap(), context_out_path)?;
+                write_context_output(&context_out.lock().unwrap(), context_out_path, &md5)?;
 
                 Ok(())
             }
+            Some(("solidity-verifier", sub_matches)) => {
+                let public_inputs: Vec<u64> = Self::parse_single_public_arg(&sub_matches);
+                let private_inputs: Vec<u64> = Self::parse_single_private_arg(&sub_matches);
+                let context_in: Vec<u64> = Self::parse_context_in_arg(&sub_matches, &md5)?;
+                let sol_dir = Self::parse_sol_dir_arg(&sub_matches);
+                assert!(public_inputs.len() <= Self::MAX_PUBLIC_INPUT_SIZE);
+
+                exec_solidity_verifier::<ExecutionArg, DefaultHostEnvBuilder>(
+                    Self::NAME,
+                    zkwasm_k,
+                    wasm_binary,
+                    phantom_functions,
+                    &output_dir,
+                    &param_dir,
+                    &sol_dir,
+                    ExecutionArg {
+                        public_inputs,
+                        private_inputs,
+                        context_inputs: context_in,
+                        ..Default::default()
+                    },
+                )
+            }
+            #[cfg(feature = "uniform-circuit")]
+            Some(("single-verify", sub_matches)) => {
+                let checksum = match Self::parse_checksum_arg(&sub_matches) {
+                    Some(checksum) => checksum,
+                    None => lookup_image_checksum(&param_dir, &md5)?.ok_or(anyhow!(
+                        "Checksum of image {} is not registered, please run checksum first",
+                        md5
+                    ))?,
+                };
+
+                exec_verify_uniform_proof(Self::NAME, &output_dir, &param_dir, &checksum)
+            }
+            #[cfg(not(feature = "uniform-circuit"))]
             Some(("single-verify", _)) => exec_verify_proof(Self::NAME, &output_dir, &param_dir),
             Some((_, _)) => todo!(),
             None => todo!(),
@@ -1,8 +1,11 @@
+use anyhow::Result;
+use circuits_batcher::args::HashType;
 use clap::arg;
 use clap::value_parser;
 use clap::Arg;
 use clap::ArgAction;
 use clap::ArgMatches;
+use delphinus_zkwasm::foreign::context::file::ContextFile;
 use specs::args::parse_args;
 use std::path::PathBuf;
 
@@ -19,7 +22,7 @@ pub trait ArgBuilder {
 
     fn zkwasm_file_arg<'a>() -> Arg<'a> {
         arg!(
-            -w --wasm <WASM_BINARY> "Path of the Wasm binary file"
+            -w --wasm [WASM_BINARY] "Path of the Wasm binary file.\nOnly optional for single-verify with --bundle."
         )
         .value_parser(value_parser!(PathBuf))
     }
@@ -32,7 +35,7 @@ pub trait ArgBuilder {
 
     fn function_name_arg<'a>() -> Arg<'a> {
         arg!(
-            -f --function <FUNCTION_NAME> "Function you would like to run."
+            -f --function [FUNCTION_NAME] "Function you would like to run.\nOnly optional for single-verify with --bundle."
         )
     }
     fn parse_function_name(matches: &ArgMatches) -> String {
@@ -98,6 +101,70 @@ pub trait ArgBuilder {
             .map_or(PathBuf::from("sol"), |x| x.clone())
     }
 
+    fn estimate_arg<'a>() -> Arg<'a> {
+        arg!(
+            --estimate "Trace the execution to report the exact rows required by each table and the minimal K."
+        )
+        .action(ArgAction::SetTrue)
+    }
+    fn parse_estimate_arg(matches: &ArgMatches) -> bool {
+        matches.get_one::<bool>("estimate").map_or(false, |v| *v)
+    }
+
+    fn audit_arg<'a>() -> Arg<'a> {
+        arg!(
+            --audit "Audit the trace natively before proving, images with phantom functions are not audited."
+        )
+        .action(ArgAction::SetTrue)
+    }
+    fn parse_audit_arg(matches: &ArgMatches) -> bool {
+        matches.get_one::<bool>("audit").map_or(false, |v| *v)
+    }
+
+    fn step_limit_arg<'a>() -> Arg<'a> {
+        arg!(
+            --step_limit [STEP_LIMIT] "Maximal steps of the execution, which fails once the steps are exhausted."
+        )
+        .value_parser(value_parser!(u64))
+    }
+    fn parse_step_limit_arg(matches: &ArgMatches) -> Option<u64> {
+        matches.get_one::<u64>("step_limit").cloned()
+    }
+
+    fn checksum_arg<'a>() -> Arg<'a> {
+        arg!(
+            --checksum [CHECKSUM] "Expected image checksum in hex, required by uniform circuit.\nThe checksum registered for the wasm image is used if not supplied."
+        )
+        .value_parser(value_parser!(String))
+    }
+    fn parse_checksum_arg(matches: &ArgMatches) -> Option<String> {
+        matches.get_one::<String>("checksum").cloned()
+    }
+
+    fn bundle_arg<'a>() -> Arg<'a> {
+        arg!(
+            --bundle [BUNDLE_PATH] "Path of the proof bundle written by single-prove.\nOnly the bundle and the params are required for verification if supplied,\nthe bundle is also checked against the wasm image if supplied."
+        )
+        .value_parser(value_parser!(PathBuf))
+    }
+    fn parse_bundle_arg(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("bundle").cloned()
+    }
+
+    fn transcript_hash_arg<'a>() -> Arg<'a> {
+        arg!(
+            --hash [HASH] "Transcript hash of the proof, poseidon for proofs to be aggregated and sha for proofs verified by external verifiers such as EVM."
+        )
+        .value_parser(["poseidon", "sha"])
+        .default_value("poseidon")
+    }
+    fn parse_transcript_hash_arg(matches: &ArgMatches) -> HashType {
+        match matches.get_one::<String>("hash").map(|v| v.as_str()) {
+            Some("sha") => HashType::Sha,
+            _ => HashType::Poseidon,
+        }
+    }
+
     fn auxonly_arg<'a>() -> Arg<'a> {
         arg!(
             -a --auxonly "Generate aux file only."
@@ -135,22 +202,32 @@ pub trait ArgBuilder {
         .long("ctxin")
         .value_parser(value_parser!(String))
         .action(ArgAction::Append)
-        .help("Context arguments of your wasm program arguments of format value:type where type=i64|bytes|bytes-packed")
+        .help("Context arguments of your wasm program arguments of format value:type where type=i64|bytes|bytes-packed|context, value of context type is the path of a context file written by --ctxout")
         .min_values(0)
     }
-    fn parse_context_in_arg(matches: &ArgMatches) -> Vec<u64> {
-        let inputs: Vec<&str> = matches
+    /// `image_md5` is the md5 of the running image, context files written by
+    /// other images are rejected.
+    fn parse_context_in_arg(matches: &ArgMatches, image_md5: &str) -> Result<Vec<u64>> {
+        let mut context_in = vec![];
+
+        for v in matches
             .get_many("ctxin")
             .unwrap_or_default()
             .map(|v: &String| v.as_str())
-            .collect();
+        {
+            match v.strip_suffix(":context") {
+                Some(path) => context_in
+                    .append(&mut ContextFile::read(&PathBuf::from(path))?.values_for(image_md5)?),
+                None => context_in.append(&mut parse_args(vec![v])),
+            }
+        }
 
-        parse_args(inputs.into())
+        Ok(context_in)
     }
 
     fn context_out_path_arg<'a>() -> Arg<'a> {
         arg!(
-            --ctxout [CONTEXT_OUT] "Path of Context Output."
+            --ctxout [CONTEXT_OUT] "Path of Context Output.\nThe file can be passed to --ctxin of the next run as CONTEXT_OUT:context."
         )
         .value_parser(value_parser!(PathBuf))
     }
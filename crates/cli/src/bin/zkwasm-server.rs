@@ -0,0 +1,64 @@
+use anyhow::Result;
+use clap::arg;
+use clap::value_parser;
+use clap::App;
+use delphinus_cli::server::serve;
+use delphinus_cli::server::ServerConfig;
+use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
+use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
+use std::fs;
+use std::path::PathBuf;
+
+const NAME: &str = "zkwasm";
+const AGGREGATE_K: u32 = 22;
+const MAX_PUBLIC_INPUT_SIZE: usize = 64;
+
+fn main() -> Result<()> {
+    env_logger::init();
+
+    let matches = App::new("zkwasm-server")
+        .version("v1.0-beta")
+        .arg(
+            arg!(
+                -s --socket [SOCKET_PATH] "Path of the unix socket to listen on."
+            )
+            .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(
+                -o --output [OUTPUT_PATH] "Path of the output files."
+            )
+            .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(
+                -p --param [PARAM_PATH] "Path of the params files."
+            )
+            .value_parser(value_parser!(PathBuf)),
+        )
+        .get_matches();
+
+    let socket_path = matches
+        .get_one::<PathBuf>("socket")
+        .map_or(PathBuf::from("zkwasm.sock"), |x| x.clone());
+    let output_dir = matches
+        .get_one::<PathBuf>("output")
+        .map_or(PathBuf::from("output"), |x| x.clone());
+    let param_dir = matches
+        .get_one::<PathBuf>("param")
+        .map_or(PathBuf::from("params"), |x| x.clone());
+
+    fs::create_dir_all(&output_dir)?;
+    fs::create_dir_all(&param_dir)?;
+
+    serve::<ExecutionArg, DefaultHostEnvBuilder>(
+        ServerConfig {
+            prefix: NAME,
+            aggregate_k: AGGREGATE_K,
+            max_public_input_size: MAX_PUBLIC_INPUT_SIZE,
+            param_dir,
+            output_dir,
+        },
+        &socket_path,
+    )
+}
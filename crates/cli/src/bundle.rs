@@ -0,0 +1,424 @@
+//! A self-describing proof bundle written by `single-prove`.
+//!
+//! The bundle carries everything a verifier needs besides the params,
+//! including the verifying key and its hash, so that `single-verify` can
+//! verify it without the wasm image and check that all pieces belong to each
+//! other. The image is identified by its md5 and checksum, which are only
+//! trusted once they are compared with the ones supplied by the verifier.
+
+use anyhow::anyhow;
+use anyhow::Result;
+use circuits_batcher::args::HashType;
+use halo2_proofs::arithmetic::BaseExt;
+use halo2_proofs::pairing::bn256::Bn256;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::Params;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+use delphinus_zkwasm::checksum::checksum_to_hex;
+use delphinus_zkwasm::circuits::config::set_zkwasm_k;
+#[cfg(feature = "public-eid")]
+use delphinus_zkwasm::circuits::consumed_steps_from_instances;
+use delphinus_zkwasm::circuits::TestCircuit;
+use delphinus_zkwasm::loader::verify_single_proof;
+
+use crate::exec::transcript_hash;
+
+/// Bumped whenever the layout of `ProofBundle` changes.
+pub const BUNDLE_VERSION: u32 = 2;
+
+pub const ZKWASM_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug)]
+pub enum BundleErr {
+    VersionMismatch {
+        expected: u32,
+        found: u32,
+    },
+    /// The md5 of the wasm image differs from the one of the bundle.
+    ImageMismatch {
+        expected: String,
+        found: String,
+    },
+    /// The checksum of the image under the params differs from the one of the bundle.
+    ChecksumMismatch,
+    /// The uniform circuit is verified without an expected image checksum.
+    ChecksumRequired,
+    /// The hash of the verifying key differs from the one of the bundle.
+    VerifyingKeyMismatch,
+    /// The instances of the bundle do not match the instance columns of the circuit.
+    InstancesMismatch,
+}
+
+impl Display for BundleErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BundleErr::VersionMismatch { expected, found } => write!(
+                f,
+                "Proof bundle version {} is not supported, expected version {}",
+                found, expected
+            ),
+            BundleErr::ImageMismatch { expected, found } => write!(
+                f,
+                "Image md5 {} does not match the md5 {} of the bundle",
+                found, expected
+            ),
+            BundleErr::ChecksumMismatch => {
+                write!(
+                    f,
+                    "Image checksum does not match the checksum of the bundle"
+                )
+            }
+            BundleErr::ChecksumRequired => write!(
+                f,
+                "Image checksum is required to verify a bundle of the uniform circuit"
+            ),
+            BundleErr::VerifyingKeyMismatch => {
+                write!(f, "Verifying key does not match the hash of the bundle")
+            }
+            BundleErr::InstancesMismatch => write!(
+                f,
+                "Instances do not match the instance columns of the circuit"
+            ),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProofBundle {
+    pub version: u32,
+    pub zkwasm_version: String,
+    pub k: u32,
+    pub image_md5: String,
+    /// Hex of the image checksum, see `checksum_to_hex`.
+    pub image_checksum: String,
+    /// Hex of the instances of each instance column.
+    pub instances: Vec<Vec<String>>,
+    pub hash: HashType,
+    /// Hex of the serialized verifying key.
+    pub vkey: String,
+    /// Hex of the sha256 of the serialized verifying key.
+    pub vkey_hash: String,
+    pub proof: String,
+}
+
+pub fn field_to_hex(value: &Fr) -> String {
+    let mut bytes = vec![];
+    value.write(&mut bytes).unwrap();
+    hex::encode(bytes)
+}
+
+pub fn field_from_hex(value: &str) -> Result<Fr> {
+    Ok(Fr::read(&mut &hex::decode(value)?[..])?)
+}
+
+fn vkey_to_bytes(vkey: &VerifyingKey<G1Affine>) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    vkey.write(&mut bytes)?;
+
+    Ok(bytes)
+}
+
+pub fn vkey_hash(vkey: &VerifyingKey<G1Affine>) -> Result<String> {
+    Ok(hex::encode(Sha256::digest(&vkey_to_bytes(vkey)?)))
+}
+
+impl ProofBundle {
+    pub fn new(
+        k: u32,
+        image_md5: String,
+        image_checksum: &Vec<G1Affine>,
+        instances: &Vec<Vec<Fr>>,
+        hash: HashType,
+        vkey: &VerifyingKey<G1Affine>,
+        proof: &Vec<u8>,
+    ) -> Result<Self> {
+        let vkey = vkey_to_bytes(vkey)?;
+
+        Ok(ProofBundle {
+            version: BUNDLE_VERSION,
+            zkwasm_version: ZKWASM_VERSION.to_owned(),
+            k,
+            image_md5,
+            image_checksum: checksum_to_hex(image_checksum),
+            instances: instances
+                .iter()
+                .map(|column| column.iter().map(field_to_hex).collect())
+                .collect(),
+            hash,
+            vkey_hash: hex::encode(Sha256::digest(&vkey)),
+            vkey: hex::encode(vkey),
+            proof: hex::encode(proof),
+        })
+    }
+
+    pub fn save(&self, path: &PathBuf) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    /// Load the bundle and reject bundles written in another layout.
+    pub fn load(path: &PathBuf) -> Result<Self> {
+        let bundle: ProofBundle = serde_json::from_slice(&std::fs::read(path)?)
+            .map_err(|e| anyhow!("Failed to parse proof bundle {:?}: {}", path, e))?;
+
+        if bundle.version != BUNDLE_VERSION {
+            return Err(anyhow!(BundleErr::VersionMismatch {
+                expected: BUNDLE_VERSION,
+                found: bundle.version,
+            }));
+        }
+
+        Ok(bundle)
+    }
+
+    pub fn instances(&self) -> Result<Vec<Vec<Fr>>> {
+        self.instances
+            .iter()
+            .map(|column| column.iter().map(|v| field_from_hex(v)).collect())
+            .collect()
+    }
+
+    pub fn proof(&self) -> Result<Vec<u8>> {
+        Ok(hex::decode(&self.proof)?)
+    }
+
+    /// Read the verifying key of the bundle and check it against `vkey_hash`.
+    pub fn vkey(&self, params: &Params<G1Affine>) -> Result<VerifyingKey<G1Affine>> {
+        let bytes = hex::decode(&self.vkey)?;
+        if hex::encode(Sha256::digest(&bytes)) != self.vkey_hash {
+            return Err(anyhow!(BundleErr::VerifyingKeyMismatch));
+        }
+
+        // The circuit is configured under the K of the bundle.
+        set_zkwasm_k(self.k);
+
+        Ok(VerifyingKey::<G1Affine>::read::<_, TestCircuit<Fr>>(
+            &mut &bytes[..],
+            params,
+        )?)
+    }
+
+    /// Verify the proof with the params of K, every field but the image md5,
+    /// which is only checked against a wasm image, is checked for consistency.
+    ///
+    /// The image checksum of the bundle is compared with the `checksum` the
+    /// caller trusts, which is required by the uniform circuit as its vkey does
+    /// not identify the image.
+    pub fn verify(
+        &self,
+        params: &Params<G1Affine>,
+        checksum: Option<&Vec<G1Affine>>,
+    ) -> Result<()> {
+        let vkey = self.vkey(params)?;
+
+        let instances = self.instances()?;
+        if instances.is_empty() || instances.len() != vkey.cs.num_instance_columns() {
+            return Err(anyhow!(BundleErr::InstancesMismatch));
+        }
+        // The last instance column is the termination eid.
+        #[cfg(feature = "public-eid")]
+        consumed_steps_from_instances(instances.last().unwrap())
+            .ok_or(anyhow!(BundleErr::InstancesMismatch))?;
+
+        if let Some(checksum) = checksum {
+            if checksum_to_hex(checksum) != self.image_checksum {
+                return Err(anyhow!(BundleErr::ChecksumMismatch));
+            }
+        }
+
+        let proof = self.proof()?;
+        let hash = transcript_hash(&self.hash);
+
+        #[cfg(feature = "uniform-circuit")]
+        {
+            use delphinus_zkwasm::loader::check_image_commitment;
+
+            let checksum = checksum.ok_or(anyhow!(BundleErr::ChecksumRequired))?;
+            check_image_commitment::<Bn256>(&vkey, &proof, checksum, hash)?;
+        }
+
+        verify_single_proof::<Bn256>(params, &vkey, &instances, &proof, hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use circuits_batcher::args::HashType;
+    use delphinus_zkwasm::checksum::checksum_to_hex;
+    use delphinus_zkwasm::loader::err::Error;
+    use delphinus_zkwasm::loader::err::VerifyErr;
+    use delphinus_zkwasm::loader::ZkWasmLoader;
+    use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
+    use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
+    use halo2_proofs::pairing::bn256::Bn256;
+    use halo2_proofs::pairing::bn256::Fr;
+    use halo2_proofs::pairing::bn256::G1Affine;
+    use halo2_proofs::pairing::group::prime::PrimeCurveAffine;
+    use halo2_proofs::poly::commitment::Params;
+    use std::fs;
+
+    use super::field_to_hex;
+    use super::BundleErr;
+    use super::ProofBundle;
+    use crate::exec::exec_verify_bundle;
+    use crate::exec::transcript_hash;
+
+    const K: u32 = 18;
+
+    #[test]
+    fn test_proof_bundle_round_trip() {
+        let dir = std::env::temp_dir().join(format!("zkwasm-bundle-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let wasm = wabt::wat2wasm(
+            r#"
+            (module
+                (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+                (func (export "zkmain")
+                  (drop (i64.add (call $wasm_input (i32.const 1)) (i64.const 1))))
+            )
+            "#,
+        )
+        .unwrap();
+        let md5 = format!("{:X}", md5::compute(&wasm));
+
+        let loader = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(
+            K,
+            wasm.clone(),
+            vec![],
+        )
+        .unwrap();
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        params
+            .write(&mut fs::File::create(dir.join(format!("K{}.params", K))).unwrap())
+            .unwrap();
+        let vkey = loader.create_vkey(&params).unwrap();
+
+        let (circuit, instances, _) = loader
+            .circuit_with_witness(ExecutionArg {
+                public_inputs: vec![41],
+                ..Default::default()
+            })
+            .unwrap();
+        let instance_columns = circuit.instance_columns(&instances).unwrap();
+        let proof = loader
+            .create_proof(
+                &params,
+                vkey.clone(),
+                circuit,
+                &instances,
+                transcript_hash(&HashType::Poseidon),
+            )
+            .unwrap();
+
+        let bundle = ProofBundle::new(
+            K,
+            md5,
+            &loader.checksum(&params).unwrap(),
+            &instance_columns,
+            HashType::Poseidon,
+            &vkey,
+            &proof,
+        )
+        .unwrap();
+        let bundle_path = dir.join("zkwasm.bundle.json");
+        bundle.save(&bundle_path).unwrap();
+
+        let checksum = checksum_to_hex(&loader.checksum(&params).unwrap());
+        let verify_with_checksum =
+            |bundle: &ProofBundle, image: Option<Vec<u8>>, checksum: Option<String>| {
+                bundle.save(&bundle_path).unwrap();
+
+                exec_verify_bundle::<ExecutionArg, DefaultHostEnvBuilder>(
+                    "zkwasm",
+                    &bundle_path,
+                    &dir,
+                    image.map(|wasm| (wasm, vec![])),
+                    checksum,
+                )
+            };
+        let verify = |bundle: &ProofBundle, image: Option<Vec<u8>>| {
+            verify_with_checksum(bundle, image, Some(checksum.clone()))
+        };
+
+        // Only the bundle, the params and the expected checksum are required.
+        let loaded = ProofBundle::load(&bundle_path).unwrap();
+        verify(&loaded, None).unwrap();
+        verify(&loaded, Some(wasm.clone())).unwrap();
+
+        // The checksum embedded in the bundle is not trusted.
+        let mut tampered = ProofBundle::load(&bundle_path).unwrap();
+        tampered.image_checksum = checksum_to_hex(&vec![G1Affine::generator()]);
+        assert!(matches!(
+            verify(&tampered, None)
+                .unwrap_err()
+                .downcast_ref::<BundleErr>(),
+            Some(BundleErr::ChecksumMismatch)
+        ));
+        assert!(matches!(
+            verify_with_checksum(&loaded, Some(wasm.clone()), Some(tampered.image_checksum))
+                .unwrap_err()
+                .downcast_ref::<BundleErr>(),
+            Some(BundleErr::ChecksumMismatch)
+        ));
+        #[cfg(feature = "uniform-circuit")]
+        assert!(matches!(
+            verify_with_checksum(&loaded, None, None)
+                .unwrap_err()
+                .downcast_ref::<BundleErr>(),
+            Some(BundleErr::ChecksumRequired)
+        ));
+
+        let mut tampered = ProofBundle::load(&bundle_path).unwrap();
+        tampered.instances[0][0] = field_to_hex(&Fr::from(42u64));
+        assert!(matches!(
+            verify(&tampered, None).unwrap_err().downcast_ref::<Error>(),
+            Some(Error::Verify(VerifyErr::InvalidProof))
+        ));
+
+        let mut tampered = ProofBundle::load(&bundle_path).unwrap();
+        let mut proof = tampered.proof().unwrap();
+        proof[0] ^= 1;
+        tampered.proof = hex::encode(proof);
+        assert!(verify(&tampered, None).is_err());
+
+        let mut tampered = ProofBundle::load(&bundle_path).unwrap();
+        tampered.vkey_hash = hex::encode([0u8; 32]);
+        assert!(matches!(
+            verify(&tampered, None)
+                .unwrap_err()
+                .downcast_ref::<BundleErr>(),
+            Some(BundleErr::VerifyingKeyMismatch)
+        ));
+
+        let mut tampered = ProofBundle::load(&bundle_path).unwrap();
+        tampered.image_md5 = "0".repeat(32);
+        verify(&tampered, None).unwrap();
+        assert!(matches!(
+            verify(&tampered, Some(wasm))
+                .unwrap_err()
+                .downcast_ref::<BundleErr>(),
+            Some(BundleErr::ImageMismatch { .. })
+        ));
+
+        let mut tampered = ProofBundle::load(&bundle_path).unwrap();
+        tampered.version += 1;
+        assert!(matches!(
+            verify(&tampered, None)
+                .unwrap_err()
+                .downcast_ref::<BundleErr>(),
+            Some(BundleErr::VersionMismatch { .. })
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
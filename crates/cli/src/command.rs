@@ -16,12 +16,35 @@ pub trait CommandBuilder: ArgBuilder {
         app.subcommand(command)
     }
 
+    fn append_circuit_info_subcommand(app: App) -> App {
+        let command = Command::new("circuit-info");
+
+        app.subcommand(command)
+    }
+
+    fn append_disasm_subcommand(app: App) -> App {
+        let command = Command::new("disasm");
+
+        app.subcommand(command)
+    }
+
     fn append_dry_run_subcommand(app: App) -> App {
         let command = Command::new("dry-run")
             .arg(Self::single_public_arg())
             .arg(Self::single_private_arg())
             .arg(Self::context_in_arg())
-            .arg(Self::context_out_path_arg());
+            .arg(Self::context_out_path_arg())
+            .arg(Self::step_limit_arg())
+            .arg(Self::estimate_arg());
+
+        app.subcommand(command)
+    }
+
+    fn append_suggest_phantom_subcommand(app: App) -> App {
+        let command = Command::new("suggest-phantom")
+            .arg(Self::single_public_arg())
+            .arg(Self::single_private_arg())
+            .arg(Self::context_in_arg());
 
         app.subcommand(command)
     }
@@ -31,13 +54,28 @@ pub trait CommandBuilder: ArgBuilder {
             .arg(Self::single_public_arg())
             .arg(Self::single_private_arg())
             .arg(Self::context_in_arg())
-            .arg(Self::context_out_path_arg());
+            .arg(Self::context_out_path_arg())
+            .arg(Self::step_limit_arg())
+            .arg(Self::audit_arg())
+            .arg(Self::transcript_hash_arg());
 
         app.subcommand(command)
     }
 
     fn append_verify_single_proof_subcommand(app: App) -> App {
-        let command = Command::new("single-verify");
+        let command = Command::new("single-verify")
+            .arg(Self::checksum_arg())
+            .arg(Self::bundle_arg());
+        app.subcommand(command)
+    }
+
+    fn append_generate_single_solidity_verifier(app: App) -> App {
+        let command = Command::new("solidity-verifier")
+            .arg(Self::sol_dir_arg())
+            .arg(Self::single_public_arg())
+            .arg(Self::single_private_arg())
+            .arg(Self::context_in_arg());
+
         app.subcommand(command)
     }
 
@@ -1,20 +1,56 @@
+use anyhow::anyhow;
 use anyhow::Result;
+use circuits_batcher::args::HashType;
 use circuits_batcher::proof::CircuitInfo;
 use circuits_batcher::proof::ProofInfo;
 use circuits_batcher::proof::ProofLoadInfo;
+use delphinus_zkwasm::checksum::checksum_from_hex;
+use delphinus_zkwasm::checksum::checksum_to_hex;
+use delphinus_zkwasm::circuits::capacity::CapacityReport;
+use delphinus_zkwasm::circuits::info::CellUsage;
+use delphinus_zkwasm::circuits::info::CircuitInfo;
+use delphinus_zkwasm::circuits::test_circuit::VAR_COLUMNS;
+use delphinus_zkwasm::circuits::PublicInputs;
 use delphinus_zkwasm::circuits::TestCircuit;
+use delphinus_zkwasm::loader::err::Error;
+use delphinus_zkwasm::loader::err::RuntimeErr;
 use delphinus_zkwasm::loader::ZkWasmLoader;
 use delphinus_zkwasm::runtime::host::HostEnvBuilder;
 use halo2_proofs::pairing::bn256::Bn256;
 use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::Params;
 use halo2_proofs::poly::commitment::ParamsVerifier;
 use halo2aggregator_s::circuits::utils::load_or_build_unsafe_params;
 use halo2aggregator_s::circuits::utils::TranscriptHash;
 use halo2aggregator_s::native_verifier;
+use log::error;
 use log::info;
+use log::warn;
+use specs::encode::instruction_table::decode_instruction_table_entry;
+use specs::itable::Opcode;
+use std::collections::BTreeMap;
 use std::io::Write;
 use std::path::PathBuf;
 
+use crate::bundle::vkey_hash;
+use crate::bundle::BundleErr;
+use crate::bundle::ProofBundle;
+use crate::bundle::ZKWASM_VERSION;
+use crate::solidity::encode_verify_calldata;
+use crate::solidity::generate_aux;
+use crate::solidity::render_verifier;
+use crate::solidity::VERIFY_SIGNATURE;
+
+/// The transcript of proofs recorded in the proof load info.
+pub fn transcript_hash(hash: &HashType) -> TranscriptHash {
+    match hash {
+        HashType::Poseidon => TranscriptHash::Poseidon,
+        HashType::Sha => TranscriptHash::Sha,
+    }
+}
+
 pub fn exec_setup<Arg, Builder>(
     zkwasm_k: u32,
     aggregate_k: u32,
@@ -67,11 +103,79 @@ where
     Ok(())
 }
 
+const IMAGE_CHECKSUMS_FILE: &str = "image_checksums.json";
+
+/// Register the checksum of the image with `md5` in the param directory.
+pub fn register_image_checksum(param_dir: &PathBuf, md5: &String, checksum: String) -> Result<()> {
+    let path = param_dir.join(IMAGE_CHECKSUMS_FILE);
+
+    let mut checksums: BTreeMap<String, String> = if path.exists() {
+        serde_json::from_slice(&std::fs::read(&path)?)?
+    } else {
+        BTreeMap::new()
+    };
+    checksums.insert(md5.clone(), checksum);
+
+    std::fs::write(&path, serde_json::to_string_pretty(&checksums)?)?;
+
+    Ok(())
+}
+
+/// Find the registered checksum of the image with `md5`.
+pub fn lookup_image_checksum(param_dir: &PathBuf, md5: &String) -> Result<Option<String>> {
+    let path = param_dir.join(IMAGE_CHECKSUMS_FILE);
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let checksums: BTreeMap<String, String> = serde_json::from_slice(&std::fs::read(&path)?)?;
+    Ok(checksums.get(md5).cloned())
+}
+
+#[cfg(feature = "uniform-circuit")]
+pub fn exec_uniform_setup<Arg, Builder>(
+    zkwasm_k: u32,
+    aggregate_k: u32,
+    prefix: &str,
+    param_dir: &PathBuf,
+) -> Result<()>
+where
+    Builder: HostEnvBuilder<Arg = Arg>,
+{
+    info!("Setup Params and uniform VerifyingKey");
+
+    let params = {
+        let params_path = &param_dir.join(format!("K{}.params", zkwasm_k));
+        load_or_build_unsafe_params::<Bn256>(zkwasm_k, Some(params_path))
+    };
+    load_or_build_unsafe_params::<Bn256>(
+        aggregate_k,
+        Some(&param_dir.join(format!("K{}.params", aggregate_k))),
+    );
+
+    let vk_path = &param_dir.join(format!("{}.vkey.data", prefix));
+
+    if vk_path.exists() {
+        info!("Found uniform Verifying at {:?}", vk_path);
+    } else {
+        info!("Create uniform Verifying to {:?}", vk_path);
+        let vkey = ZkWasmLoader::<Bn256, Arg, Builder>::create_uniform_vkey(zkwasm_k, &params)?;
+
+        let mut fd = std::fs::File::create(&vk_path)?;
+        vkey.write(&mut fd)?;
+    }
+
+    Ok(())
+}
+
 pub fn exec_image_checksum<Arg, Builder>(
     zkwasm_k: u32,
     wasm_binary: Vec<u8>,
     phantom_functions: Vec<String>,
+    md5: &String,
     output_dir: &PathBuf,
+    param_dir: &PathBuf,
 ) -> Result<()>
 where
     Builder: HostEnvBuilder<Arg = Arg>,
@@ -85,16 +189,171 @@ where
     );
 
     let checksum = loader.checksum(&params)?;
-    assert_eq!(checksum.len(), 1);
-    let checksum = checksum[0];
+    assert_eq!(checksum.len(), 2);
 
     println!("image checksum: {:?}", checksum);
+    println!("image hash: {:?}", loader.image_hash()?);
 
     let mut fd =
         std::fs::File::create(&output_dir.join(format!("checksum.data",)).as_path()).unwrap();
 
     write!(fd, "{:?}", checksum)?;
 
+    register_image_checksum(param_dir, md5, checksum_to_hex(&checksum))?;
+
+    Ok(())
+}
+
+/// Print what the image table, and hence the checksum, commits to: the
+/// instructions decoded from their encoding, the br table, the elem table, the
+/// init memory and the static frames.
+pub fn exec_disasm<Arg, Builder: HostEnvBuilder<Arg = Arg>>(
+    zkwasm_k: u32,
+    wasm_binary: Vec<u8>,
+    phantom_functions: Vec<String>,
+) -> Result<()> {
+    let loader =
+        ZkWasmLoader::<Bn256, Arg, Builder>::new(zkwasm_k, wasm_binary, phantom_functions)?;
+
+    let (env, _) = Builder::create_env_without_value();
+    let tables = loader.compile(&env, true)?;
+
+    let mut current_fid = None;
+    for entry in tables.itable.iter() {
+        let (fid, iid, opcode) = decode_instruction_table_entry(&entry.encode);
+
+        if current_fid != Some(fid) {
+            let name = loader
+                .function_name(fid)
+                .unwrap_or(format!("func[{}]", fid));
+            let entry_mark = if fid == tables.fid_of_entry {
+                " (entry)"
+            } else {
+                ""
+            };
+
+            println!("function {} <{}>{}:", fid, name, entry_mark);
+            current_fid = Some(fid);
+        }
+
+        match Opcode::try_from(&opcode) {
+            Ok(Opcode::BrTable { targets }) => {
+                println!("{:>8}: BrTable {{ targets: {} }}", iid, targets.len())
+            }
+            Ok(opcode) => println!("{:>8}: {:?}", iid, opcode),
+            Err(_) => println!("{:>8}: undecodable {:#x}", iid, opcode),
+        }
+    }
+
+    println!("br table:");
+    for entry in tables.itable.create_brtable().entries() {
+        println!(
+            "  fid {} iid {} index {}: drop {} keep {} dst_pc {}",
+            entry.fid, entry.iid, entry.index, entry.drop, entry.keep, entry.dst_pc
+        );
+    }
+
+    println!("elem table:");
+    for entry in tables.elem_table.entries() {
+        println!(
+            "  table {} offset {}: func {} type {}",
+            entry.table_idx, entry.offset, entry.func_idx, entry.type_idx
+        );
+    }
+
+    println!("init memory:");
+    for entry in tables.imtable.entries() {
+        println!(
+            "  {:?} [{}, {}]{}: {:?} {:#x}",
+            entry.ltype,
+            entry.start_offset,
+            entry.end_offset,
+            if entry.is_mutable { " mut" } else { "" },
+            entry.vtype,
+            entry.value
+        );
+    }
+
+    println!("static jtable:");
+    for entry in tables.static_jtable.iter() {
+        println!(
+            "  frame {} -> {}: call fid {} from fid {} iid {}{}",
+            entry.frame_id,
+            entry.next_frame_id,
+            entry.callee_fid,
+            entry.fid,
+            entry.iid,
+            if entry.enable { "" } else { " (disabled)" }
+        );
+    }
+
+    Ok(())
+}
+
+fn format_cell_usages(cells: &[CellUsage]) -> String {
+    cells
+        .iter()
+        .map(|usage| {
+            format!(
+                "{} {}/{}",
+                usage.cell_type, usage.allocated, usage.available
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Print the configuration of the circuit under K, see `CircuitInfo`.
+pub fn exec_circuit_info(zkwasm_k: u32) -> Result<()> {
+    let info = CircuitInfo::new::<Fr>(zkwasm_k);
+
+    println!("K: {}", info.k);
+    println!(
+        "columns: {} advice ({} shared by VAR_COLUMNS), {} fixed, {} instance",
+        info.advice_columns, VAR_COLUMNS, info.fixed_columns, info.instance_columns
+    );
+    println!("lookups: {}, gates: {}", info.lookups, info.gates.len());
+    println!("degree: {}", info.degree);
+
+    println!("tables:");
+    for table in info.tables.iter() {
+        println!(
+            "  {}: {} advice, {} of VAR_COLUMNS, {} fixed, {} instance, {} lookups, {} gates",
+            table.table,
+            table.advice,
+            table.var_advice,
+            table.fixed,
+            table.instance,
+            table.lookups,
+            table.gates
+        );
+    }
+
+    println!("gates:");
+    for gate in info.gates.iter() {
+        println!(
+            "  {}: {} constraints, degree {}",
+            gate.name, gate.constraints, gate.degree
+        );
+    }
+
+    println!("etable cells (allocated/available):");
+    println!(
+        "  common: {}",
+        format_cell_usages(&info.etable_common_cells)
+    );
+    for opcode in info.opcode_cells.iter() {
+        println!("  {}: {}", opcode.opcode, format_cell_usages(&opcode.cells));
+    }
+    println!("mtable cells (allocated/available):");
+    println!("  {}", format_cell_usages(&info.mtable_cells));
+
+    println!("max available rows: {}", info.max_available_rows);
+    println!("rows of each table:");
+    for (table, rows) in info.capacities.iter() {
+        println!("  {:?}: {}", table, rows);
+    }
+
     Ok(())
 }
 
@@ -103,10 +362,77 @@ pub fn exec_dry_run<Arg, Builder: HostEnvBuilder<Arg = Arg>>(
     wasm_binary: Vec<u8>,
     phantom_functions: Vec<String>,
     arg: Arg,
+    estimate: bool,
 ) -> Result<()> {
     let loader =
         ZkWasmLoader::<Bn256, Arg, Builder>::new(zkwasm_k, wasm_binary, phantom_functions)?;
-    loader.run(arg, true, false)?;
+
+    // The traced estimate is exact, while the default dry run only counts the
+    // steps of each table without tracing.
+    if estimate {
+        print_capacity_report(&loader.estimate(arg)?);
+    } else {
+        let report = loader.estimate_by_dry_run(arg)?;
+
+        println!("Estimated by the step counts of the dry run:");
+        print_capacity_report(&report);
+    }
+
+    Ok(())
+}
+
+fn print_capacity_report(report: &CapacityReport) {
+    for usage in report.usages.iter() {
+        println!(
+            "{:?}: {} / {} rows, minimal K: {}",
+            usage.table,
+            usage.required_rows,
+            usage.available_rows,
+            usage
+                .minimal_k
+                .map_or("unavailable".to_string(), |k| k.to_string())
+        );
+    }
+
+    let bottleneck = report.bottleneck();
+    match report.minimal_k() {
+        Some(k) => println!("minimal K: {}, bottleneck: {:?}", k, bottleneck.table),
+        None => println!(
+            "The trace cannot fit in any K, bottleneck: {:?}",
+            bottleneck.table
+        ),
+    }
+}
+
+pub fn exec_suggest_phantom<Arg, Builder: HostEnvBuilder<Arg = Arg>>(
+    zkwasm_k: u32,
+    wasm_binary: Vec<u8>,
+    phantom_functions: Vec<String>,
+    arg: Arg,
+) -> Result<()> {
+    let loader =
+        ZkWasmLoader::<Bn256, Arg, Builder>::new(zkwasm_k, wasm_binary, phantom_functions)?;
+    let suggestions = loader.suggest_phantom_functions(arg)?;
+
+    if suggestions.is_empty() {
+        println!("No pure function is found in the trace.");
+    }
+
+    for suggestion in suggestions {
+        println!(
+            "--phantom '{}'\tsteps: {} ({:.2}%)\tsaved etable rows: {}{}",
+            suggestion.regex,
+            suggestion.steps,
+            suggestion.share * 100f64,
+            suggestion.saved_rows,
+            if suggestion.has_return_value {
+                "\t(the return value is unconstrained, check it in the callers)"
+            } else {
+                ""
+            }
+        );
+    }
+
     Ok(())
 }
 
@@ -118,11 +444,43 @@ pub fn exec_create_proof<Arg, Builder: HostEnvBuilder<Arg = Arg>>(
     output_dir: &PathBuf,
     param_dir: &PathBuf,
     arg: Arg,
+    hash: HashType,
+    audit: bool,
 ) -> Result<()> {
+    let md5 = format!("{:X}", md5::compute(&wasm_binary));
     let loader =
-        ZkWasmLoader::<Bn256, Arg, Builder>::new(zkwasm_k, wasm_binary, phantom_functions)?;
+        ZkWasmLoader::<Bn256, Arg, Builder>::new(zkwasm_k, wasm_binary, phantom_functions)?
+            .with_audit(audit);
 
-    let (circuit, instances, _) = loader.circuit_with_witness(arg)?;
+    let (circuit, instances, outputs) = loader.circuit_with_witness(arg).map_err(|e| {
+        match e.downcast_ref::<Error>() {
+            Some(Error::Runtime(RuntimeErr::TableOverflow {
+                table,
+                required_rows,
+                available_rows,
+                minimal_k,
+            })) => {
+                error!(
+                    "{:?} requires {} rows but only {} rows are available with K = {}",
+                    table, required_rows, available_rows, zkwasm_k
+                );
+
+                match minimal_k {
+                    Some(k) => error!("Please setup and prove with a larger K, e.g. -k {}", k),
+                    None => error!("The trace is too large for a single proof, please split the execution with continuation"),
+                }
+            }
+            Some(Error::Runtime(RuntimeErr::OutOfFuel { step_limit })) => {
+                error!("The execution does not terminate within {} steps", step_limit);
+            }
+            Some(Error::Runtime(RuntimeErr::InconsistentTrace { eid, reason })) => {
+                error!("The trace is inconsistent at eid {}: {}", eid, reason);
+            }
+            _ => (),
+        }
+
+        e
+    })?;
 
     if false {
         info!("Mock test...");
@@ -130,18 +488,154 @@ pub fn exec_create_proof<Arg, Builder: HostEnvBuilder<Arg = Arg>>(
         info!("Mock test passed");
     }
 
+    info!("Outputs: {:?}", outputs);
+    #[cfg(feature = "continuation")]
+    info!("Context hashes: {:?}", circuit.context_hashes()?);
+    info!("Consumed steps: {}", circuit.consumed_steps());
+
+    let instances = circuit.instance_columns(&instances)?;
+
     let circuit: CircuitInfo<Bn256, TestCircuit<Fr>> = CircuitInfo::new(
         circuit,
         prefix.to_string(),
-        vec![instances],
+        instances,
         zkwasm_k as usize,
-        circuits_batcher::args::HashType::Poseidon,
+        hash.clone(),
     );
     circuit.proofloadinfo.save(output_dir);
     circuit.exec_create_proof(output_dir, param_dir, 0);
 
     info!("Proof has been created.");
 
+    {
+        let proofs: Vec<ProofInfo<Bn256>> =
+            ProofInfo::load_proof(&output_dir, &param_dir, &circuit.proofloadinfo);
+        let proof = &proofs[0];
+
+        let params = load_or_build_unsafe_params::<Bn256>(
+            zkwasm_k,
+            Some(&param_dir.join(format!("K{}.params", zkwasm_k))),
+        );
+        let checksum = loader.checksum(&params)?;
+
+        let bundle = ProofBundle::new(
+            zkwasm_k,
+            md5,
+            &checksum,
+            &proof.instances,
+            hash,
+            &proof.vkey,
+            &proof.transcripts,
+        )?;
+
+        let bundle_path = output_dir.join(format!("{}.bundle.json", prefix));
+        bundle.save(&bundle_path)?;
+
+        info!("Proof bundle has been written to {:?}", bundle_path);
+    }
+
+    Ok(())
+}
+
+/// Verify a proof bundle with the params in `param_dir`. The verifying key of
+/// `param_dir` and the wasm `image` with its phantom functions are optional,
+/// the bundle is checked against them if supplied.
+///
+/// The image checksum of the bundle is checked against the one of `image`,
+/// `checksum`, or the one registered in `param_dir` for the md5 of the bundle.
+pub fn exec_verify_bundle<Arg, Builder: HostEnvBuilder<Arg = Arg>>(
+    prefix: &'static str,
+    bundle_path: &PathBuf,
+    param_dir: &PathBuf,
+    image: Option<(Vec<u8>, Vec<String>)>,
+    checksum: Option<String>,
+) -> Result<()> {
+    let bundle = ProofBundle::load(bundle_path)?;
+    if bundle.zkwasm_version != ZKWASM_VERSION {
+        warn!(
+            "The bundle is written by zkwasm {}, the current version is {}",
+            bundle.zkwasm_version, ZKWASM_VERSION
+        );
+    }
+
+    let params = {
+        let params_path = param_dir.join(format!("K{}.params", bundle.k));
+        if !params_path.exists() {
+            return Err(anyhow!(
+                "Params with K = {} is not found at {:?}",
+                bundle.k,
+                params_path
+            ));
+        }
+
+        Params::<G1Affine>::read(&mut std::fs::File::open(&params_path)?)?
+    };
+
+    let loader = match image {
+        Some((wasm_binary, phantom_functions)) => {
+            let md5 = format!("{:X}", md5::compute(&wasm_binary));
+            if md5 != bundle.image_md5 {
+                return Err(anyhow!(BundleErr::ImageMismatch {
+                    expected: bundle.image_md5,
+                    found: md5,
+                }));
+            }
+
+            Some(ZkWasmLoader::<Bn256, Arg, Builder>::new(
+                bundle.k,
+                wasm_binary,
+                phantom_functions,
+            )?)
+        }
+        None => None,
+    };
+
+    let checksum = match checksum {
+        Some(checksum) => Some(checksum),
+        None => lookup_image_checksum(param_dir, &bundle.image_md5)?,
+    }
+    .map(|checksum| checksum_from_hex::<G1Affine>(&checksum))
+    .transpose()?;
+    let checksum = match &loader {
+        Some(loader) => {
+            let image_checksum = loader.checksum(&params)?;
+            if checksum.map_or(false, |checksum| checksum != image_checksum) {
+                return Err(anyhow!(BundleErr::ChecksumMismatch));
+            }
+
+            Some(image_checksum)
+        }
+        None => checksum,
+    };
+
+    bundle.verify(&params, checksum.as_ref())?;
+
+    let vk_path = param_dir.join(format!("{}.vkey.data", prefix));
+    if vk_path.exists() {
+        let mut fd = std::fs::File::open(&vk_path)?;
+        let vkey = VerifyingKey::<G1Affine>::read::<_, TestCircuit<Fr>>(&mut fd, &params)?;
+
+        if vkey_hash(&vkey)? != bundle.vkey_hash {
+            return Err(anyhow!(BundleErr::VerifyingKeyMismatch));
+        }
+    }
+
+    if let Some(loader) = loader {
+        // The context hashes and the consumed steps are public inputs of the proof
+        // rather than properties of the image, they are checked by the verifier only.
+        let instances = bundle.instances()?;
+        let public_inputs = PublicInputs::from_instance_columns(&instances)
+            .ok_or(anyhow!(BundleErr::InstancesMismatch))?;
+        if loader.instance_columns(&public_inputs)? != instances {
+            return Err(anyhow!(BundleErr::InstancesMismatch));
+        }
+    }
+
+    info!(
+        "Verifing proof bundle passed, image md5: {}, checksum: {}, vkey hash: {}",
+        bundle.image_md5, bundle.image_checksum, bundle.vkey_hash
+    );
+
     Ok(())
 }
 
@@ -176,10 +670,115 @@ pub fn exec_verify_proof(
             &proof.vkey,
             &proof.instances,
             proof.transcripts.clone(),
-            TranscriptHash::Poseidon,
+            transcript_hash(&proofloadinfo.hashtype),
         );
     }
     info!("Verifing proof passed");
 
     Ok(())
 }
+
+/// Create a proof with the keccak transcript, render the solidity verifier of
+/// the vkey from `setup` and write the calldata of the proof.
+pub fn exec_solidity_verifier<Arg, Builder: HostEnvBuilder<Arg = Arg>>(
+    prefix: &'static str,
+    zkwasm_k: u32,
+    wasm_binary: Vec<u8>,
+    phantom_functions: Vec<String>,
+    output_dir: &PathBuf,
+    param_dir: &PathBuf,
+    sol_dir: &PathBuf,
+    arg: Arg,
+) -> Result<()> {
+    let loader =
+        ZkWasmLoader::<Bn256, Arg, Builder>::new(zkwasm_k, wasm_binary, phantom_functions)?;
+
+    let params = load_or_build_unsafe_params::<Bn256>(
+        zkwasm_k,
+        Some(&param_dir.join(format!("K{}.params", zkwasm_k))),
+    );
+    let vkey = {
+        let vk_path = param_dir.join(format!("{}.vkey.data", prefix));
+        if !vk_path.exists() {
+            return Err(anyhow!(
+                "Verifying key is not found at {:?}, please run setup first",
+                vk_path
+            ));
+        }
+
+        let mut fd = std::fs::File::open(&vk_path)?;
+        VerifyingKey::<G1Affine>::read::<_, TestCircuit<Fr>>(&mut fd, &params)?
+    };
+
+    let (circuit, instances, _) = loader.circuit_with_witness(arg)?;
+    if circuit.instance_columns(&instances)?.len() != 1 {
+        return Err(anyhow!(
+            "Solidity verifier only supports circuits with a single instance column"
+        ));
+    }
+
+    let proof = loader.create_proof(
+        &params,
+        vkey.clone(),
+        circuit,
+        &instances,
+        TranscriptHash::Keccak,
+    )?;
+
+    let params_verifier: ParamsVerifier<Bn256> = params.verifier(instances.len()).unwrap();
+    render_verifier(sol_dir, &params_verifier, &vkey, &instances, proof.clone())?;
+    info!(
+        "Solidity verifier has been rendered to {:?}",
+        sol_dir.join("contracts")
+    );
+
+    let aux = generate_aux(
+        &params_verifier,
+        &vkey,
+        &instances,
+        proof.clone(),
+        &output_dir.join(format!("{}.aux.data", prefix)),
+    )?;
+
+    let calldata = encode_verify_calldata(&proof, &instances, &aux);
+    let calldata_path = output_dir.join(format!("{}.calldata", prefix));
+    std::fs::write(&calldata_path, format!("0x{}", hex::encode(calldata)))?;
+    info!(
+        "Calldata of {} has been written to {:?}",
+        VERIFY_SIGNATURE, calldata_path
+    );
+
+    Ok(())
+}
+
+/// Verify the proofs and check that they are generated from the image with `checksum`.
+#[cfg(feature = "uniform-circuit")]
+pub fn exec_verify_uniform_proof(
+    prefix: &'static str,
+    output_dir: &PathBuf,
+    param_dir: &PathBuf,
+    checksum: &String,
+) -> Result<()> {
+    use delphinus_zkwasm::loader::check_image_commitment;
+
+    exec_verify_proof(prefix, output_dir, param_dir)?;
+
+    let checksum = checksum_from_hex::<G1Affine>(checksum)?;
+
+    let load_info = output_dir.join(format!("{}.loadinfo.json", prefix));
+    let proofloadinfo = ProofLoadInfo::load(&load_info);
+    let proofs: Vec<ProofInfo<Bn256>> =
+        ProofInfo::load_proof(&output_dir, &param_dir, &proofloadinfo);
+
+    for proof in proofs.iter() {
+        check_image_commitment::<Bn256>(
+            &proof.vkey,
+            &proof.transcripts,
+            &checksum,
+            transcript_hash(&proofloadinfo.hashtype),
+        )?;
+    }
+    info!("Image checksum matches");
+
+    Ok(())
+}
@@ -1,4 +1,7 @@
 pub mod app_builder;
 pub mod args;
+pub mod bundle;
 pub mod command;
 pub mod exec;
+pub mod server;
+pub mod solidity;
@@ -1,18 +1,13 @@
 use anyhow::Result;
-use app_builder::AppBuilder;
-use args::ArgBuilder;
 use clap::value_parser;
 use clap::Arg;
 use clap::ArgAction;
 use clap::ArgMatches;
-use command::CommandBuilder;
+use delphinus_cli::app_builder::AppBuilder;
+use delphinus_cli::args::ArgBuilder;
+use delphinus_cli::command::CommandBuilder;
 use specs::args::parse_args;
 
-pub mod app_builder;
-pub mod args;
-pub mod command;
-pub mod exec;
-
 struct SampleApp;
 
 impl ArgBuilder for SampleApp {
@@ -0,0 +1,854 @@
+//! A long-running prover daemon.
+//!
+//! The server listens on a unix socket and speaks line-delimited JSON-RPC 2.0:
+//! each request is a single line of json and is answered with a single line.
+//!
+//! Supported methods:
+//! - `setup`:      `{ "wasm": <path>, "k": <u32>?, "phantom": [<regex>]? }`, returns the image id and md5.
+//! - `dry_run`:    `{ "image": <id>, "sequence": <Sequence> }`
+//! - `prove`:      `{ "image": <id>, "sequence": <Sequence>, "hash": <HashType>? }`, returns a job id.
+//! - `verify`:     `{ "image": <id>, "instances": [<hex>], "context_hashes": [<hex>, <hex>]?, "consumed_steps": <u64>, "proof": <hex>, "hash": <HashType>? }`
+//! - `job_status`: `{ "job": <u64> }`
+//!
+//! `hash` is the transcript hash of the proof, `"Poseidon"` if not supplied.
+//! `context_hashes` are the hashes of the context inputs and outputs returned
+//! by `prove`, the hashes of empty contexts if not supplied; they are only
+//! read with `continuation`. `consumed_steps` is returned by `prove` as well
+//! and only read, but then required, with `public-eid`.
+//!
+//! An image is identified by the md5 of the wasm together with K and the
+//! phantom functions, as all of them change the circuit. Params, verifying keys
+//! and proving keys are created once per K/image and kept in memory. Since the
+//! zkwasm runtime keeps K as a global, all jobs are executed sequentially by a
+//! single worker thread, which reports a panicking job as failed and moves on.
+//!
+//! The status of a finished `prove` job is dropped once it has been returned by
+//! `job_status`, or once `MAX_FINISHED_JOBS` newer jobs have finished.
+use anyhow::anyhow;
+use anyhow::Result;
+use circuits_batcher::args::HashType;
+use delphinus_zkwasm::circuits::PublicInputs;
+#[cfg(feature = "continuation")]
+use delphinus_zkwasm::foreign::context::ContextHashes;
+use delphinus_zkwasm::loader::ZkWasmLoader;
+use delphinus_zkwasm::runtime::host::ContextOutput;
+use delphinus_zkwasm::runtime::host::HostEnvBuilder;
+use delphinus_zkwasm::runtime::host::Sequence;
+use halo2_proofs::pairing::bn256::Bn256;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::plonk::ProvingKey;
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::Params;
+use log::info;
+use log::warn;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::json;
+use serde_json::Value;
+use std::any::Any;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::os::unix::net::UnixListener;
+use std::os::unix::net::UnixStream;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+
+use crate::bundle::field_from_hex;
+use crate::bundle::field_to_hex;
+use crate::exec::exec_dry_run;
+use crate::exec::exec_setup;
+use crate::exec::transcript_hash;
+
+pub struct ServerConfig {
+    pub prefix: &'static str,
+    pub aggregate_k: u32,
+    pub max_public_input_size: usize,
+    pub param_dir: PathBuf,
+    pub output_dir: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Serialize)]
+struct RpcResponse {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+#[derive(Serialize)]
+struct RpcError {
+    code: i32,
+    message: String,
+}
+
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+const EXECUTION_ERROR: i32 = -32000;
+
+const MAX_FINISHED_JOBS: usize = 1024;
+
+#[derive(Deserialize)]
+struct SetupParams {
+    wasm: PathBuf,
+    k: Option<u32>,
+    #[serde(default)]
+    phantom: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ExecuteParams {
+    image: String,
+    sequence: Sequence,
+    /// Only used by prove.
+    hash: Option<HashType>,
+}
+
+#[derive(Deserialize)]
+struct VerifyParams {
+    image: String,
+    instances: Vec<String>,
+    #[cfg(feature = "continuation")]
+    context_hashes: Option<Vec<String>>,
+    /// Steps executed by the proof.
+    #[cfg(feature = "public-eid")]
+    consumed_steps: u64,
+    proof: String,
+    hash: Option<HashType>,
+}
+
+#[derive(Deserialize)]
+struct JobStatusParams {
+    job: u64,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done { result: Value },
+    Failed { message: String },
+}
+
+impl JobStatus {
+    fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Done { .. } | JobStatus::Failed { .. })
+    }
+}
+
+#[derive(Default)]
+struct JobTable {
+    statuses: HashMap<u64, JobStatus>,
+    /// Finished jobs whose status has not been fetched, the oldest first.
+    finished: VecDeque<u64>,
+}
+
+impl JobTable {
+    fn set(&mut self, job_id: u64, status: JobStatus) {
+        if status.is_finished() {
+            self.finished.push_back(job_id);
+
+            if self.finished.len() > MAX_FINISHED_JOBS {
+                let evicted = self.finished.pop_front().unwrap();
+                self.statuses.remove(&evicted);
+            }
+        }
+
+        self.statuses.insert(job_id, status);
+    }
+
+    /// Get the status of `job_id`, a finished job is removed once fetched.
+    fn fetch(&mut self, job_id: u64) -> Option<JobStatus> {
+        let status = self.statuses.get(&job_id)?.clone();
+
+        if status.is_finished() {
+            self.statuses.remove(&job_id);
+            self.finished.retain(|id| *id != job_id);
+        }
+
+        Some(status)
+    }
+
+    fn remove(&mut self, job_id: u64) {
+        self.statuses.remove(&job_id);
+    }
+}
+
+enum Job {
+    Setup(SetupParams),
+    DryRun(ExecuteParams),
+    Prove(ExecuteParams),
+    Verify(VerifyParams),
+}
+
+struct Task {
+    job_id: u64,
+    job: Job,
+    /// Set for synchronous requests, the worker sends the outcome back once the job is finished.
+    reply: Option<mpsc::Sender<Result<Value>>>,
+}
+
+struct Image {
+    k: u32,
+    wasm: Vec<u8>,
+    phantom_functions: Vec<String>,
+    vkey: VerifyingKey<G1Affine>,
+    pkey: ProvingKey<G1Affine>,
+}
+
+struct Worker<Arg, Builder> {
+    config: ServerConfig,
+    params: HashMap<u32, Params<G1Affine>>,
+    images: HashMap<String, Image>,
+    jobs: Arc<Mutex<JobTable>>,
+    _mark: PhantomData<(Arg, Builder)>,
+}
+
+impl<Arg, Builder> Worker<Arg, Builder>
+where
+    Arg: From<Sequence> + ContextOutput,
+    Builder: HostEnvBuilder<Arg = Arg>,
+{
+    fn image(&self, id: &String) -> Result<&Image> {
+        self.images
+            .get(id)
+            .ok_or(anyhow!("image {} has not been setup", id))
+    }
+
+    fn loader(&self, image: &Image) -> Result<ZkWasmLoader<Bn256, Arg, Builder>> {
+        ZkWasmLoader::<Bn256, Arg, Builder>::new(
+            image.k,
+            image.wasm.clone(),
+            image.phantom_functions.clone(),
+        )
+    }
+
+    fn setup(&mut self, params: SetupParams) -> Result<Value> {
+        let k = params
+            .k
+            .unwrap_or(delphinus_zkwasm::circuits::config::MIN_K);
+        let wasm = fs::read(&params.wasm)?;
+        let md5 = format!("{:X}", md5::compute(&wasm));
+        let id = image_id(&md5, k, &params.phantom);
+
+        if self.images.contains_key(&id) {
+            info!("Image {} has been setup, reuse the cached keys", id);
+
+            return Ok(json!({ "image": id, "md5": md5 }));
+        }
+
+        let param_dir = self.config.param_dir.join(&id);
+        let output_dir = self.config.output_dir.join(&id);
+        fs::create_dir_all(&param_dir)?;
+        fs::create_dir_all(&output_dir)?;
+
+        exec_setup::<Arg, Builder>(
+            k,
+            self.config.aggregate_k,
+            self.config.prefix,
+            wasm.clone(),
+            params.phantom.clone(),
+            &output_dir,
+            &param_dir,
+        )?;
+
+        if !self.params.contains_key(&k) {
+            let mut fd = fs::File::open(param_dir.join(format!("K{}.params", k)))?;
+            self.params.insert(k, Params::<G1Affine>::read(&mut fd)?);
+        }
+        let params_k = self.params.get(&k).unwrap();
+
+        let loader =
+            ZkWasmLoader::<Bn256, Arg, Builder>::new(k, wasm.clone(), params.phantom.clone())?;
+        let vkey = {
+            let mut fd =
+                fs::File::open(param_dir.join(format!("{}.vkey.data", self.config.prefix)))?;
+            VerifyingKey::<G1Affine>::read::<_, delphinus_zkwasm::circuits::TestCircuit<Fr>>(
+                &mut fd, params_k,
+            )?
+        };
+        let pkey = loader.create_pkey(params_k, vkey.clone())?;
+
+        self.images.insert(
+            id.clone(),
+            Image {
+                k,
+                wasm,
+                phantom_functions: params.phantom,
+                vkey,
+                pkey,
+            },
+        );
+
+        Ok(json!({ "image": id, "md5": md5 }))
+    }
+
+    fn dry_run(&self, params: ExecuteParams) -> Result<Value> {
+        let image = self.image(&params.image)?;
+        let arg = Arg::from(params.sequence);
+        let context_outputs = arg.get_context_outputs();
+
+        exec_dry_run::<Arg, Builder>(
+            image.k,
+            image.wasm.clone(),
+            image.phantom_functions.clone(),
+            arg,
+            false,
+        )?;
+
+        let context_outputs = context_outputs.lock().unwrap().clone();
+        Ok(json!({ "context_outputs": context_outputs }))
+    }
+
+    fn prove(&self, params: ExecuteParams) -> Result<Value> {
+        let image = self.image(&params.image)?;
+        let loader = self.loader(image)?;
+        let arg = Arg::from(params.sequence);
+        let context_outputs = arg.get_context_outputs();
+
+        let (circuit, instances, outputs) = loader.circuit_with_witness(arg)?;
+        let context_hashes = circuit.context_hashes()?;
+        let consumed_steps = circuit.consumed_steps();
+        if instances.len() > self.config.max_public_input_size {
+            return Err(anyhow!(
+                "too many public inputs: {} > {}",
+                instances.len(),
+                self.config.max_public_input_size
+            ));
+        }
+
+        let hash = params.hash.unwrap_or(HashType::Poseidon);
+        let proof = loader.create_proof_with_pkey(
+            self.params.get(&image.k).unwrap(),
+            &image.pkey,
+            circuit,
+            &instances,
+            transcript_hash(&hash),
+        )?;
+
+        let instances = instances.iter().map(field_to_hex).collect::<Vec<_>>();
+        let context_hashes = context_hashes
+            .instances()
+            .iter()
+            .map(field_to_hex)
+            .collect::<Vec<_>>();
+        let context_outputs = context_outputs.lock().unwrap().clone();
+
+        Ok(json!({
+            "instances": instances,
+            "context_hashes": context_hashes,
+            "consumed_steps": consumed_steps,
+            "outputs": outputs,
+            "context_outputs": context_outputs,
+            "proof": hex::encode(proof),
+            "hash": hash,
+        }))
+    }
+
+    fn verify(&self, params: VerifyParams) -> Result<Value> {
+        let image = self.image(&params.image)?;
+        let loader = self.loader(image)?;
+
+        let instances = params
+            .instances
+            .iter()
+            .map(|instance| field_from_hex(instance))
+            .collect::<Result<Vec<_>>>()?;
+        #[cfg(feature = "continuation")]
+        let context_hashes = match params.context_hashes {
+            Some(hashes) => ContextHashes::from_instances(
+                &hashes
+                    .iter()
+                    .map(|hash| field_from_hex(hash))
+                    .collect::<Result<Vec<_>>>()?,
+            )
+            .ok_or(anyhow!(
+                "context_hashes should be the input and output hashes"
+            ))?,
+            None => ContextHashes::new(&vec![], &vec![])?,
+        };
+        let proof = hex::decode(params.proof)?;
+
+        loader.verify_proof(
+            self.params.get(&image.k).unwrap(),
+            image.vkey.clone(),
+            PublicInputs {
+                instances,
+                #[cfg(feature = "continuation")]
+                context_hashes,
+                #[cfg(feature = "public-eid")]
+                consumed_steps: params.consumed_steps,
+            },
+            proof,
+            transcript_hash(&params.hash.unwrap_or(HashType::Poseidon)),
+        )?;
+
+        Ok(json!({ "verified": true }))
+    }
+
+    fn run(mut self, receiver: mpsc::Receiver<Task>) {
+        for task in receiver {
+            self.set_status(task.job_id, JobStatus::Running);
+
+            // A panic of the job, e.g. an assertion of a chip, must not stop the worker.
+            let result = panic::catch_unwind(AssertUnwindSafe(|| match task.job {
+                Job::Setup(params) => self.setup(params),
+                Job::DryRun(params) => self.dry_run(params),
+                Job::Prove(params) => self.prove(params),
+                Job::Verify(params) => self.verify(params),
+            }))
+            .unwrap_or_else(|payload| Err(anyhow!("job panicked: {}", panic_message(payload))));
+
+            let status = match &result {
+                Ok(value) => JobStatus::Done {
+                    result: value.clone(),
+                },
+                Err(e) => {
+                    warn!("Job {} failed: {}", task.job_id, e);
+
+                    JobStatus::Failed {
+                        message: e.to_string(),
+                    }
+                }
+            };
+
+            match task.reply {
+                Some(reply) => {
+                    // The outcome is sent back directly, no one fetches the status.
+                    self.jobs.lock().unwrap().remove(task.job_id);
+
+                    // The client may have gone away, which is not an error of the job.
+                    let _ = reply.send(result);
+                }
+                None => self.set_status(task.job_id, status),
+            }
+        }
+    }
+
+    fn set_status(&self, job_id: u64, status: JobStatus) {
+        self.jobs.lock().unwrap().set(job_id, status);
+    }
+}
+
+fn image_id(md5: &str, k: u32, phantom_functions: &[String]) -> String {
+    let key = json!({ "md5": md5, "k": k, "phantom": phantom_functions });
+
+    format!("{:X}", md5::compute(key.to_string()))
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or("unknown panic".to_owned())
+}
+
+#[derive(Clone)]
+struct Queue {
+    sender: mpsc::Sender<Task>,
+    jobs: Arc<Mutex<JobTable>>,
+    next_job_id: Arc<Mutex<u64>>,
+}
+
+impl Queue {
+    fn submit(&self, job: Job, reply: Option<mpsc::Sender<Result<Value>>>) -> Result<u64> {
+        let job_id = {
+            let mut next_job_id = self.next_job_id.lock().unwrap();
+            *next_job_id += 1;
+            *next_job_id
+        };
+
+        self.jobs.lock().unwrap().set(job_id, JobStatus::Queued);
+        self.sender
+            .send(Task { job_id, job, reply })
+            .map_err(|_| anyhow!("prover worker has stopped"))?;
+
+        Ok(job_id)
+    }
+
+    fn submit_and_wait(&self, job: Job) -> Result<Value> {
+        let (sender, receiver) = mpsc::channel();
+        self.submit(job, Some(sender))?;
+
+        receiver
+            .recv()
+            .map_err(|_| anyhow!("prover worker has stopped"))?
+    }
+
+    fn dispatch(&self, request: RpcRequest) -> std::result::Result<Value, RpcError> {
+        macro_rules! params {
+            () => {
+                serde_json::from_value(request.params).map_err(|e| RpcError {
+                    code: INVALID_PARAMS,
+                    message: e.to_string(),
+                })?
+            };
+        }
+
+        let execution_error = |e: anyhow::Error| RpcError {
+            code: EXECUTION_ERROR,
+            message: e.to_string(),
+        };
+
+        match request.method.as_str() {
+            "setup" => self
+                .submit_and_wait(Job::Setup(params!()))
+                .map_err(execution_error),
+            "dry_run" => self
+                .submit_and_wait(Job::DryRun(params!()))
+                .map_err(execution_error),
+            "verify" => self
+                .submit_and_wait(Job::Verify(params!()))
+                .map_err(execution_error),
+            "prove" => self
+                .submit(Job::Prove(params!()), None)
+                .map(|job_id| json!({ "job": job_id }))
+                .map_err(execution_error),
+            "job_status" => {
+                let params: JobStatusParams = params!();
+
+                self.jobs
+                    .lock()
+                    .unwrap()
+                    .fetch(params.job)
+                    .map(|status| serde_json::to_value(status).unwrap())
+                    .ok_or(RpcError {
+                        code: INVALID_PARAMS,
+                        message: format!("job {} does not exist", params.job),
+                    })
+            }
+            method => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("method {} is not supported", method),
+            }),
+        }
+    }
+
+    fn handle_connection(&self, stream: UnixStream) -> Result<()> {
+        let mut writer = stream.try_clone()?;
+        let reader = BufReader::new(stream);
+
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match serde_json::from_str::<RpcRequest>(&line) {
+                Ok(request) => {
+                    let id = request.id.clone();
+
+                    match self.dispatch(request) {
+                        Ok(result) => RpcResponse {
+                            jsonrpc: "2.0",
+                            id,
+                            result: Some(result),
+                            error: None,
+                        },
+                        Err(error) => RpcResponse {
+                            jsonrpc: "2.0",
+                            id,
+                            result: None,
+                            error: Some(error),
+                        },
+                    }
+                }
+                Err(e) => RpcResponse {
+                    jsonrpc: "2.0",
+                    id: Value::Null,
+                    result: None,
+                    error: Some(RpcError {
+                        code: INVALID_REQUEST,
+                        message: e.to_string(),
+                    }),
+                },
+            };
+
+            writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Serve on the unix socket at `socket_path` until the process is killed.
+pub fn serve<Arg, Builder>(config: ServerConfig, socket_path: &PathBuf) -> Result<()>
+where
+    Arg: From<Sequence> + ContextOutput + 'static,
+    Builder: HostEnvBuilder<Arg = Arg> + 'static,
+{
+    if socket_path.exists() {
+        fs::remove_file(socket_path)?;
+    }
+    let listener = UnixListener::bind(socket_path)?;
+
+    let jobs = Arc::new(Mutex::new(JobTable::default()));
+    let (sender, receiver) = mpsc::channel();
+
+    {
+        let jobs = jobs.clone();
+
+        thread::spawn(move || {
+            let worker = Worker::<Arg, Builder> {
+                config,
+                params: HashMap::new(),
+                images: HashMap::new(),
+                jobs,
+                _mark: PhantomData,
+            };
+
+            worker.run(receiver)
+        });
+    }
+
+    let queue = Queue {
+        sender,
+        jobs,
+        next_job_id: Arc::new(Mutex::new(0)),
+    };
+
+    info!("zkwasm server is listening on {:?}", socket_path);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let queue = queue.clone();
+
+                thread::spawn(move || {
+                    if let Err(e) = queue.handle_connection(stream) {
+                        warn!("Connection closed with error: {}", e);
+                    }
+                });
+            }
+            Err(e) => warn!("Failed to accept connection: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
+    use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
+    use halo2_proofs::pairing::bn256::Fr;
+    use serde_json::json;
+    use serde_json::Value;
+    use std::fs;
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+    use std::path::PathBuf;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::image_id;
+    use super::serve;
+    use super::JobStatus;
+    use super::JobTable;
+    use super::ServerConfig;
+    use super::EXECUTION_ERROR;
+    use super::INVALID_PARAMS;
+    use super::MAX_FINISHED_JOBS;
+    use super::METHOD_NOT_FOUND;
+    use crate::bundle::field_to_hex;
+
+    const K: u32 = 18;
+
+    struct Client {
+        reader: BufReader<UnixStream>,
+        writer: UnixStream,
+        next_id: u64,
+    }
+
+    impl Client {
+        fn connect(socket_path: &PathBuf) -> Client {
+            // The server binds the socket asynchronously.
+            let stream = loop {
+                match UnixStream::connect(socket_path) {
+                    Ok(stream) => break stream,
+                    Err(_) => thread::sleep(Duration::from_millis(10)),
+                }
+            };
+
+            Client {
+                reader: BufReader::new(stream.try_clone().unwrap()),
+                writer: stream,
+                next_id: 0,
+            }
+        }
+
+        fn call(&mut self, method: &str, params: Value) -> Value {
+            self.next_id += 1;
+
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": self.next_id,
+                "method": method,
+                "params": params,
+            });
+            writeln!(self.writer, "{}", request).unwrap();
+
+            let mut line = String::new();
+            self.reader.read_line(&mut line).unwrap();
+            let response: Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(response["id"], json!(self.next_id));
+
+            response
+        }
+
+        fn wait_job(&mut self, job: &Value) -> Value {
+            loop {
+                let status = self.call("job_status", json!({ "job": job }))["result"].clone();
+
+                match status["status"].as_str().unwrap() {
+                    "queued" | "running" => thread::sleep(Duration::from_millis(100)),
+                    _ => return status,
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_server_rpc_round_trip() {
+        let dir = std::env::temp_dir().join(format!("zkwasm-server-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let wasm = wabt::wat2wasm(
+            r#"
+            (module
+                (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+                (func (export "zkmain")
+                  (drop (i64.add (call $wasm_input (i32.const 1)) (i64.const 1))))
+            )
+            "#,
+        )
+        .unwrap();
+        let wasm_path = dir.join("image.wasm");
+        fs::write(&wasm_path, &wasm).unwrap();
+
+        let socket_path = dir.join("zkwasm.sock");
+        {
+            let config = ServerConfig {
+                prefix: "zkwasm",
+                aggregate_k: K,
+                max_public_input_size: 64,
+                param_dir: dir.join("params"),
+                output_dir: dir.join("output"),
+            };
+            let socket_path = socket_path.clone();
+
+            thread::spawn(move || {
+                serve::<ExecutionArg, DefaultHostEnvBuilder>(config, &socket_path).unwrap()
+            });
+        }
+        let mut client = Client::connect(&socket_path);
+
+        let response = client.call("no_such_method", json!({}));
+        assert_eq!(response["error"]["code"], json!(METHOD_NOT_FOUND));
+
+        let response = client.call("setup", json!({ "wasm": wasm_path, "k": K }));
+        let image = response["result"]["image"].clone();
+        let md5 = format!("{:X}", md5::compute(&wasm));
+        assert_eq!(response["result"]["md5"], json!(md5));
+        assert_eq!(image, json!(image_id(&md5, K, &[])));
+        assert_ne!(image, json!(image_id(&md5, K + 1, &[])));
+
+        // The second setup of the same image reuses the cached keys.
+        let response = client.call("setup", json!({ "wasm": wasm_path }));
+        assert_eq!(response["result"]["image"], image);
+
+        let sequence = json!({
+            "private_inputs": [],
+            "public_inputs": ["41:i64"],
+            "context_input": [],
+            "context_output": null,
+        });
+        let response = client.call("prove", json!({ "image": image, "sequence": sequence }));
+        let job = response["result"]["job"].clone();
+
+        let status = client.wait_job(&job);
+        assert_eq!(status["status"], json!("done"), "{}", status);
+        let proof = status["result"].clone();
+
+        // The status of a finished job is dropped once fetched.
+        let response = client.call("job_status", json!({ "job": job }));
+        assert_eq!(response["error"]["code"], json!(INVALID_PARAMS));
+
+        let verify = |proof: &Value| {
+            json!({
+                "image": image,
+                "instances": proof["instances"],
+                "context_hashes": proof["context_hashes"],
+                "consumed_steps": proof["consumed_steps"],
+                "proof": proof["proof"],
+                "hash": proof["hash"],
+            })
+        };
+
+        let response = client.call("verify", verify(&proof));
+        assert_eq!(response["result"]["verified"], json!(true), "{}", response);
+
+        let mut bad_proof = proof.clone();
+        bad_proof["instances"] = json!([field_to_hex(&Fr::from(42u64))]);
+        let response = client.call("verify", verify(&bad_proof));
+        assert_eq!(response["error"]["code"], json!(EXECUTION_ERROR));
+
+        let mut bad_proof = proof.clone();
+        bad_proof["proof"] = json!("00");
+        let response = client.call("verify", verify(&bad_proof));
+        assert_eq!(response["error"]["code"], json!(EXECUTION_ERROR));
+
+        // The worker survives bad proofs.
+        let response = client.call("verify", verify(&proof));
+        assert_eq!(response["result"]["verified"], json!(true), "{}", response);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_job_table_evicts_finished_jobs() {
+        let mut jobs = JobTable::default();
+
+        for job_id in 0..=MAX_FINISHED_JOBS as u64 {
+            jobs.set(job_id, JobStatus::Queued);
+            jobs.set(
+                job_id,
+                JobStatus::Failed {
+                    message: "".to_owned(),
+                },
+            );
+        }
+        jobs.set(MAX_FINISHED_JOBS as u64 + 1, JobStatus::Running);
+
+        assert!(jobs.fetch(0).is_none());
+        assert!(jobs.fetch(1).is_some());
+        assert!(jobs.fetch(1).is_none());
+        assert!(jobs.fetch(MAX_FINISHED_JOBS as u64 + 1).is_some());
+        assert!(jobs.fetch(MAX_FINISHED_JOBS as u64 + 1).is_some());
+    }
+}
@@ -0,0 +1,401 @@
+//! Solidity verifier of a single zkWasm proof.
+//!
+//! The contracts are rendered from the halo2aggregator-s templates and
+//! verify proofs created with the Keccak transcript.
+//!
+//! `test_single_proof_solidity_verifier` checks the contracts on revm, it is
+//! built with the `solidity-test` feature as it needs solc and the templates,
+//! the abi encoding is checked without them.
+
+use anyhow::Result;
+use halo2_proofs::arithmetic::BaseExt;
+use halo2_proofs::pairing::bn256::Bn256;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::ParamsVerifier;
+use halo2aggregator_s::solidity_verifier::codegen::solidity_aux_gen;
+use halo2aggregator_s::solidity_verifier::solidity_render;
+use sha3::Digest;
+use sha3::Keccak256;
+use std::path::PathBuf;
+
+pub const VERIFY_SIGNATURE: &str = "verify(uint256[],uint256[],uint256[],uint256[][])";
+
+/// Render the verifier contracts from `sol_dir/templates` into `sol_dir/contracts`.
+pub fn render_verifier(
+    sol_dir: &PathBuf,
+    params_verifier: &ParamsVerifier<Bn256>,
+    vkey: &VerifyingKey<G1Affine>,
+    instances: &Vec<Fr>,
+    proof: Vec<u8>,
+) -> Result<()> {
+    let path_in = sol_dir.join("templates");
+    let path_out = sol_dir.join("contracts");
+
+    std::fs::create_dir_all(&path_out)?;
+
+    solidity_render(
+        &(path_in.to_str().unwrap().to_owned() + "/*"),
+        path_out.to_str().unwrap(),
+        vec![(
+            "AggregatorConfig.sol.tera".to_owned(),
+            "AggregatorConfig.sol".to_owned(),
+        )],
+        "AggregatorVerifierStepStart.sol.tera",
+        "AggregatorVerifierStepEnd.sol.tera",
+        |i| format!("AggregatorVerifierStep{}.sol", i + 1),
+        params_verifier,
+        vkey,
+        instances,
+        proof,
+    );
+
+    Ok(())
+}
+
+/// Generate the aux data of the proof and return its words.
+pub fn generate_aux(
+    params_verifier: &ParamsVerifier<Bn256>,
+    vkey: &VerifyingKey<G1Affine>,
+    instances: &Vec<Fr>,
+    proof: Vec<u8>,
+    aux_path: &PathBuf,
+) -> Result<Vec<Fr>> {
+    solidity_aux_gen(params_verifier, vkey, instances, proof, aux_path);
+
+    let bytes = std::fs::read(aux_path)?;
+    bytes
+        .chunks(32)
+        .map(|mut chunk| Ok(Fr::read(&mut chunk)?))
+        .collect()
+}
+
+fn u256_from_usize(value: usize) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+    word
+}
+
+/// Field elements and proof words are little endian, uint256 is big endian in abi.
+fn u256_from_le_bytes(bytes: &[u8]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    for (i, byte) in bytes.iter().enumerate() {
+        word[31 - i] = *byte;
+    }
+    word
+}
+
+fn u256_from_field(value: &Fr) -> [u8; 32] {
+    let mut bytes = vec![];
+    value.write(&mut bytes).unwrap();
+    u256_from_le_bytes(&bytes)
+}
+
+fn encode_array(words: &Vec<[u8; 32]>) -> Vec<u8> {
+    let mut buf = u256_from_usize(words.len()).to_vec();
+    for word in words {
+        buf.extend_from_slice(word);
+    }
+    buf
+}
+
+fn encode_nested_array(arrays: &Vec<Vec<[u8; 32]>>) -> Vec<u8> {
+    let encoded = arrays.iter().map(encode_array).collect::<Vec<_>>();
+
+    let mut buf = u256_from_usize(arrays.len()).to_vec();
+    let mut offset = arrays.len() * 32;
+    for array in encoded.iter() {
+        buf.extend_from_slice(&u256_from_usize(offset));
+        offset += array.len();
+    }
+    for array in encoded {
+        buf.extend(array);
+    }
+    buf
+}
+
+pub fn function_selector(signature: &str) -> [u8; 4] {
+    let hash = Keccak256::digest(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Abi encoded calldata of `verify(proof, verify_instance, aux, target_instance)`.
+pub fn encode_verify_calldata(proof: &Vec<u8>, instances: &Vec<Fr>, aux: &Vec<Fr>) -> Vec<u8> {
+    let instances = instances.iter().map(u256_from_field).collect::<Vec<_>>();
+
+    let tails = vec![
+        encode_array(&proof.chunks(32).map(u256_from_le_bytes).collect()),
+        encode_array(&instances),
+        encode_array(&aux.iter().map(u256_from_field).collect()),
+        encode_nested_array(&vec![instances.clone()]),
+    ];
+
+    let mut calldata = function_selector(VERIFY_SIGNATURE).to_vec();
+    let mut offset = tails.len() * 32;
+    for tail in tails.iter() {
+        calldata.extend_from_slice(&u256_from_usize(offset));
+        offset += tail.len();
+    }
+    for tail in tails {
+        calldata.extend(tail);
+    }
+
+    calldata
+}
+
+#[cfg(test)]
+mod abi_tests {
+    use super::*;
+
+    fn u256(value: u64) -> [u8; 32] {
+        u256_from_usize(value as usize)
+    }
+
+    fn words(bytes: &[u8]) -> Vec<[u8; 32]> {
+        assert_eq!(bytes.len() % 32, 0);
+
+        bytes
+            .chunks(32)
+            .map(|chunk| chunk.try_into().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_function_selector() {
+        assert_eq!(
+            function_selector("transfer(address,uint256)"),
+            [0xa9, 0x05, 0x9c, 0xbb]
+        );
+    }
+
+    #[test]
+    fn test_encode_nested_array() {
+        let a = u256(1);
+        let b = u256(2);
+        let c = u256(3);
+
+        assert_eq!(
+            words(&encode_nested_array(&vec![vec![a, b], vec![c]])),
+            vec![
+                u256(2),
+                // offsets from the start of the offsets
+                u256(2 * 32),
+                u256(2 * 32 + 3 * 32),
+                u256(2),
+                a,
+                b,
+                u256(1),
+                c,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_encode_verify_calldata() {
+        let proof = (0u8..64).collect::<Vec<_>>();
+        let instances = vec![Fr::from(1u64)];
+        let aux = vec![Fr::from(0x0102u64)];
+
+        let calldata = encode_verify_calldata(&proof, &instances, &aux);
+        assert_eq!(calldata[..4], function_selector(VERIFY_SIGNATURE));
+
+        let mut proof_word_0 = [0u8; 32];
+        let mut proof_word_1 = [0u8; 32];
+        for i in 0..32 {
+            proof_word_0[i] = 31 - i as u8;
+            proof_word_1[i] = 63 - i as u8;
+        }
+        let mut aux_word = [0u8; 32];
+        aux_word[30] = 0x01;
+        aux_word[31] = 0x02;
+
+        assert_eq!(
+            words(&calldata[4..]),
+            vec![
+                // heads
+                u256(4 * 32),
+                u256(4 * 32 + 3 * 32),
+                u256(4 * 32 + 3 * 32 + 2 * 32),
+                u256(4 * 32 + 3 * 32 + 2 * 32 + 2 * 32),
+                // proof
+                u256(2),
+                proof_word_0,
+                proof_word_1,
+                // verify instances
+                u256(1),
+                u256(1),
+                // aux
+                u256(1),
+                aux_word,
+                // target instances
+                u256(1),
+                u256(32),
+                u256(1),
+                u256(1),
+            ]
+        );
+    }
+}
+
+// The solidity verifier supports circuits with a single instance column.
+#[cfg(all(test, feature = "solidity-test", not(feature = "uniform-circuit")))]
+mod tests {
+    use super::*;
+
+    use delphinus_zkwasm::circuits::TestCircuit;
+    use delphinus_zkwasm::loader::ZkWasmLoader;
+    use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
+    use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
+    use halo2_proofs::poly::commitment::Params;
+    use halo2aggregator_s::circuits::utils::load_or_create_proof;
+    use halo2aggregator_s::circuits::utils::TranscriptHash;
+    use revm::primitives::Bytes;
+    use revm::primitives::ExecutionResult;
+    use revm::primitives::Output;
+    use revm::primitives::TransactTo;
+    use revm::primitives::B160;
+    use revm::InMemoryDB;
+    use revm::EVM;
+    use std::collections::BTreeMap;
+    use std::process::Command;
+
+    const K: u32 = 18;
+
+    fn compile_contracts(dir: &PathBuf) -> BTreeMap<String, Vec<u8>> {
+        let sources = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "sol"))
+            .collect::<Vec<_>>();
+
+        let output = Command::new("solc")
+            .arg("--optimize")
+            .arg("--combined-json")
+            .arg("bin")
+            .arg("--base-path")
+            .arg(dir)
+            .args(&sources)
+            .output()
+            .expect("solc is required");
+        assert!(output.status.success());
+
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        json["contracts"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(name, contract)| {
+                (
+                    name.rsplit(':').next().unwrap().to_owned(),
+                    hex::decode(contract["bin"].as_str().unwrap()).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    fn transact(evm: &mut EVM<InMemoryDB>, to: TransactTo, data: Vec<u8>) -> ExecutionResult {
+        evm.env.tx.caller = B160::from_low_u64_be(1);
+        evm.env.tx.transact_to = to;
+        evm.env.tx.data = Bytes::from(data);
+        evm.env.tx.gas_limit = u64::MAX;
+        evm.transact_commit().unwrap()
+    }
+
+    fn deploy(evm: &mut EVM<InMemoryDB>, bytecode: Vec<u8>) -> B160 {
+        match transact(evm, TransactTo::create(), bytecode) {
+            ExecutionResult::Success {
+                output: Output::Create(_, Some(address)),
+                ..
+            } => address,
+            result => panic!("deployment failed: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_single_proof_solidity_verifier() {
+        let sol_dir = PathBuf::from(std::env::var("ZKWASM_SOL_DIR").unwrap_or("sol".to_owned()));
+        let wasm = wabt::wat2wasm(
+            r#"
+            (module
+                (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+                (func (export "zkmain")
+                  (drop (i64.add (call $wasm_input (i32.const 1)) (i64.const 1))))
+            )
+            "#,
+        )
+        .unwrap();
+
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(K, wasm, vec![])
+                .unwrap();
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let vkey = loader.create_vkey(&params).unwrap();
+
+        let (circuit, instances, _) = loader
+            .circuit_with_witness(ExecutionArg {
+                public_inputs: vec![41],
+                ..Default::default()
+            })
+            .unwrap();
+        let proof = load_or_create_proof::<Bn256, TestCircuit<Fr>>(
+            &params,
+            vkey.clone(),
+            circuit,
+            &[&instances],
+            None,
+            TranscriptHash::Keccak,
+            false,
+        );
+
+        let params_verifier: ParamsVerifier<Bn256> = params.verifier(instances.len()).unwrap();
+        render_verifier(&sol_dir, &params_verifier, &vkey, &instances, proof.clone()).unwrap();
+        let aux = generate_aux(
+            &params_verifier,
+            &vkey,
+            &instances,
+            proof.clone(),
+            &sol_dir.join("aux.data"),
+        )
+        .unwrap();
+
+        let contracts = compile_contracts(&sol_dir.join("contracts"));
+
+        let mut evm = EVM::new();
+        evm.database(InMemoryDB::default());
+
+        let mut steps = contracts
+            .iter()
+            .filter_map(|(name, bytecode)| {
+                name.strip_prefix("AggregatorVerifierStep")
+                    .and_then(|i| i.parse::<usize>().ok())
+                    .map(|i| (i, bytecode.clone()))
+            })
+            .collect::<Vec<_>>();
+        steps.sort_by_key(|(i, _)| *i);
+        let steps = steps
+            .into_iter()
+            .map(|(_, bytecode)| deploy(&mut evm, bytecode))
+            .collect::<Vec<_>>();
+
+        // constructor(address[] steps)
+        let mut bytecode = contracts["AggregatorVerifier"].clone();
+        bytecode.extend_from_slice(&u256_from_usize(32));
+        bytecode.extend_from_slice(&u256_from_usize(steps.len()));
+        for step in steps {
+            let mut word = [0u8; 32];
+            word[12..].copy_from_slice(step.as_bytes());
+            bytecode.extend_from_slice(&word);
+        }
+        let verifier = deploy(&mut evm, bytecode);
+
+        let calldata = encode_verify_calldata(&proof, &instances, &aux);
+        let result = transact(&mut evm, TransactTo::Call(verifier), calldata);
+        assert!(result.is_success(), "{:?}", result);
+
+        let tampered = vec![instances[0] + Fr::from(1u64)];
+        let calldata = encode_verify_calldata(&proof, &tampered, &aux);
+        let result = transact(&mut evm, TransactTo::Call(verifier), calldata);
+        assert!(!result.is_success());
+    }
+}
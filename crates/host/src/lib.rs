@@ -6,7 +6,7 @@ use delphinus_zkwasm::foreign::context::runtime::register_context_foreign;
 use delphinus_zkwasm::foreign::log_helper::register_log_foreign;
 use delphinus_zkwasm::foreign::require_helper::register_require_foreign;
 use delphinus_zkwasm::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
-use delphinus_zkwasm::runtime::wasmi_interpreter::WasmRuntimeIO;
+use delphinus_zkwasm::runtime::WasmRuntimeIO;
 
 use delphinus_zkwasm::runtime::host::host_env::HostEnv;
 use delphinus_zkwasm::runtime::host::HostEnvBuilder;
@@ -25,6 +25,8 @@ pub struct ExecutionArg {
     pub context_outputs: Arc<Mutex<Vec<u64>>>,
     /// db src
     pub tree_db: Option<Rc<RefCell<dyn TreeDB>>>,
+    /// The maximal steps of the execution, unlimited if None
+    pub step_limit: Option<u64>,
 }
 
 pub struct StandardHostEnvBuilder;
@@ -50,8 +52,9 @@ impl HostEnvBuilder for StandardHostEnvBuilder {
 
     fn create_env(arg: Self::Arg) -> (HostEnv, WasmRuntimeIO) {
         let mut env = HostEnv::new();
-        let wasm_runtime_io =
+        let mut wasm_runtime_io =
             register_wasm_input_foreign(&mut env, arg.public_inputs, arg.private_inputs);
+        wasm_runtime_io.step_limit = arg.step_limit;
         register_require_foreign(&mut env);
         register_log_foreign(&mut env);
         register_context_foreign(&mut env, arg.context_inputs, arg.context_outputs);
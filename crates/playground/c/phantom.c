@@ -10,6 +10,10 @@
  * Since the phantom function will not produce any traces, memory/global
  * writing is invisible to prover,
  * *** the function MUST NOT have these operations ***.
+ *
+ * For the same reason its return value is not constrained, so the function
+ * can only be phantom with the `phantom-hint` feature and the caller
+ * MUST check it, as zkmain does with `require`.
  */
 __attribute__((noinline)) int search(int *arr, int size, int v)
 {
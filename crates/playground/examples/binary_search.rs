@@ -1,6 +1,3 @@
-use std::sync::Arc;
-use std::sync::Mutex;
-
 use anyhow::Result;
 use delphinus_zkwasm::loader::ZkWasmLoader;
 use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
@@ -14,9 +11,7 @@ fn main() -> Result<()> {
 
     let (circuit, instances, _) = loader.circuit_with_witness(ExecutionArg {
         public_inputs: vec![0],
-        private_inputs: vec![],
-        context_inputs: vec![],
-        context_outputs: Arc::new(Mutex::new(vec![])),
+        ..Default::default()
     })?;
     loader.mock_test(&circuit, &instances)
 }
@@ -15,20 +15,17 @@ fn main() -> Result<()> {
 
     let loader = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(18, wasm, vec![])?;
     let arg = ExecutionArg {
-        public_inputs: vec![],
-        private_inputs: vec![],
         context_inputs: context_in,
         context_outputs: context_outputs.clone(),
+        ..Default::default()
     };
 
     let (circuit, instances, _) = loader.circuit_with_witness(arg)?;
     loader.mock_test(&circuit, &instances)?;
 
     let arg = ExecutionArg {
-        public_inputs: vec![],
-        private_inputs: vec![],
         context_inputs: context_outputs.lock().unwrap().to_vec(),
-        context_outputs: Arc::new(Mutex::new(vec![])),
+        ..Default::default()
     };
 
     let (circuit, instances, _) = loader.circuit_with_witness(arg)?;
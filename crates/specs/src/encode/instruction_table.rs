@@ -10,10 +10,10 @@ use super::FromBn;
 
 pub(crate) const INSTRUCTION_ENCODE_BOUNDARY: u32 = 224;
 
-pub fn encode_instruction_table_entry<T: FromBn>(fid: T, iid: T, opcode: T) -> T {
-    const FID_SHIFT: u32 = IID_SHIFT + COMMON_RANGE_OFFSET;
-    const IID_SHIFT: u32 = OPCODE_SHIFT;
+const FID_SHIFT: u32 = IID_SHIFT + COMMON_RANGE_OFFSET;
+const IID_SHIFT: u32 = OPCODE_SHIFT;
 
+pub fn encode_instruction_table_entry<T: FromBn>(fid: T, iid: T, opcode: T) -> T {
     assert!(FID_SHIFT + COMMON_RANGE_OFFSET <= INSTRUCTION_ENCODE_BOUNDARY);
 
     fid * T::from_bn(&(1u64.to_biguint().unwrap() << FID_SHIFT))
@@ -21,6 +21,20 @@ pub fn encode_instruction_table_entry<T: FromBn>(fid: T, iid: T, opcode: T) -> T
         + opcode
 }
 
+/// Split the encoding of an instruction table entry into its fid, iid and
+/// opcode encoding, see `Opcode::try_from` to decode the latter.
+pub fn decode_instruction_table_entry(encode: &BigUint) -> (u32, u32, BigUint) {
+    let field =
+        |shift: u32, width: u32| (encode >> shift) & ((1u64.to_biguint().unwrap() << width) - 1u64);
+    let to_u32 = |bn: BigUint| bn.iter_u32_digits().next().unwrap_or(0);
+
+    (
+        to_u32(field(FID_SHIFT, COMMON_RANGE_OFFSET)),
+        to_u32(field(IID_SHIFT, COMMON_RANGE_OFFSET)),
+        field(0, OPCODE_SHIFT),
+    )
+}
+
 impl InstructionTableEntry {
     pub(crate) fn encode(fid: u32, iid: u32, opcode: &Opcode) -> BigUint {
         encode_instruction_table_entry(BigUint::from(fid), BigUint::from(iid), opcode.into())
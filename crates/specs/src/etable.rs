@@ -65,12 +65,12 @@ impl EventTable {
 
     pub fn filter_foreign_entries(&self, foreign: HostPlugin) -> Vec<EventTableEntry> {
         self.0
-            .clone()
-            .into_iter()
+            .iter()
             .filter(|entry| match entry.step_info {
                 StepInfo::CallHost { plugin, .. } => plugin == foreign,
                 _ => false,
             })
+            .cloned()
             .collect::<Vec<_>>()
     }
 }
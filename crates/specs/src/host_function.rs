@@ -1,5 +1,6 @@
 use serde::Deserialize;
 use serde::Serialize;
+use strum_macros::EnumIter;
 
 use crate::external_host_call_table::ExternalHostCallSignature;
 use crate::types::ValueType;
@@ -39,7 +40,9 @@ impl HostFunctionDesc {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(
+    Clone, Debug, Serialize, Deserialize, Copy, EnumIter, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
 pub enum HostPlugin {
     HostInput = 0,
     Context,
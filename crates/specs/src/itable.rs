@@ -20,6 +20,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::fmt::Debug;
 use std::sync::Arc;
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 #[derive(Clone, Copy, Debug, EnumIter, Eq, PartialEq, PartialOrd, Ord, Hash)]
@@ -108,14 +109,14 @@ impl OpcodeClassPlain {
     }
 }
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
 pub enum UnaryOp {
     Ctz,
     Clz,
     Popcnt,
 }
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
 pub enum BinOp {
     Add,
     Sub,
@@ -126,7 +127,7 @@ pub enum BinOp {
     SignedRem,
 }
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ShiftOp {
     Shl,
     UnsignedShr,
@@ -152,7 +153,7 @@ impl BitOp {
     }
 }
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
 pub enum RelOp {
     Eq,
     Ne,
@@ -171,7 +172,7 @@ pub enum TestOp {
     Eqz,
 }
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ConversionOp {
     I32WrapI64,
     I64ExtendI32s,
@@ -540,6 +541,197 @@ impl Into<BigUint> for &Opcode {
     }
 }
 
+#[derive(Debug)]
+pub struct OpcodeDecodeError(pub BigUint);
+
+const MAX_DECODED_BR_TABLE_TARGETS: u64 = u16::MAX as u64;
+
+fn decode_field(bn: &BigUint, shift: u32, width: u32) -> u64 {
+    let mask = (BigUint::from(1u64) << width) - 1u64;
+
+    ((bn >> shift) & mask).iter_u64_digits().next().unwrap_or(0)
+}
+
+fn decode_enum<T: IntoEnumIterator + Copy>(index: u64, discriminant: fn(T) -> u64) -> Option<T> {
+    T::iter().find(|t| discriminant(*t) == index)
+}
+
+fn decode_opcode(bn: &BigUint) -> Option<Opcode> {
+    let class = decode_field(bn, OPCODE_CLASS_SHIFT, OPCODE_SHIFT - OPCODE_CLASS_SHIFT);
+    let arg0 = decode_field(bn, OPCODE_ARG0_SHIFT, COMMON_RANGE_OFFSET);
+    let arg1 = decode_field(bn, OPCODE_ARG1_SHIFT, COMMON_RANGE_OFFSET);
+    let low = decode_field(bn, 0, OPCODE_ARG1_SHIFT);
+
+    // `encode_br_table` leaves the class unshifted.
+    if class == 0 {
+        let len = low.checked_sub(OpcodeClass::BrTable as u64)?;
+        // Rather than allocating the placeholders of an absurd table.
+        if len > MAX_DECODED_BR_TABLE_TARGETS {
+            return None;
+        }
+
+        return Some(Opcode::BrTable {
+            targets: vec![
+                BrTarget {
+                    drop: 0,
+                    keep: vec![],
+                    dst_pc: 0,
+                };
+                len as usize
+            ],
+        });
+    }
+
+    if class >= OpcodeClass::ForeignPluginStart as u64 {
+        let plugin = decode_enum(
+            class - OpcodeClass::ForeignPluginStart as u64,
+            |plugin: HostPlugin| plugin as u64,
+        )?;
+
+        return Some(Opcode::InternalHostCall {
+            plugin,
+            function_index: 0,
+            function_name: String::new(),
+            op_index_in_plugin: low as usize,
+        });
+    }
+
+    let vtype = |index| decode_enum(index, |vtype: VarType| vtype as u64);
+    let value_types = |len: u64, first: Option<ValueType>| -> Vec<ValueType> {
+        (0..len)
+            .map(|i| match first {
+                Some(vtype) if i == 0 => vtype,
+                _ => ValueType::I64,
+            })
+            .collect()
+    };
+
+    let opcode = match decode_enum(class, |class: OpcodeClass| class as u64)? {
+        OpcodeClass::LocalGet => Opcode::LocalGet {
+            vtype: vtype(arg0)?,
+            offset: low,
+        },
+        OpcodeClass::LocalSet => Opcode::LocalSet {
+            vtype: vtype(arg0)?,
+            offset: low,
+        },
+        OpcodeClass::LocalTee => Opcode::LocalTee {
+            vtype: vtype(arg0)?,
+            offset: low,
+        },
+        OpcodeClass::GlobalGet => Opcode::GlobalGet { idx: low },
+        OpcodeClass::GlobalSet => Opcode::GlobalSet { idx: low },
+        OpcodeClass::Const => Opcode::Const {
+            vtype: vtype(arg0)?,
+            value: low,
+        },
+        OpcodeClass::Drop => Opcode::Drop,
+        OpcodeClass::Select => Opcode::Select,
+        OpcodeClass::Return => Opcode::Return {
+            drop: arg0 as u32,
+            keep: value_types(
+                arg1,
+                Some(match vtype(low)? {
+                    VarType::I32 => ValueType::I32,
+                    VarType::I64 => ValueType::I64,
+                }),
+            ),
+        },
+        OpcodeClass::Bin => Opcode::Bin {
+            class: decode_enum(arg0, |class: BinOp| class as u64)?,
+            vtype: vtype(arg1)?,
+        },
+        OpcodeClass::Unary => Opcode::Unary {
+            class: decode_enum(arg0, |class: UnaryOp| class as u64)?,
+            vtype: vtype(arg1)?,
+        },
+        OpcodeClass::BinShift => Opcode::BinShift {
+            class: decode_enum(arg0, |class: ShiftOp| class as u64)?,
+            vtype: vtype(arg1)?,
+        },
+        OpcodeClass::BinBit => Opcode::BinBit {
+            class: decode_enum(arg0, |class: BitOp| class as u64)?,
+            vtype: vtype(arg1)?,
+        },
+        OpcodeClass::Test => Opcode::Test {
+            class: decode_enum(arg0, |class: TestOp| class as u64)?,
+            vtype: vtype(arg1)?,
+        },
+        OpcodeClass::Rel => Opcode::Rel {
+            class: decode_enum(arg0, |class: RelOp| class as u64)?,
+            vtype: vtype(arg1)?,
+        },
+        OpcodeClass::Br => Opcode::Br {
+            drop: arg0 as u32,
+            keep: value_types(arg1, None),
+            dst_pc: low as u32,
+        },
+        OpcodeClass::BrIf => Opcode::BrIf {
+            drop: arg0 as u32,
+            keep: value_types(arg1, None),
+            dst_pc: low as u32,
+        },
+        OpcodeClass::BrIfEqz => Opcode::BrIfEqz {
+            drop: arg0 as u32,
+            keep: value_types(arg1, None),
+            dst_pc: low as u32,
+        },
+        OpcodeClass::Unreachable => Opcode::Unreachable,
+        OpcodeClass::Call => Opcode::Call { index: arg0 as u32 },
+        OpcodeClass::CallHost => Opcode::ExternalHostCall {
+            op: arg0 as usize,
+            sig: if arg1 == 0 {
+                ExternalHostCallSignature::Argument
+            } else {
+                ExternalHostCallSignature::Return
+            },
+        },
+        OpcodeClass::CallIndirect => Opcode::CallIndirect {
+            type_idx: arg0 as u32,
+        },
+        OpcodeClass::Load => Opcode::Load {
+            offset: low as u32,
+            vtype: vtype(arg0)?,
+            size: decode_enum(arg1, |size: MemoryReadSize| size as u64)?,
+        },
+        OpcodeClass::Store => Opcode::Store {
+            offset: low as u32,
+            vtype: vtype(arg0)?,
+            size: decode_enum(arg1, |size: MemoryStoreSize| size as u64)?,
+        },
+        OpcodeClass::MemorySize => Opcode::MemorySize,
+        OpcodeClass::MemoryGrow => Opcode::MemoryGrow,
+        OpcodeClass::Conversion => ConversionOp::iter()
+            .map(|class| Opcode::Conversion { class })
+            .find(|opcode| Into::<BigUint>::into(opcode) == *bn)?,
+        OpcodeClass::BrTable | OpcodeClass::ForeignPluginStart => return None,
+    };
+
+    Some(opcode)
+}
+
+/// Decode the image table encoding of an opcode.
+///
+/// Only what the encoding commits to is recovered: the targets of `br_table`
+/// are committed by the br table instead and decode as placeholders, the types
+/// of the values kept by branches (but the first one of `return`) decode as
+/// i64, and the name and index of an internal host function are left empty.
+/// Re-encoding the decoded opcode gives back the same encoding.
+impl TryFrom<&BigUint> for Opcode {
+    type Error = OpcodeDecodeError;
+
+    fn try_from(bn: &BigUint) -> Result<Self, Self::Error> {
+        if *bn >= BigUint::from(1u64) << OPCODE_SHIFT {
+            return Err(OpcodeDecodeError(bn.clone()));
+        }
+
+        // Any bit outside of the fields makes the round trip fail.
+        decode_opcode(bn)
+            .filter(|opcode| Into::<BigUint>::into(opcode) == *bn)
+            .ok_or_else(|| OpcodeDecodeError(bn.clone()))
+    }
+}
+
 impl Into<OpcodeClass> for &Opcode {
     fn into(self) -> OpcodeClass {
         match self {
@@ -652,6 +844,14 @@ impl InstructionTable {
         &self.0[fid as usize][iid as usize]
     }
 
+    /// Like `get`, but None if `fid` or `iid` is out of the table.
+    pub fn try_get(&self, fid: u32, iid: u32) -> Option<&InstructionTableEntry> {
+        self.0
+            .get(fid as usize)
+            .and_then(|function| function.get(iid as usize))
+            .and_then(|entry| entry.as_ref())
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &InstructionTableEntry> {
         self.0.iter().flatten().filter_map(|e| e.as_ref())
     }
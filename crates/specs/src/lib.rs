@@ -14,6 +14,7 @@ use itable::InstructionTable;
 use jtable::JumpTable;
 use jtable::StaticFrameEntry;
 use mtable::MTable;
+use mtable::MemoryEvents;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -49,6 +50,7 @@ pub struct ExecutionTable {
     pub etable: EventTable,
     pub mtable: MTable,
     pub jtable: JumpTable,
+    pub memory_events: MemoryEvents,
 }
 
 #[derive(Default, Clone)]
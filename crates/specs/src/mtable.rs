@@ -146,6 +146,32 @@ impl MemoryTableEntry {
     pub fn is_same_location(&self, other: &MemoryTableEntry) -> bool {
         self.offset == other.offset && self.ltype == other.ltype
     }
+
+    /// The key of the memory table order, the init entry of a location comes first.
+    pub fn sort_key(&self) -> (LocationType, u32, u32, u32) {
+        (self.ltype, self.offset, self.eid, self.emid)
+    }
+
+    /// The init entry of the location accessed by the entry, only heap and
+    /// global locations are initialized.
+    pub fn init_entry(&self, imtable: &InitMemoryTable) -> Option<MemoryTableEntry> {
+        if self.ltype == LocationType::Heap || self.ltype == LocationType::Global {
+            let (_, _, value) = imtable.try_find(self.ltype, self.offset).unwrap();
+
+            Some(MemoryTableEntry {
+                eid: 0,
+                emid: 0,
+                offset: self.offset,
+                ltype: self.ltype,
+                atype: AccessType::Init,
+                vtype: self.vtype,
+                is_mutable: self.is_mutable,
+                value,
+            })
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
@@ -165,23 +191,18 @@ impl MTable {
         mtable
     }
 
+    /// Entries already sorted by `MemoryTableEntry::sort_key`, with the init
+    /// entries of the accessed heap and global locations.
+    pub fn from_sorted_entries(entries: Vec<MemoryTableEntry>) -> Self {
+        MTable(entries)
+    }
+
     fn push_accessed_memory_initialization(&mut self, imtable: &InitMemoryTable) {
         let mut set = HashSet::<MemoryTableEntry>::default();
 
         self.0.iter().for_each(|entry| {
-            if entry.ltype == LocationType::Heap || entry.ltype == LocationType::Global {
-                let (_, _, value) = imtable.try_find(entry.ltype, entry.offset).unwrap();
-
-                set.insert(MemoryTableEntry {
-                    eid: 0,
-                    emid: 0,
-                    offset: entry.offset,
-                    ltype: entry.ltype,
-                    atype: AccessType::Init,
-                    vtype: entry.vtype,
-                    is_mutable: entry.is_mutable,
-                    value,
-                });
+            if let Some(init_entry) = entry.init_entry(imtable) {
+                set.insert(init_entry);
             }
         });
 
@@ -191,11 +212,70 @@ impl MTable {
     }
 
     fn sort(&mut self) {
-        self.0
-            .sort_by_key(|item| (item.ltype, item.offset, item.eid, item.emid))
+        self.0.sort_by_key(|item| item.sort_key())
     }
 
     pub fn entries(&self) -> &Vec<MemoryTableEntry> {
         &self.0
     }
 }
+
+/// The memory events of the steps, derived once from the event table and
+/// shared by the auditor, the profiler and the circuit.
+///
+/// The events of the i-th step of the event table end at `step_ends[i]`, and
+/// `mtable_rows[j]` is the row of the j-th event in the sorted `MTable`, the
+/// events themselves are only stored in the `MTable`.
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+pub struct MemoryEvents {
+    step_ends: Vec<usize>,
+    mtable_rows: Vec<usize>,
+}
+
+impl MemoryEvents {
+    /// Push the count of the memory events of the next step.
+    pub fn push_step(&mut self, events: usize) {
+        self.step_ends.push(self.events() + events);
+    }
+
+    pub fn set_mtable_rows(&mut self, mtable_rows: Vec<usize>) {
+        assert_eq!(mtable_rows.len(), self.events());
+
+        self.mtable_rows = mtable_rows;
+    }
+
+    /// The count of the memory events of all steps.
+    pub fn events(&self) -> usize {
+        self.step_ends.last().copied().unwrap_or(0)
+    }
+
+    pub fn steps(&self) -> usize {
+        self.step_ends.len()
+    }
+
+    fn step_range(&self, step: usize) -> std::ops::Range<usize> {
+        let start = if step == 0 {
+            0
+        } else {
+            self.step_ends[step - 1]
+        };
+
+        start..self.step_ends[step]
+    }
+
+    /// The memory events of the step with index `step` in the event table.
+    pub fn of_step<'a>(
+        &'a self,
+        step: usize,
+        mtable: &'a MTable,
+    ) -> impl Iterator<Item = &'a MemoryTableEntry> + 'a {
+        self.mtable_rows_of_step(step)
+            .iter()
+            .map(move |row| &mtable.entries()[*row])
+    }
+
+    /// The rows in the memory table of the events of the step.
+    pub fn mtable_rows_of_step(&self, step: usize) -> &[usize] {
+        &self.mtable_rows[self.step_range(step)]
+    }
+}
@@ -1,10 +1,18 @@
+use anyhow::anyhow;
+use anyhow::Result;
 use halo2_proofs::arithmetic::best_multiexp_gpu_cond;
+use halo2_proofs::arithmetic::BaseExt;
 use halo2_proofs::arithmetic::CurveAffine;
+use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::poly::commitment::Params;
 use specs::CompilationTable;
 
 use crate::circuits::image_table::EncodeCompilationTableValues;
 
+use self::poseidon_hash::PoseidonSpec;
+
+pub(crate) mod poseidon_hash;
+
 pub trait ImageCheckSum<Output> {
     fn checksum(&self) -> Output;
 }
@@ -15,10 +23,66 @@ pub(crate) struct CompilationTableWithParams<'a, 'b, C: CurveAffine> {
 }
 
 impl<'a, 'b, C: CurveAffine> ImageCheckSum<Vec<C>> for CompilationTableWithParams<'a, 'b, C> {
+    /// Commitments of the image column and the init memory column.
     fn checksum(&self) -> Vec<C> {
-        let cells = self.table.encode_compilation_table_values().plain();
+        let commit = |cells: Vec<C::Scalar>| -> C {
+            best_multiexp_gpu_cond(&cells[..], &self.params.get_g_lagrange()[0..cells.len()])
+                .into()
+        };
 
-        let c = best_multiexp_gpu_cond(&cells[..], &self.params.get_g_lagrange()[0..cells.len()]);
-        vec![c.into()]
+        vec![
+            commit(self.table.encode_compilation_table_values().plain()),
+            commit(self.table.encode_init_memory_table_values()),
+        ]
     }
 }
+
+pub(crate) struct CompilationTableWithPoseidon<'a> {
+    pub(crate) table: &'a CompilationTable,
+}
+
+/// Poseidon hash of the image table, which is independent of the setup and is
+/// constrained by the uniform circuit as a public instance.
+impl<'a, F: FieldExt> ImageCheckSum<F> for CompilationTableWithPoseidon<'a> {
+    fn checksum(&self) -> F {
+        let cells = [
+            self.table.encode_compilation_table_values().plain(),
+            self.table.encode_init_memory_table_values(),
+        ]
+        .concat();
+
+        PoseidonSpec::new().hash(&cells)
+    }
+}
+
+/// Encode the checksum as the hex of the affine coordinates of its points.
+pub fn checksum_to_hex<C: CurveAffine>(checksum: &Vec<C>) -> String {
+    let mut bytes = vec![];
+
+    for point in checksum {
+        let coordinates = point.coordinates().unwrap();
+
+        coordinates.x().write(&mut bytes).unwrap();
+        coordinates.y().write(&mut bytes).unwrap();
+    }
+
+    hex::encode(bytes)
+}
+
+pub fn checksum_from_hex<C: CurveAffine>(checksum: &str) -> Result<Vec<C>> {
+    let bytes = hex::decode(checksum.trim_start_matches("0x"))?;
+    let mut reader = &bytes[..];
+    let mut points = vec![];
+
+    while !reader.is_empty() {
+        let x = C::Base::read(&mut reader)?;
+        let y = C::Base::read(&mut reader)?;
+
+        points.push(
+            Option::from(C::from_xy(x, y))
+                .ok_or(anyhow!("checksum {} is not on curve", checksum))?,
+        );
+    }
+
+    Ok(points)
+}
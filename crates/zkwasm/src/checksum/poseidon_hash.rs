@@ -0,0 +1,180 @@
+use halo2_proofs::arithmetic::FieldExt;
+use poseidon::Spec;
+
+pub(crate) const T: usize = 9;
+pub(crate) const RATE: usize = 8;
+const R_F: usize = 8;
+const R_P: usize = 63;
+
+/*
+ * Rounds of the optimized poseidon permutation, each round maps the state
+ * of its row to the state of the next row.
+ */
+#[derive(Clone, Copy)]
+pub(crate) enum Round<F: FieldExt> {
+    // state + inputs + constants
+    Absorb([F; T]),
+    // mds * (sbox(state) + constants)
+    Full([F; T]),
+    // pre_sparse_mds * (sbox(state) + constants)
+    PreSparse([F; T]),
+    // sparse_mds * (sbox(state[0]) + constant, state[1..])
+    Partial {
+        constant: F,
+        row: [F; T],
+        col_hat: [F; RATE],
+    },
+}
+
+/// A row of the hash trace: the state before the round and the auxiliary
+/// cells, i.e. the absorbed inputs of an absorb round or the sbox output of a
+/// partial round.
+pub(crate) type HashRow<F> = ([F; T], [F; RATE]);
+
+pub(crate) struct PoseidonSpec<F: FieldExt> {
+    pub(crate) mds: [[F; T]; T],
+    pub(crate) pre_sparse_mds: [[F; T]; T],
+    /// Rounds of one permutation, starting with the absorb round.
+    pub(crate) rounds: Vec<Round<F>>,
+}
+
+fn sbox<F: FieldExt>(x: F) -> F {
+    x.square().square() * x
+}
+
+fn apply_matrix<F: FieldExt>(matrix: &[[F; T]; T], state: &[F; T]) -> [F; T] {
+    let mut next = [F::zero(); T];
+
+    for (i, row) in matrix.iter().enumerate() {
+        for (coeff, word) in row.iter().zip(state.iter()) {
+            next[i] += *coeff * word;
+        }
+    }
+
+    next
+}
+
+impl<F: FieldExt> PoseidonSpec<F> {
+    pub(crate) fn new() -> Self {
+        let spec = Spec::<F, T, RATE>::new(R_F, R_P);
+
+        let constants = spec.constants();
+        let start = constants.start();
+
+        let mut rounds = vec![Round::Absorb(start[0].clone())];
+        for c in start[1..start.len() - 1].iter() {
+            rounds.push(Round::Full(c.clone()));
+        }
+        rounds.push(Round::PreSparse(start.last().unwrap().clone()));
+        for (constant, sparse_mds) in constants
+            .partial()
+            .iter()
+            .zip(spec.mds_matrices().sparse_matrices().iter())
+        {
+            rounds.push(Round::Partial {
+                constant: *constant,
+                row: sparse_mds.row().clone(),
+                col_hat: sparse_mds.col_hat().clone(),
+            });
+        }
+        for c in constants.end().iter() {
+            rounds.push(Round::Full(c.clone()));
+        }
+        rounds.push(Round::Full([F::zero(); T]));
+
+        PoseidonSpec {
+            mds: spec.mds_matrices().mds().rows().clone(),
+            pre_sparse_mds: spec.mds_matrices().pre_sparse_mds().rows().clone(),
+            rounds,
+        }
+    }
+
+    pub(crate) fn initial_state() -> [F; T] {
+        let mut state = [F::zero(); T];
+        state[0] = F::from_u128(1u128 << 64);
+        state
+    }
+
+    /// Pad the message with a one followed by zeros to a multiple of RATE.
+    pub(crate) fn pad(message: &[F]) -> Vec<[F; RATE]> {
+        let mut message = message.to_vec();
+        message.push(F::one());
+        message.resize((message.len() + RATE - 1) / RATE * RATE, F::zero());
+
+        message
+            .chunks(RATE)
+            .map(|chunk| chunk.try_into().unwrap())
+            .collect()
+    }
+
+    fn apply(&self, round: &Round<F>, state: &[F; T], inputs: &[F; RATE]) -> HashRow<F> {
+        match round {
+            Round::Absorb(constants) => {
+                let mut next = *state;
+                next[0] += constants[0];
+                for i in 1..T {
+                    next[i] += inputs[i - 1] + constants[i];
+                }
+                (next, *inputs)
+            }
+            Round::Full(constants) | Round::PreSparse(constants) => {
+                let mut words = *state;
+                for (word, constant) in words.iter_mut().zip(constants.iter()) {
+                    *word = sbox(*word) + constant;
+                }
+
+                let matrix = match round {
+                    Round::Full(_) => &self.mds,
+                    _ => &self.pre_sparse_mds,
+                };
+
+                (apply_matrix(matrix, &words), [F::zero(); RATE])
+            }
+            Round::Partial {
+                constant,
+                row,
+                col_hat,
+            } => {
+                let mut words = *state;
+                words[0] = sbox(words[0]) + constant;
+
+                let mut next = [F::zero(); T];
+                for (coeff, word) in row.iter().zip(words.iter()) {
+                    next[0] += *coeff * word;
+                }
+                for i in 1..T {
+                    next[i] = words[i] + col_hat[i - 1] * words[0];
+                }
+
+                let mut aux = [F::zero(); RATE];
+                aux[0] = words[0];
+                (next, aux)
+            }
+        }
+    }
+
+    /// Returns the rows of all rounds and the final state.
+    pub(crate) fn hash_with_trace(&self, message: &[F]) -> (Vec<HashRow<F>>, [F; T]) {
+        let mut rows = vec![];
+        let mut state = Self::initial_state();
+
+        for chunk in Self::pad(message) {
+            for round in self.rounds.iter() {
+                let inputs = match round {
+                    Round::Absorb(_) => chunk,
+                    _ => [F::zero(); RATE],
+                };
+
+                let (next, aux) = self.apply(round, &state, &inputs);
+                rows.push((state, aux));
+                state = next;
+            }
+        }
+
+        (rows, state)
+    }
+
+    pub(crate) fn hash(&self, message: &[F]) -> F {
+        self.hash_with_trace(message).1[1]
+    }
+}
@@ -0,0 +1,334 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Circuit;
+use halo2_proofs::plonk::ConstraintSystem;
+use specs::itable::UnaryOp;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+use specs::CompilationTable;
+use specs::Tables;
+use strum_macros::EnumIter;
+
+use crate::foreign::context::circuits::assign::ExtractContextFromTrace;
+use crate::foreign::context::CONTEXT_HASH_CAPACITY;
+use crate::runtime::step_counter::StepCounts;
+
+use super::bit_table::STEP_SIZE;
+use super::config::compute_max_image_table_rows;
+#[cfg(feature = "uniform-circuit")]
+use super::config::compute_max_init_memory_table_rows;
+use super::config::MIN_K;
+use super::etable::EVENT_TABLE_ENTRY_ROWS;
+use super::jtable::JtableOffset;
+use super::mtable::MEMORY_TABLE_ENTRY_ROWS;
+#[cfg(any(feature = "uniform-circuit", feature = "continuation"))]
+use super::poseidon::poseidon_hash_region_rows;
+use super::test_circuit::RESERVE_ROWS;
+use super::TestCircuit;
+
+/// The two-adicity of the bn256 scalar field.
+pub const MAX_K: u32 = 28;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum CircuitTable {
+    EventTable,
+    MemoryTable,
+    JumpTable,
+    BitTable,
+    ExternalHostCallTable,
+    ImageTable,
+    InitMemoryTable,
+    WasmInputHelperTable,
+    ContextInputHelperTable,
+    ContextOutputHelperTable,
+    /// The regions of the image hash of the uniform circuit and the context
+    /// hashes of the continuation circuit.
+    #[cfg(any(feature = "uniform-circuit", feature = "continuation"))]
+    PoseidonHash,
+}
+
+#[derive(Debug, Clone)]
+pub struct TableUsage {
+    pub table: CircuitTable,
+    pub required_rows: usize,
+    pub available_rows: usize,
+    /// The minimal K whose circuit can hold the table, None if no K can hold it.
+    pub minimal_k: Option<u32>,
+}
+
+impl TableUsage {
+    pub fn is_overflow(&self) -> bool {
+        self.required_rows > self.available_rows
+    }
+}
+
+/// Rows required by each table of the trace against the rows available under K.
+#[derive(Debug, Clone)]
+pub struct CapacityReport {
+    pub k: u32,
+    pub usages: Vec<TableUsage>,
+}
+
+fn blinding_factors<F: FieldExt>() -> usize {
+    let mut meta = ConstraintSystem::<F>::default();
+    TestCircuit::<F>::configure(&mut meta);
+
+    meta.blinding_factors()
+}
+
+pub(crate) fn available_rows(table: CircuitTable, k: u32, blinding_factors: usize) -> usize {
+    match table {
+        CircuitTable::EventTable
+        | CircuitTable::MemoryTable
+        | CircuitTable::JumpTable
+        | CircuitTable::BitTable
+        | CircuitTable::ExternalHostCallTable => {
+            (1usize << k).saturating_sub(blinding_factors + 1 + RESERVE_ROWS)
+        }
+        #[cfg(feature = "uniform-circuit")]
+        CircuitTable::InitMemoryTable => compute_max_init_memory_table_rows(k) as usize,
+        #[cfg(not(feature = "uniform-circuit"))]
+        CircuitTable::InitMemoryTable => {
+            (1usize << k).saturating_sub(blinding_factors + 1 + RESERVE_ROWS)
+        }
+        #[cfg(any(feature = "uniform-circuit", feature = "continuation"))]
+        CircuitTable::PoseidonHash => {
+            (1usize << k).saturating_sub(blinding_factors + 1 + RESERVE_ROWS)
+        }
+        CircuitTable::ImageTable => compute_max_image_table_rows(k) as usize,
+        CircuitTable::WasmInputHelperTable => 1 << (k - 1),
+        // The context values are covered by the context hashes up to the capacity.
+        CircuitTable::ContextInputHelperTable | CircuitTable::ContextOutputHelperTable => {
+            if cfg!(feature = "continuation") {
+                usize::min(1 << (k - 1), CONTEXT_HASH_CAPACITY + 1)
+            } else {
+                1 << (k - 1)
+            }
+        }
+    }
+}
+
+/// Entries of the image table, they are known before the execution.
+pub(crate) fn image_table_entries(compilation_tables: &CompilationTable) -> usize {
+    // Each section of the image table starts with a zero entry.
+    2 + compilation_tables.itable.iter().count()
+        + compilation_tables.itable.create_brtable().entries().len()
+        + compilation_tables.elem_table.entries().len()
+}
+
+pub(crate) fn init_memory_table_entries(compilation_tables: &CompilationTable) -> usize {
+    // The init memory table starts with a zero entry.
+    1 + compilation_tables.imtable.filter(LocationType::Heap).len()
+        + compilation_tables
+            .imtable
+            .filter(LocationType::Global)
+            .len()
+}
+
+/// Rows of the poseidon hash regions, which hash the image and the init
+/// memory under `uniform-circuit` and the padded context inputs and outputs
+/// under `continuation`.
+#[cfg(any(feature = "uniform-circuit", feature = "continuation"))]
+fn poseidon_hash_rows<F: FieldExt>(compilation_tables: &CompilationTable) -> usize {
+    #[allow(unused_mut)]
+    let mut message_lens = vec![];
+
+    #[cfg(feature = "uniform-circuit")]
+    {
+        use super::image_table::EncodeCompilationTableValues;
+
+        let image: Vec<F> = compilation_tables.encode_compilation_table_values().plain();
+        let init_memory: Vec<F> = compilation_tables.encode_init_memory_table_values();
+        message_lens.push(image.len() + init_memory.len());
+    }
+
+    // The messages of the context hashes start with the count of the values.
+    #[cfg(feature = "continuation")]
+    message_lens.extend([CONTEXT_HASH_CAPACITY + 1; 2]);
+
+    message_lens
+        .into_iter()
+        .map(|message_len| poseidon_hash_region_rows::<F>(message_len))
+        .sum()
+}
+
+/// The minimal K whose image table can hold `entries`.
+pub(crate) fn image_table_minimal_k(entries: usize) -> Option<u32> {
+    (MIN_K..=MAX_K).find(|k| entries <= compute_max_image_table_rows(*k) as usize)
+}
+
+/// The minimal K whose init memory table of the uniform circuit can hold `entries`.
+#[cfg(feature = "uniform-circuit")]
+pub(crate) fn init_memory_table_minimal_k(entries: usize) -> Option<u32> {
+    (MIN_K..=MAX_K).find(|k| entries <= compute_max_init_memory_table_rows(*k) as usize)
+}
+
+fn required_rows<F: FieldExt>(tables: &Tables, instances: usize) -> Vec<(CircuitTable, usize)> {
+    let etable = &tables.execution_tables.etable;
+    let compilation_tables = &tables.compilation_tables;
+    let image_entries = image_table_entries(compilation_tables);
+
+    let bit_entries = etable
+        .entries()
+        .iter()
+        .filter(|entry| {
+            matches!(
+                entry.step_info,
+                StepInfo::I32BinBitOp { .. }
+                    | StepInfo::I64BinBitOp { .. }
+                    | StepInfo::UnaryOp {
+                        class: UnaryOp::Popcnt,
+                        ..
+                    }
+            )
+        })
+        .count();
+
+    let init_memory_entries = init_memory_table_entries(compilation_tables);
+
+    vec![
+        (
+            CircuitTable::EventTable,
+            etable.entries().len() * EVENT_TABLE_ENTRY_ROWS as usize,
+        ),
+        (
+            CircuitTable::MemoryTable,
+            // The last entry of mtable is reserved to terminate the table.
+            tables.execution_tables.mtable.entries().len() * MEMORY_TABLE_ENTRY_ROWS as usize + 1,
+        ),
+        (
+            CircuitTable::JumpTable,
+            // Two static entries are always assigned.
+            (tables.execution_tables.jtable.entries().len() + 2)
+                * JtableOffset::JtableOffsetMax as usize,
+        ),
+        (CircuitTable::BitTable, bit_entries * STEP_SIZE),
+        (
+            CircuitTable::ExternalHostCallTable,
+            // The first row is a padding entry.
+            etable.filter_external_host_call_table().entries().len() + 1,
+        ),
+        (CircuitTable::ImageTable, image_entries),
+        (CircuitTable::InitMemoryTable, init_memory_entries),
+        (CircuitTable::WasmInputHelperTable, instances + 1),
+        // Helper tables for context start from index 1.
+        (
+            CircuitTable::ContextInputHelperTable,
+            etable.get_context_inputs().len() + 1,
+        ),
+        (
+            CircuitTable::ContextOutputHelperTable,
+            etable.get_context_outputs().len() + 1,
+        ),
+        #[cfg(any(feature = "uniform-circuit", feature = "continuation"))]
+        (
+            CircuitTable::PoseidonHash,
+            poseidon_hash_rows::<F>(compilation_tables),
+        ),
+    ]
+}
+
+impl CapacityReport {
+    /// Estimate the rows of each table required by `tables`, `instances` is
+    /// the number of public inputs and outputs.
+    pub fn new<F: FieldExt>(tables: &Tables, instances: usize, k: u32) -> Self {
+        Self::from_required_rows::<F>(required_rows::<F>(tables, instances), k)
+    }
+
+    /// Estimate the rows of each table from the step counts of the non-tracing
+    /// dry run, the context helper tables are not covered by the step counts.
+    pub fn from_step_counts<F: FieldExt>(
+        compilation_tables: &CompilationTable,
+        step_counts: &StepCounts,
+        instances: usize,
+        k: u32,
+    ) -> Self {
+        let required_rows = vec![
+            (
+                CircuitTable::EventTable,
+                step_counts.etable as usize * EVENT_TABLE_ENTRY_ROWS as usize,
+            ),
+            (
+                CircuitTable::MemoryTable,
+                step_counts.mtable as usize * MEMORY_TABLE_ENTRY_ROWS as usize + 1,
+            ),
+            (
+                CircuitTable::JumpTable,
+                (step_counts.jtable as usize + 2) * JtableOffset::JtableOffsetMax as usize,
+            ),
+            (
+                CircuitTable::BitTable,
+                step_counts.bit_table as usize * STEP_SIZE,
+            ),
+            (
+                CircuitTable::ExternalHostCallTable,
+                step_counts.external_host_calls as usize + 1,
+            ),
+            (
+                CircuitTable::ImageTable,
+                image_table_entries(compilation_tables),
+            ),
+            (
+                CircuitTable::InitMemoryTable,
+                init_memory_table_entries(compilation_tables),
+            ),
+            (CircuitTable::WasmInputHelperTable, instances + 1),
+            #[cfg(any(feature = "uniform-circuit", feature = "continuation"))]
+            (
+                CircuitTable::PoseidonHash,
+                poseidon_hash_rows::<F>(compilation_tables),
+            ),
+        ];
+
+        Self::from_required_rows::<F>(required_rows, k)
+    }
+
+    fn from_required_rows<F: FieldExt>(required_rows: Vec<(CircuitTable, usize)>, k: u32) -> Self {
+        let blinding_factors = blinding_factors::<F>();
+
+        let usages = required_rows
+            .into_iter()
+            .map(|(table, required_rows)| TableUsage {
+                table,
+                required_rows,
+                available_rows: available_rows(table, k, blinding_factors),
+                minimal_k: (MIN_K..=MAX_K)
+                    .find(|k| required_rows <= available_rows(table, *k, blinding_factors)),
+            })
+            .collect();
+
+        CapacityReport { k, usages }
+    }
+
+    pub fn overflows(&self) -> Vec<&TableUsage> {
+        self.usages
+            .iter()
+            .filter(|usage| usage.is_overflow())
+            .collect()
+    }
+
+    /// The minimal K which can hold all tables, None if no K can hold them.
+    pub fn minimal_k(&self) -> Option<u32> {
+        self.usages
+            .iter()
+            .map(|usage| usage.minimal_k)
+            .collect::<Option<Vec<_>>>()
+            .map(|ks| ks.into_iter().max().unwrap_or(MIN_K))
+    }
+
+    /// The table which determines the minimal K.
+    pub fn bottleneck(&self) -> &TableUsage {
+        self.usages
+            .iter()
+            .max_by(|a, b| {
+                a.minimal_k
+                    .unwrap_or(u32::MAX)
+                    .cmp(&b.minimal_k.unwrap_or(u32::MAX))
+                    .then(
+                        (a.required_rows as f64 / a.available_rows as f64)
+                            .partial_cmp(&(b.required_rows as f64 / b.available_rows as f64))
+                            .unwrap(),
+                    )
+            })
+            .unwrap()
+    }
+}
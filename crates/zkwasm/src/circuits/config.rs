@@ -1,3 +1,4 @@
+use specs::configure_table::WASM_BYTES_PER_PAGE;
 use std::env;
 use std::sync::Mutex;
 
@@ -25,6 +26,33 @@ pub fn init_zkwasm_runtime(k: u32) {
     set_zkwasm_k(k);
 }
 
+/// The rows of the image table under K, 8192 rows with `MIN_K` and doubled
+/// with each increment of K. As it is derived from K, the capacity is recorded
+/// by the verifying key as well.
+pub fn compute_max_image_table_rows(k: u32) -> u32 {
+    8192 << (k - MIN_K)
+}
+
 pub(crate) fn max_image_table_rows() -> u32 {
-    8192
+    compute_max_image_table_rows(zkwasm_k())
+}
+
+/// The rows of the init memory table committed by the uniform circuit under K,
+/// the init memory is padded to them so that the image hash region, and so the
+/// verifying key, does not depend on the data segments and globals.
+pub fn compute_max_init_memory_table_rows(k: u32) -> u32 {
+    compute_max_image_table_rows(k)
+}
+
+#[cfg(feature = "uniform-circuit")]
+pub(crate) fn max_init_memory_table_rows() -> u32 {
+    compute_max_init_memory_table_rows(zkwasm_k())
+}
+
+/// The memory pages addressable under K, heap offsets of the memory table are
+/// in u64 words and bounded by `1 << (k - 1)`.
+pub fn compute_maximal_pages(k: u32) -> u32 {
+    let bytes = 1u64 << (k - 1 + 3);
+
+    (bytes / WASM_BYTES_PER_PAGE) as u32
 }
@@ -2,6 +2,7 @@ use super::EVENT_TABLE_ENTRY_ROWS;
 use crate::circuits::bit_table::BitTableOp;
 use crate::circuits::cell::*;
 use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::info::CellUsage;
 use crate::circuits::jtable::JumpTableConfig;
 use crate::circuits::rtable::RangeTableConfig;
 use crate::circuits::traits::ConfigureLookupTable;
@@ -352,6 +353,27 @@ impl<F: FieldExt> EventTableCellAllocator<F> {
         }
     }
 
+    /// The cells allocated out of the cells of each type in an entry.
+    pub(crate) fn cell_usages(&self) -> Vec<CellUsage> {
+        let mut usages: Vec<_> = self
+            .free_cells
+            .iter()
+            .map(|(t, (col, rot))| CellUsage {
+                cell_type: format!("{:?}", t),
+                allocated: col * EVENT_TABLE_ENTRY_ROWS as usize + *rot as usize,
+                available: self.all_cols.get(t).unwrap().len() * EVENT_TABLE_ENTRY_ROWS as usize,
+            })
+            .collect();
+
+        usages.push(CellUsage {
+            cell_type: "U64".to_string(),
+            allocated: U64_CELLS - self.free_u64_cells.len(),
+            available: U64_CELLS,
+        });
+
+        usages
+    }
+
     fn alloc(&mut self, t: &EventTableCellType) -> AllocatedCell<F> {
         let v = self.free_cells.get_mut(t).unwrap();
 
@@ -13,6 +13,8 @@ use wasmi::DEFAULT_VALUE_STACK_LIMIT;
 use super::EventTableChip;
 use super::EventTableOpcodeConfig;
 use super::EVENT_TABLE_ENTRY_ROWS;
+#[cfg(any(feature = "public-eid", feature = "continuation"))]
+use crate::circuits::cell::AllocatedCommonRangeCell;
 use crate::circuits::cell::CellExpression;
 use crate::circuits::utils::bn_to_field;
 use crate::circuits::utils::step_status::Status;
@@ -26,6 +28,13 @@ pub(in crate::circuits) struct EventTablePermutationCells {
     pub(in crate::circuits) fid_of_entry: Cell,
     pub(in crate::circuits) initial_memory_pages: Cell,
     pub(in crate::circuits) maximal_memory_pages: Cell,
+    /// The eid at the end of the table, which is the count of steps plus one.
+    #[cfg(feature = "public-eid")]
+    pub(in crate::circuits) termination_eid: Cell,
+    /// The context input and output indices at the end of the table, which
+    /// are the counts of context values plus one.
+    #[cfg(feature = "continuation")]
+    pub(in crate::circuits) context_indices: (Cell, Cell),
 }
 
 impl<F: FieldExt> EventTableChip<F> {
@@ -332,6 +341,58 @@ impl<F: FieldExt> EventTableChip<F> {
         ))
     }
 
+    /// Keep the value of `cell` at the termination on the rows following it,
+    /// and return the cell at the end of the table.
+    #[cfg(any(feature = "public-eid", feature = "continuation"))]
+    fn assign_after_termination(
+        &self,
+        ctx: &mut Context<'_, F>,
+        event_table: &EventTableWithMemoryInfo,
+        cell: AllocatedCommonRangeCell<F>,
+        value: u64,
+    ) -> Result<Cell, Error> {
+        let capability = self.max_available_rows / EVENT_TABLE_ENTRY_ROWS as usize;
+
+        // The termination step itself is assigned by `assign_entries`.
+        let first_step = (event_table.0.len() + 1).min(capability);
+        ctx.step(first_step * EVENT_TABLE_ENTRY_ROWS as usize);
+
+        for _ in first_step..capability {
+            cell.assign(ctx, F::from(value))?;
+
+            ctx.step(EVENT_TABLE_ENTRY_ROWS as usize);
+        }
+
+        let cell = cell.assign(ctx, F::from(value))?;
+
+        Ok(cell.cell())
+    }
+
+    /// The context input and output indices at the termination.
+    #[cfg(feature = "continuation")]
+    fn context_indices_at_termination(
+        &self,
+        itable: &InstructionTable,
+        event_table: &EventTableWithMemoryInfo,
+    ) -> (u64, u64) {
+        event_table
+            .0
+            .iter()
+            .fold((1, 1), |(input_index, output_index), entry| {
+                let instruction = entry.eentry.get_instruction(itable);
+                let op_config = self
+                    .config
+                    .op_configs
+                    .get(&((&instruction.opcode).into()))
+                    .unwrap();
+
+                (
+                    input_index + op_config.is_context_input_op(&entry.eentry) as u64,
+                    output_index + op_config.is_context_output_op(&entry.eentry) as u64,
+                )
+            })
+    }
+
     pub(in crate::circuits) fn assign(
         &self,
         ctx: &mut Context<'_, F>,
@@ -340,6 +401,11 @@ impl<F: FieldExt> EventTableChip<F> {
         configure_table: &ConfigureTable,
         fid_of_entry: u32,
     ) -> Result<EventTablePermutationCells, Error> {
+        #[cfg(test)]
+        let tampered = crate::circuits::tamper::tamper_event_table(event_table);
+        #[cfg(test)]
+        let event_table = &*tampered;
+
         debug!("size of execution table: {}", event_table.0.len());
         assert!(event_table.0.len() * EVENT_TABLE_ENTRY_ROWS as usize <= self.max_available_rows);
 
@@ -367,12 +433,54 @@ impl<F: FieldExt> EventTableChip<F> {
         )?;
         ctx.reset();
 
+        #[cfg(feature = "public-eid")]
+        let termination_eid = {
+            let termination_eid = event_table.0.last().map_or(1, |entry| entry.eentry.eid + 1);
+            let cell = self.assign_after_termination(
+                ctx,
+                event_table,
+                self.config.common_config.eid_cell,
+                termination_eid as u64,
+            )?;
+            ctx.reset();
+
+            cell
+        };
+
+        #[cfg(feature = "continuation")]
+        let context_indices = {
+            let (input_index, output_index) =
+                self.context_indices_at_termination(itable, event_table);
+
+            let input_cell = self.assign_after_termination(
+                ctx,
+                event_table,
+                self.config.common_config.context_input_index_cell,
+                input_index,
+            )?;
+            ctx.reset();
+
+            let output_cell = self.assign_after_termination(
+                ctx,
+                event_table,
+                self.config.common_config.context_output_index_cell,
+                output_index,
+            )?;
+            ctx.reset();
+
+            (input_cell, output_cell)
+        };
+
         Ok(EventTablePermutationCells {
             rest_mops: Some(rest_mops_cell),
             rest_jops: Some(rest_jops_cell),
             fid_of_entry,
             initial_memory_pages,
             maximal_memory_pages,
+            #[cfg(feature = "public-eid")]
+            termination_eid,
+            #[cfg(feature = "continuation")]
+            context_indices,
         })
     }
 }
@@ -58,6 +58,16 @@ impl<'a, 'b, F: FieldExt> ConstraintBuilder<'a, 'b, F> {
         }
     }
 
+    /// The names of the constraints and lookups pushed so far.
+    #[cfg(test)]
+    pub(super) fn names(&self) -> Vec<&'static str> {
+        self.constraints
+            .iter()
+            .map(|(name, _)| *name)
+            .chain(self.lookups.values().flatten().map(|(name, _)| *name))
+            .collect()
+    }
+
     pub(super) fn finalize(
         self,
         selector: impl Fn(&mut VirtualCells<F>) -> (Expression<F>, Expression<F>),
@@ -4,6 +4,7 @@ use super::bit_table::BitTableConfig;
 use super::cell::*;
 use super::external_host_call_table::ExternalHostCallTableConfig;
 use super::image_table::ImageTableConfig;
+use super::info::CellUsage;
 use super::jtable::JumpTableConfig;
 use super::mtable::MemoryTableConfig;
 use super::rtable::RangeTableConfig;
@@ -207,6 +208,9 @@ pub struct EventTableConfig<F: FieldExt> {
     pub step_sel: Column<Fixed>,
     pub common_config: EventTableCommonConfig<F>,
     op_configs: BTreeMap<OpcodeClassPlain, Rc<Box<dyn EventTableOpcodeConfig<F>>>>,
+    /// The cells of the common config, and those of each opcode on top of them.
+    pub(crate) common_cells: Vec<CellUsage>,
+    pub(crate) opcode_cells: BTreeMap<OpcodeClassPlain, Vec<CellUsage>>,
 }
 
 impl<F: FieldExt> EventTableConfig<F> {
@@ -292,6 +296,9 @@ impl<F: FieldExt> EventTableConfig<F> {
             external_foreign_call_lookup_cell,
         };
 
+        let common_cells = allocator.cell_usages();
+        let mut opcode_cells = BTreeMap::new();
+
         let mut op_bitmaps: BTreeMap<OpcodeClassPlain, usize> = BTreeMap::new();
         let mut op_configs: BTreeMap<OpcodeClassPlain, Rc<Box<dyn EventTableOpcodeConfig<F>>>> =
             BTreeMap::new();
@@ -303,11 +310,13 @@ impl<F: FieldExt> EventTableConfig<F> {
                 let foreign_table_configs = BTreeMap::new();
                 let mut constraint_builder = ConstraintBuilder::new(meta, &foreign_table_configs);
 
-                let config = $x::configure(
-                    &common_config,
-                    &mut allocator.clone(),
-                    &mut constraint_builder,
-                );
+                let mut op_allocator = allocator.clone();
+                let config =
+                    $x::configure(&common_config, &mut op_allocator, &mut constraint_builder);
+                opcode_cells.insert(op, op_allocator.cell_usages());
+
+                #[cfg(test)]
+                crate::circuits::tamper::record_op_constraint_names(op, constraint_builder.names());
 
                 constraint_builder.finalize(|meta| {
                     (fixed_curr!(meta, step_sel), ops[op.index()].curr_expr(meta))
@@ -354,12 +363,14 @@ impl<F: FieldExt> EventTableConfig<F> {
 
                 let mut constraint_builder = ConstraintBuilder::new(meta, foreign_table_configs);
 
+                let mut op_allocator = allocator.clone();
                 let config = builder.configure(
                     &common_config,
-                    &mut allocator.clone(),
+                    &mut op_allocator,
                     &mut constraint_builder,
                     &mut foreign_table_reserved_lookup_cells,
                 );
+                opcode_cells.insert(op, op_allocator.cell_usages());
 
                 constraint_builder.finalize(|meta| {
                     (fixed_curr!(meta, step_sel), ops[op.index()].curr_expr(meta))
@@ -518,6 +529,20 @@ impl<F: FieldExt> EventTableConfig<F> {
             )]
         });
 
+        #[cfg(feature = "continuation")]
+        meta.create_gate("c5i. context indices are kept after termination", |meta| {
+            vec![
+                (context_input_index_cell.next_expr(meta)
+                    - context_input_index_cell.curr_expr(meta))
+                    * (constant_from!(1) - enabled_cell.curr_expr(meta))
+                    * fixed_curr!(meta, step_sel),
+                (context_output_index_cell.next_expr(meta)
+                    - context_output_index_cell.curr_expr(meta))
+                    * (constant_from!(1) - enabled_cell.curr_expr(meta))
+                    * fixed_curr!(meta, step_sel),
+            ]
+        });
+
         meta.create_gate("c6a. eid change", |meta| {
             vec![
                 (eid_cell.next_expr(meta) - eid_cell.curr_expr(meta) - constant_from!(1))
@@ -565,6 +590,19 @@ impl<F: FieldExt> EventTableConfig<F> {
             )]
         });
 
+        /*
+         * The eid of the termination is kept till the end of the table,
+         * where it is exposed as the count of executed steps plus one.
+         */
+        #[cfg(feature = "public-eid")]
+        meta.create_gate("c6e. eid is kept after termination", |meta| {
+            vec![
+                (eid_cell.next_expr(meta) - eid_cell.curr_expr(meta))
+                    * (constant_from!(1) - enabled_cell.curr_expr(meta))
+                    * fixed_curr!(meta, step_sel),
+            ]
+        });
+
         meta.create_gate("c7. itable_lookup_encode", |meta| {
             let opcode = sum_ops_expr(
                 meta,
@@ -626,6 +664,8 @@ impl<F: FieldExt> EventTableConfig<F> {
             step_sel,
             common_config,
             op_configs,
+            common_cells,
+            opcode_cells,
         }
     }
 }
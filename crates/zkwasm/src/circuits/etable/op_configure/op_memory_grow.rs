@@ -26,6 +26,7 @@ pub struct MemoryGrowConfig<F: FieldExt> {
     result: AllocatedU64Cell<F>,
     success: AllocatedBitCell<F>,
     current_maximal_diff: AllocatedCommonRangeCell<F>,
+    current_maximal_overflow: AllocatedU64Cell<F>,
 
     memory_table_lookup_stack_read: AllocatedMemoryTableLookupReadCell<F>,
     memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
@@ -42,6 +43,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for MemoryGrowConfigBuilder {
         let grow_size = allocator.alloc_u64_cell();
         let result = allocator.alloc_u64_cell();
         let current_maximal_diff = allocator.alloc_common_range_cell();
+        let current_maximal_overflow = allocator.alloc_u64_cell();
 
         let success = allocator.alloc_bit_cell();
 
@@ -73,6 +75,19 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for MemoryGrowConfigBuilder {
             }),
         );
 
+        constraint_builder.push(
+            "memory_grow: updated memory size should greater than maximal memory size if fails",
+            Box::new(move |meta| {
+                vec![
+                    (current_memory_size.expr(meta) + grow_size.expr(meta)
+                        - maximal_memory_pages.expr(meta)
+                        - constant_from!(1)
+                        - current_maximal_overflow.expr(meta))
+                        * (constant_from!(1) - success.expr(meta)),
+                ]
+            }),
+        );
+
         let eid = common_config.eid_cell;
         let sp = common_config.sp_cell;
 
@@ -103,6 +118,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for MemoryGrowConfigBuilder {
             result,
             success,
             current_maximal_diff,
+            current_maximal_overflow,
             memory_table_lookup_stack_read,
             memory_table_lookup_stack_write,
         })
@@ -125,8 +141,10 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for MemoryGrowConfig<F> {
         match &entry.eentry.step_info {
             StepInfo::MemoryGrow { grow_size, result } => {
                 let success = *result != -1;
+                // The grow size is an unsigned page count.
+                let grow_size = *grow_size as u32;
 
-                self.grow_size.assign(ctx, *grow_size as u64)?;
+                self.grow_size.assign(ctx, grow_size as u64)?;
                 self.result.assign(ctx, *result as u32 as u64)?;
                 self.success.assign_bool(ctx, success)?;
                 if success {
@@ -134,10 +152,17 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for MemoryGrowConfig<F> {
                         ctx,
                         F::from(
                             (step.configure_table.maximal_memory_pages
-                                - (step.current.allocated_memory_pages + *grow_size as u32))
+                                - (step.current.allocated_memory_pages + grow_size))
                                 as u64,
                         ),
                     )?;
+                } else {
+                    self.current_maximal_overflow.assign(
+                        ctx,
+                        step.current.allocated_memory_pages as u64 + grow_size as u64
+                            - step.configure_table.maximal_memory_pages as u64
+                            - 1,
+                    )?;
                 }
 
                 self.memory_table_lookup_stack_read.assign(
@@ -148,7 +173,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for MemoryGrowConfig<F> {
                     step.current.sp + 1,
                     LocationType::Stack,
                     true,
-                    *grow_size as u32 as u64,
+                    grow_size as u64,
                 )?;
 
                 self.memory_table_lookup_stack_write.assign(
@@ -8,55 +8,57 @@ use super::ImageTableLayouter;
 use crate::circuits::utils::Context;
 
 impl<F: FieldExt> ImageTableChip<F> {
+    /// Returns the cells of the image table in the order of `ImageTableLayouter::plain`
+    /// and the cells of the init memory table.
     pub fn assign(
         self,
         layouter: &mut impl Layouter<F>,
         image_table: ImageTableLayouter<F>,
+        init_memory_entries: Vec<F>,
         permutation_cells: ImageTableLayouter<Cell>,
-    ) -> Result<(), Error> {
-        layouter.assign_region(
+    ) -> Result<(Vec<Cell>, Vec<Cell>), Error> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature="uniform-circuit")] {
+                macro_rules! assign_cell {
+                    ($ctx: expr, $cells: expr, $col: expr, $v: expr) => {{
+                        let cell = $ctx
+                            .region
+                            .assign_advice(|| "image table", $col, $ctx.offset, || Ok($v))?
+                            .cell();
+
+                        $ctx.next();
+                        $cells.push(cell);
+
+                        cell
+                    }};
+                }
+            } else {
+                macro_rules! assign_cell {
+                    ($ctx: expr, $cells: expr, $col: expr, $v: expr) => {{
+                        let cell = $ctx
+                            .region
+                            .assign_fixed(|| "image table", $col, $ctx.offset, || Ok($v))?
+                            .cell();
+
+                        $ctx.next();
+                        $cells.push(cell);
+
+                        cell
+                    }};
+                }
+            }
+        }
+
+        let image_cells = layouter.assign_region(
             || "image table",
             |region| {
                 let mut ctx = Context::new(region);
+                let mut cells = vec![];
 
-                cfg_if::cfg_if! {
-                    if #[cfg(feature="uniform-circuit")] {
-                        macro_rules! assign_one_line {
-                            ($v: expr) => {{
-                                let cell = ctx
-                                    .region
-                                    .assign_advice(
-                                        || "image table",
-                                        self.config.col,
-                                        ctx.offset,
-                                        || Ok($v),
-                                    )?
-                                    .cell();
-
-                                ctx.next();
-
-                                cell
-                            }};
-                        }
-                    } else {
-                        macro_rules! assign_one_line {
-                            ($v: expr) => {{
-                                let cell = ctx
-                                    .region
-                                    .assign_fixed(
-                                        || "image table",
-                                        self.config.col,
-                                        ctx.offset,
-                                        || Ok($v),
-                                    )?
-                                    .cell();
-
-                                ctx.next();
-
-                                cell
-                            }};
-                        }
-                    }
+                macro_rules! assign_one_line {
+                    ($v: expr) => {
+                        assign_cell!(ctx, cells, self.config.col, $v)
+                    };
                 }
 
                 let entry_fid_cell = assign_one_line!(image_table.entry_fid);
@@ -92,8 +94,24 @@ impl<F: FieldExt> ImageTableChip<F> {
                     assign_one_line!(*value);
                 }
 
-                Ok(())
+                Ok(cells)
             },
-        )
+        )?;
+
+        let init_memory_cells = layouter.assign_region(
+            || "init memory table",
+            |region| {
+                let mut ctx = Context::new(region);
+                let mut cells = vec![];
+
+                for value in init_memory_entries.iter() {
+                    assign_cell!(ctx, cells, self.config.init_memory_col, *value);
+                }
+
+                Ok(cells)
+            },
+        )?;
+
+        Ok((image_cells, init_memory_cells))
     }
 }
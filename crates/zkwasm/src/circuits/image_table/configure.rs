@@ -19,17 +19,32 @@ impl<F: FieldExt> ImageTableConfig<F> {
         }
     }
 
+    fn init_memory_expr(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature="uniform-circuit")] {
+                crate::curr!(meta, self.init_memory_col)
+            } else {
+                crate::fixed_curr!(meta, self.init_memory_col)
+            }
+        }
+    }
+
     pub(in crate::circuits) fn configure(meta: &mut ConstraintSystem<F>) -> Self {
         cfg_if::cfg_if! {
             if #[cfg(feature="uniform-circuit")] {
                 let col = meta.named_advice_column(super::IMAGE_COL_NAME.to_owned());
+                let init_memory_col =
+                    meta.named_advice_column(super::INIT_MEMORY_COL_NAME.to_owned());
             } else {
                 let col = meta.fixed_column();
+                let init_memory_col = meta.fixed_column();
             }
         }
         meta.enable_equality(col);
+        meta.enable_equality(init_memory_col);
         Self {
             col,
+            init_memory_col,
             _mark: PhantomData,
         }
     }
@@ -57,7 +72,7 @@ impl<F: FieldExt> ImageTableConfig<F> {
         meta.lookup_any(key, |meta| {
             vec![(
                 ImageTableEncoder::InitMemory.encode(expr(meta)),
-                self.expr(meta),
+                self.init_memory_expr(meta),
             )]
         });
     }
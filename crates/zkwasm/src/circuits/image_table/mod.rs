@@ -4,7 +4,6 @@ use num_bigint::BigUint;
 use specs::brtable::BrTable;
 use specs::brtable::ElemTable;
 use specs::encode::image_table::ImageTableEncoder;
-use specs::imtable::InitMemoryTable;
 use specs::itable::InstructionTable;
 use specs::jtable::StaticFrameEntry;
 use specs::mtable::LocationType;
@@ -12,12 +11,15 @@ use specs::CompilationTable;
 use std::marker::PhantomData;
 
 use crate::circuits::config::max_image_table_rows;
+#[cfg(feature = "uniform-circuit")]
+use crate::circuits::config::max_init_memory_table_rows;
 use crate::circuits::utils::bn_to_field;
 
 mod assign;
 mod configure;
 
 pub const IMAGE_COL_NAME: &str = "img_col";
+pub const INIT_MEMORY_COL_NAME: &str = "init_memory_col";
 
 pub struct ImageTableLayouter<T: Clone> {
     pub entry_fid: T,
@@ -29,7 +31,9 @@ pub struct ImageTableLayouter<T: Clone> {
      *   instruction table
      *   br table
      *   elem table
-     *   init memory table
+     *
+     * The init memory table is committed in its own column, see
+     * `EncodeCompilationTableValues::encode_init_memory_table_values`.
      */
     pub lookup_entries: Option<Vec<T>>,
 }
@@ -59,6 +63,13 @@ impl<T: Clone> ImageTableLayouter<T> {
 
 pub trait EncodeCompilationTableValues<F: Clone> {
     fn encode_compilation_table_values(&self) -> ImageTableLayouter<F>;
+
+    /// Entries of the init memory column. The column is not bounded by
+    /// `max_image_table_rows`, so images with large data segments can still
+    /// be loaded, and it is committed in bulk by the vkey. The uniform circuit
+    /// pads it to `max_init_memory_table_rows`, so that its vkey does not
+    /// depend on the image.
+    fn encode_init_memory_table_values(&self) -> Vec<F>;
 }
 
 impl<F: FieldExt> EncodeCompilationTableValues<F> for CompilationTable {
@@ -97,36 +108,15 @@ impl<F: FieldExt> EncodeCompilationTableValues<F> for CompilationTable {
             cells
         }
 
-        fn msg_of_init_memory_table<F: FieldExt>(init_memory_table: &InitMemoryTable) -> Vec<F> {
-            let heap_entries = init_memory_table.filter(LocationType::Heap);
-            let global_entries = init_memory_table.filter(LocationType::Global);
-
-            let mut cells = vec![];
-
-            cells.push(bn_to_field(
-                &ImageTableEncoder::InitMemory.encode(BigUint::from(0u64)),
-            ));
-
-            for v in heap_entries.into_iter().chain(global_entries.into_iter()) {
-                cells.push(bn_to_field::<F>(
-                    &ImageTableEncoder::InitMemory.encode(v.encode()),
-                ));
-            }
-
-            cells
-        }
-
         fn msg_of_image_table<F: FieldExt>(
             instruction_table: &InstructionTable,
             br_table: &BrTable,
             elem_table: &ElemTable,
-            init_memory_table: &InitMemoryTable,
         ) -> Vec<F> {
             let mut cells = vec![];
 
             cells.append(&mut msg_of_instruction_table(instruction_table));
             cells.append(&mut msg_of_br_table(br_table, elem_table));
-            cells.append(&mut msg_of_init_memory_table(init_memory_table));
 
             for _ in cells.len()..(max_image_table_rows() as usize) {
                 cells.push(F::zero());
@@ -184,7 +174,6 @@ impl<F: FieldExt> EncodeCompilationTableValues<F> for CompilationTable {
             &self.itable,
             &self.itable.create_brtable(),
             &self.elem_table,
-            &self.imtable,
         );
 
         ImageTableLayouter {
@@ -195,12 +184,37 @@ impl<F: FieldExt> EncodeCompilationTableValues<F> for CompilationTable {
             lookup_entries: Some(lookup_entries),
         }
     }
+
+    fn encode_init_memory_table_values(&self) -> Vec<F> {
+        let heap_entries = self.imtable.filter(LocationType::Heap);
+        let global_entries = self.imtable.filter(LocationType::Global);
+
+        let mut cells = vec![];
+
+        cells.push(bn_to_field(
+            &ImageTableEncoder::InitMemory.encode(BigUint::from(0u64)),
+        ));
+
+        for v in heap_entries.into_iter().chain(global_entries.into_iter()) {
+            cells.push(bn_to_field::<F>(
+                &ImageTableEncoder::InitMemory.encode(v.encode()),
+            ));
+        }
+
+        #[cfg(feature = "uniform-circuit")]
+        for _ in cells.len()..(max_init_memory_table_rows() as usize) {
+            cells.push(F::zero());
+        }
+
+        cells
+    }
 }
 
 #[cfg(feature = "uniform-circuit")]
 #[derive(Clone)]
 pub struct ImageTableConfig<F: FieldExt> {
     col: Column<halo2_proofs::plonk::Advice>,
+    init_memory_col: Column<halo2_proofs::plonk::Advice>,
     _mark: PhantomData<F>,
 }
 
@@ -208,6 +222,7 @@ pub struct ImageTableConfig<F: FieldExt> {
 #[derive(Clone)]
 pub struct ImageTableConfig<F: FieldExt> {
     col: Column<halo2_proofs::plonk::Fixed>,
+    init_memory_col: Column<halo2_proofs::plonk::Fixed>,
     _mark: PhantomData<F>,
 }
 
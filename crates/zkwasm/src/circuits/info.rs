@@ -0,0 +1,204 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Circuit;
+use halo2_proofs::plonk::ConstraintSystem;
+use specs::host_function::HostPlugin;
+use specs::itable::OpcodeClass;
+use specs::itable::OpcodeClassPlain;
+use strum::IntoEnumIterator;
+
+use super::capacity::available_rows;
+use super::capacity::CircuitTable;
+use super::config::set_zkwasm_k;
+use super::test_circuit::VAR_COLUMNS;
+use super::TestCircuit;
+
+/// The cells of a type allocated in an entry of a table out of the available ones.
+#[derive(Debug, Clone)]
+pub struct CellUsage {
+    pub cell_type: String,
+    pub allocated: usize,
+    pub available: usize,
+}
+
+/// Columns, lookups and gates added to the constraint system by a table.
+#[derive(Debug, Clone, Default)]
+pub struct TableColumns {
+    pub table: &'static str,
+    pub advice: usize,
+    /// Advice columns taken from the `VAR_COLUMNS` shared ones, which are
+    /// counted by `advice` of the common entry.
+    pub var_advice: usize,
+    pub fixed: usize,
+    pub instance: usize,
+    pub lookups: usize,
+    pub gates: usize,
+}
+
+/// Attribute what is added to the constraint system between two records to a table.
+pub(crate) struct ColumnProfiler {
+    last: TableColumns,
+    tables: Vec<TableColumns>,
+}
+
+impl ColumnProfiler {
+    fn snapshot<F: FieldExt>(meta: &ConstraintSystem<F>, free_var_columns: usize) -> TableColumns {
+        TableColumns {
+            table: "",
+            advice: meta.num_advice_columns(),
+            var_advice: VAR_COLUMNS - free_var_columns,
+            fixed: meta.num_fixed_columns(),
+            instance: meta.num_instance_columns(),
+            lookups: meta.lookups().len(),
+            gates: meta.gates().len(),
+        }
+    }
+
+    pub(crate) fn new<F: FieldExt>(meta: &ConstraintSystem<F>) -> Self {
+        Self {
+            last: Self::snapshot(meta, VAR_COLUMNS),
+            tables: vec![],
+        }
+    }
+
+    /// Record the table `table`, `free_var_columns` is the number of the
+    /// `VAR_COLUMNS` not taken yet. Tables adding nothing are skipped.
+    pub(crate) fn record<F: FieldExt>(
+        &mut self,
+        table: &'static str,
+        meta: &ConstraintSystem<F>,
+        free_var_columns: usize,
+    ) {
+        let current = Self::snapshot(meta, free_var_columns);
+        let columns = TableColumns {
+            table,
+            advice: current.advice - self.last.advice,
+            var_advice: current.var_advice - self.last.var_advice,
+            fixed: current.fixed - self.last.fixed,
+            instance: current.instance - self.last.instance,
+            lookups: current.lookups - self.last.lookups,
+            gates: current.gates - self.last.gates,
+        };
+
+        if columns.advice
+            + columns.var_advice
+            + columns.fixed
+            + columns.instance
+            + columns.lookups
+            + columns.gates
+            != 0
+        {
+            self.tables.push(columns);
+        }
+        self.last = current;
+    }
+
+    pub(crate) fn finish(self) -> Vec<TableColumns> {
+        self.tables
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GateInfo {
+    pub name: String,
+    pub constraints: usize,
+    pub degree: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct OpcodeCells {
+    pub opcode: String,
+    /// The cells of the entry, including the ones of the common config.
+    pub cells: Vec<CellUsage>,
+}
+
+/// The configuration of the circuit under K, to keep track of the cost of
+/// circuit changes.
+#[derive(Debug, Clone)]
+pub struct CircuitInfo {
+    pub k: u32,
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    pub lookups: usize,
+    /// The degree of the constraint system, including lookups and permutation.
+    pub degree: usize,
+    pub tables: Vec<TableColumns>,
+    pub gates: Vec<GateInfo>,
+    pub etable_common_cells: Vec<CellUsage>,
+    pub opcode_cells: Vec<OpcodeCells>,
+    pub mtable_cells: Vec<CellUsage>,
+    pub max_available_rows: usize,
+    /// The rows available to each table.
+    pub capacities: Vec<(CircuitTable, usize)>,
+}
+
+fn opcode_name(op: OpcodeClassPlain) -> String {
+    let foreign_plugin_start = OpcodeClass::ForeignPluginStart as usize;
+
+    let name = if op.index() >= foreign_plugin_start {
+        HostPlugin::iter()
+            .nth(op.index() - foreign_plugin_start)
+            .map(|plugin| format!("{:?}", plugin))
+    } else {
+        OpcodeClass::iter()
+            .find(|class| *class as usize == op.index())
+            .map(|class| format!("{:?}", class))
+    };
+
+    name.unwrap_or(format!("{:?}", op))
+}
+
+impl CircuitInfo {
+    /// Configure the circuit under `k`, which is set as the K of zkWasm as
+    /// `ZkWasmLoader::new` does.
+    pub fn new<F: FieldExt>(k: u32) -> Self {
+        set_zkwasm_k(k);
+
+        let mut meta = ConstraintSystem::<F>::default();
+        let config = TestCircuit::<F>::configure(&mut meta);
+        let blinding_factors = meta.blinding_factors();
+
+        let gates = meta
+            .gates()
+            .iter()
+            .map(|gate| GateInfo {
+                name: gate.name().to_string(),
+                constraints: gate.polynomials().len(),
+                degree: gate
+                    .polynomials()
+                    .iter()
+                    .map(|polynomial| polynomial.degree())
+                    .max()
+                    .unwrap_or(0),
+            })
+            .collect();
+
+        let opcode_cells = config
+            .etable
+            .opcode_cells
+            .iter()
+            .map(|(op, cells)| OpcodeCells {
+                opcode: opcode_name(*op),
+                cells: cells.clone(),
+            })
+            .collect();
+
+        CircuitInfo {
+            k,
+            advice_columns: meta.num_advice_columns(),
+            fixed_columns: meta.num_fixed_columns(),
+            instance_columns: meta.num_instance_columns(),
+            lookups: meta.lookups().len(),
+            degree: meta.degree(),
+            tables: config.table_columns,
+            gates,
+            etable_common_cells: config.etable.common_cells,
+            opcode_cells,
+            mtable_cells: config.mtable.cells,
+            max_available_rows: config.max_available_rows,
+            capacities: CircuitTable::iter()
+                .map(|table| (table, available_rows(table, k, blinding_factors)))
+                .collect(),
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use crate::circuits::utils::Context;
+use crate::foreign::context::circuits::assign::ExtractContextFromTrace;
+use crate::foreign::context::ContextHashes;
 
+use anyhow::Result;
 use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::plonk::ConstraintSystem;
 use halo2_proofs::plonk::Expression;
@@ -14,11 +17,17 @@ pub(crate) mod etable;
 mod bit_table;
 mod external_host_call_table;
 mod mtable;
+#[cfg(test)]
+pub(crate) mod tamper;
 mod traits;
 
+pub mod capacity;
 pub mod config;
 pub mod image_table;
+pub mod info;
 pub mod jtable;
+#[cfg(any(feature = "uniform-circuit", feature = "continuation"))]
+pub mod poseidon;
 pub mod rtable;
 pub mod test_circuit;
 pub mod utils;
@@ -26,6 +35,39 @@ pub mod utils;
 pub type CompilationTable = specs::CompilationTable;
 pub type ExecutionTable = specs::ExecutionTable;
 
+/// The public inputs of a proof that are not fixed by its image. The context
+/// hashes and the consumed steps are only present with the features exposing
+/// them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublicInputs<F: FieldExt> {
+    pub instances: Vec<F>,
+    #[cfg(feature = "continuation")]
+    pub context_hashes: ContextHashes<F>,
+    #[cfg(feature = "public-eid")]
+    pub consumed_steps: u64,
+}
+
+impl<F: FieldExt> PublicInputs<F> {
+    /// Read the public inputs back from the instance columns of a proof.
+    pub fn from_instance_columns(columns: &Vec<Vec<F>>) -> Option<Self> {
+        #[allow(unused_mut, unused_variables)]
+        let mut trailing_columns = columns.iter().skip(1).rev();
+
+        #[cfg(feature = "public-eid")]
+        let consumed_steps = consumed_steps_from_instances(trailing_columns.next()?)?;
+        #[cfg(feature = "continuation")]
+        let context_hashes = ContextHashes::from_instances(trailing_columns.next()?)?;
+
+        Some(PublicInputs {
+            instances: columns.first()?.clone(),
+            #[cfg(feature = "continuation")]
+            context_hashes,
+            #[cfg(feature = "public-eid")]
+            consumed_steps,
+        })
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct TestCircuit<F: FieldExt> {
     pub tables: Tables,
@@ -39,6 +81,74 @@ impl<F: FieldExt> TestCircuit<F> {
             _data: PhantomData,
         }
     }
+
+    /// Instance columns of the circuit, the uniform circuit exposes the
+    /// poseidon hash of its image in the next column, followed by the column
+    /// of the context hashes of the continuation circuit and the column of
+    /// the termination eid with `public-eid`.
+    pub fn instance_columns(&self, instances: &Vec<F>) -> Result<Vec<Vec<F>>> {
+        #[allow(unused_mut)]
+        let mut columns = vec![instances.clone()];
+
+        #[cfg(feature = "uniform-circuit")]
+        {
+            use crate::checksum::CompilationTableWithPoseidon;
+            use crate::checksum::ImageCheckSum;
+
+            let image_hash = CompilationTableWithPoseidon {
+                table: &self.tables.compilation_tables,
+            }
+            .checksum();
+
+            columns.push(vec![image_hash]);
+        }
+
+        #[cfg(feature = "continuation")]
+        columns.push(self.context_hashes()?.instances());
+
+        #[cfg(feature = "public-eid")]
+        columns.push(vec![termination_eid(self.consumed_steps())]);
+
+        Ok(columns)
+    }
+
+    /// Public inputs of the proof of the circuit with `instances`.
+    pub fn public_inputs(&self, instances: &Vec<F>) -> Result<PublicInputs<F>> {
+        Ok(PublicInputs {
+            instances: instances.clone(),
+            #[cfg(feature = "continuation")]
+            context_hashes: self.context_hashes()?,
+            #[cfg(feature = "public-eid")]
+            consumed_steps: self.consumed_steps(),
+        })
+    }
+
+    /// Steps executed by the trace, i.e. the entries of the event table.
+    pub fn consumed_steps(&self) -> u64 {
+        self.tables.execution_tables.etable.entries().len() as u64
+    }
+
+    /// Hashes of the context inputs and outputs of the execution.
+    pub fn context_hashes(&self) -> Result<ContextHashes<F>> {
+        let etable = &self.tables.execution_tables.etable;
+
+        ContextHashes::new(&etable.get_context_inputs(), &etable.get_context_outputs())
+    }
+}
+
+/// The eid of the termination exposed with `public-eid`, eid starts from 1.
+#[cfg(feature = "public-eid")]
+pub fn termination_eid<F: FieldExt>(consumed_steps: u64) -> F {
+    F::from(consumed_steps + 1)
+}
+
+/// The steps proven by the instance column of the termination eid.
+#[cfg(feature = "public-eid")]
+pub fn consumed_steps_from_instances<F: FieldExt>(instances: &Vec<F>) -> Option<u64> {
+    match instances[..] {
+        [eid] => u64::try_from(eid.get_lower_128()).ok()?.checked_sub(1),
+        _ => None,
+    }
 }
 
 trait Encode {
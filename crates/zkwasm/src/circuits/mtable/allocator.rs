@@ -10,6 +10,7 @@ use halo2_proofs::plonk::Fixed;
 use halo2_proofs::plonk::VirtualCells;
 
 use crate::circuits::cell::*;
+use crate::circuits::info::CellUsage;
 use crate::circuits::rtable::RangeTableConfig;
 use crate::circuits::utils::bit::BitColumn;
 use crate::circuits::utils::common_range::CommonRangeColumn;
@@ -173,6 +174,27 @@ impl<F: FieldExt> MemoryTableCellAllocator<F> {
         }
     }
 
+    /// The cells allocated out of the cells of each type in an entry.
+    pub(super) fn cell_usages(&self) -> Vec<CellUsage> {
+        let mut usages: Vec<_> = self
+            .free_cells
+            .iter()
+            .map(|(t, (col, rot))| CellUsage {
+                cell_type: format!("{:?}", t),
+                allocated: col * MEMORY_TABLE_ENTRY_ROWS as usize + *rot as usize,
+                available: self.all_cols.get(t).unwrap().len() * MEMORY_TABLE_ENTRY_ROWS as usize,
+            })
+            .collect();
+
+        usages.push(CellUsage {
+            cell_type: "U64".to_string(),
+            allocated: U64_CELLS - self.free_u64_cells.len(),
+            available: U64_CELLS,
+        });
+
+        usages
+    }
+
     fn alloc(&mut self, t: &MemoryTableCellType) -> AllocatedCell<F> {
         let v = self.free_cells.get_mut(t).unwrap();
         let res = AllocatedCell {
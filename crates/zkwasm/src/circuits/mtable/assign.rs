@@ -208,6 +208,11 @@ impl<F: FieldExt> MemoryTableChip<F> {
         mtable: &MemoryWritingTable,
         imtable: &InitMemoryTable,
     ) -> Result<(), Error> {
+        #[cfg(test)]
+        let tampered = crate::circuits::tamper::tamper_memory_writing_table(mtable);
+        #[cfg(test)]
+        let mtable = &*tampered;
+
         debug!("size of memory writing table: {}", mtable.0.len());
         assert!(mtable.0.len() * (MEMORY_TABLE_ENTRY_ROWS as usize) < self.maximal_available_rows);
 
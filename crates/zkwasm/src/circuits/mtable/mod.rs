@@ -1,6 +1,7 @@
 use self::allocator::*;
 use super::cell::*;
 use super::image_table::ImageTableConfig;
+use super::info::CellUsage;
 use super::rtable::RangeTableConfig;
 use super::traits::ConfigureLookupTable;
 use crate::constant_from;
@@ -56,6 +57,8 @@ pub struct MemoryTableConfig<F: FieldExt> {
     init_encode_cell: AllocatedUnlimitedCell<F>,
 
     value: AllocatedU64Cell<F>,
+
+    pub(crate) cells: Vec<CellUsage>,
 }
 
 impl<F: FieldExt> MemoryTableConfig<F> {
@@ -312,6 +315,8 @@ impl<F: FieldExt> MemoryTableConfig<F> {
             .collect::<Vec<_>>()
         });
 
+        let cells = allocator.cell_usages();
+
         Self {
             entry_sel,
             enabled_cell,
@@ -339,6 +344,7 @@ impl<F: FieldExt> MemoryTableConfig<F> {
             value,
             init_encode_cell,
             encode_cell,
+            cells,
         }
     }
 }
@@ -0,0 +1,300 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::Cell;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::plonk::Advice;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::Fixed;
+use std::marker::PhantomData;
+
+use crate::checksum::poseidon_hash::PoseidonSpec;
+use crate::checksum::poseidon_hash::Round;
+use crate::checksum::poseidon_hash::RATE;
+use crate::checksum::poseidon_hash::T;
+use crate::constant;
+use crate::constant_from;
+use crate::curr;
+use crate::fixed_curr;
+use crate::next;
+
+const COEFFS: usize = 1 + T + RATE;
+
+/*
+ * In-circuit poseidon hash, which is shared by the image hash of the uniform
+ * circuit and the context hashes of the continuation circuit.
+ *
+ * One row per round of the poseidon permutation.
+ *
+ * coeffs layout:
+ *   absorb/full/pre sparse rounds: coeffs[0..T] are the round constants
+ *   partial rounds: coeffs[0] is the round constant, coeffs[1..1 + T] is the
+ *   first row of the sparse mds and coeffs[1 + T..] is its col hat.
+ *
+ * aux holds the absorbed inputs of an absorb round and the sbox output of
+ * state[0] in aux[0] of a partial round.
+ */
+#[derive(Clone)]
+pub struct PoseidonHashConfig<F: FieldExt> {
+    sel_absorb: Column<Fixed>,
+    sel_full: Column<Fixed>,
+    sel_pre_sparse: Column<Fixed>,
+    sel_partial: Column<Fixed>,
+    coeffs: [Column<Fixed>; COEFFS],
+    state: [Column<Advice>; T],
+    aux: [Column<Advice>; RATE],
+    _mark: PhantomData<F>,
+}
+
+impl<F: FieldExt> PoseidonHashConfig<F> {
+    pub(in crate::circuits) fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let spec = PoseidonSpec::<F>::new();
+
+        let sel_absorb = meta.fixed_column();
+        let sel_full = meta.fixed_column();
+        let sel_pre_sparse = meta.fixed_column();
+        let sel_partial = meta.fixed_column();
+        let coeffs = [(); COEFFS].map(|_| meta.fixed_column());
+        let state = [(); T].map(|_| meta.advice_column());
+        let aux = [(); RATE].map(|_| meta.advice_column());
+
+        for col in state.iter().chain(aux.iter()) {
+            meta.enable_equality(*col);
+        }
+
+        meta.create_gate("poseidon hash absorb", |meta| {
+            let sel = fixed_curr!(meta, sel_absorb);
+
+            (0..T)
+                .map(|i| {
+                    let input = if i == 0 {
+                        constant_from!(0)
+                    } else {
+                        curr!(meta, aux[i - 1])
+                    };
+
+                    sel.clone()
+                        * (next!(meta, state[i])
+                            - curr!(meta, state[i])
+                            - input
+                            - fixed_curr!(meta, coeffs[i]))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let sbox = |x: Expression<F>| x.clone() * x.clone() * x.clone() * x.clone() * x;
+
+        for (name, sel, matrix) in [
+            ("poseidon hash full round", sel_full, spec.mds),
+            (
+                "poseidon hash pre sparse round",
+                sel_pre_sparse,
+                spec.pre_sparse_mds,
+            ),
+        ] {
+            meta.create_gate(name, |meta| {
+                let sel = fixed_curr!(meta, sel);
+                let words = (0..T)
+                    .map(|j| sbox(curr!(meta, state[j])) + fixed_curr!(meta, coeffs[j]))
+                    .collect::<Vec<_>>();
+
+                (0..T)
+                    .map(|i| {
+                        let acc = words
+                            .iter()
+                            .zip(matrix[i].iter())
+                            .fold(constant_from!(0), |acc, (word, coeff)| {
+                                acc + constant!(*coeff) * word.clone()
+                            });
+
+                        sel.clone() * (next!(meta, state[i]) - acc)
+                    })
+                    .collect::<Vec<_>>()
+            });
+        }
+
+        meta.create_gate("poseidon hash partial round", |meta| {
+            let sel = fixed_curr!(meta, sel_partial);
+            let word0 = curr!(meta, aux[0]);
+
+            let mut constraints = vec![
+                sel.clone()
+                    * (word0.clone() - sbox(curr!(meta, state[0])) - fixed_curr!(meta, coeffs[0])),
+            ];
+
+            let acc = (0..T).fold(constant_from!(0), |acc, j| {
+                let word = if j == 0 {
+                    word0.clone()
+                } else {
+                    curr!(meta, state[j])
+                };
+
+                acc + fixed_curr!(meta, coeffs[1 + j]) * word
+            });
+            constraints.push(sel.clone() * (next!(meta, state[0]) - acc));
+
+            for i in 1..T {
+                constraints.push(
+                    sel.clone()
+                        * (next!(meta, state[i])
+                            - curr!(meta, state[i])
+                            - fixed_curr!(meta, coeffs[T + i]) * word0.clone()),
+                );
+            }
+
+            constraints
+        });
+
+        PoseidonHashConfig {
+            sel_absorb,
+            sel_full,
+            sel_pre_sparse,
+            sel_partial,
+            coeffs,
+            state,
+            aux,
+            _mark: PhantomData,
+        }
+    }
+}
+
+/// Rows of the region hashing a message of `message_len`: a row per round of
+/// each padded chunk of the message and the row of the final state.
+pub(crate) fn poseidon_hash_region_rows<F: FieldExt>(message_len: usize) -> usize {
+    // The padding appends a one to the message.
+    (message_len / RATE + 1) * PoseidonSpec::<F>::new().rounds.len() + 1
+}
+
+pub struct PoseidonHashChip<F: FieldExt> {
+    config: PoseidonHashConfig<F>,
+    spec: PoseidonSpec<F>,
+}
+
+impl<F: FieldExt> PoseidonHashChip<F> {
+    pub fn new(config: PoseidonHashConfig<F>) -> Self {
+        PoseidonHashChip {
+            config,
+            spec: PoseidonSpec::new(),
+        }
+    }
+
+    /// Hash `message` and return the cell of the hash, `input_cells` are the
+    /// cells of `message` which the inputs of the hash are bound to.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        message: Vec<F>,
+        input_cells: &Vec<Cell>,
+    ) -> Result<Cell, Error> {
+        assert_eq!(message.len(), input_cells.len());
+
+        layouter.assign_region(
+            || "poseidon hash",
+            |mut region| {
+                let (rows, last_state) = self.spec.hash_with_trace(&message);
+                let rounds = self.spec.rounds.len();
+
+                for (offset, (state, aux)) in rows.iter().enumerate() {
+                    let round = &self.spec.rounds[offset % rounds];
+
+                    let (sel, coeffs) = match round {
+                        Round::Absorb(constants) => (self.config.sel_absorb, constants.to_vec()),
+                        Round::Full(constants) => (self.config.sel_full, constants.to_vec()),
+                        Round::PreSparse(constants) => {
+                            (self.config.sel_pre_sparse, constants.to_vec())
+                        }
+                        Round::Partial {
+                            constant,
+                            row,
+                            col_hat,
+                        } => (
+                            self.config.sel_partial,
+                            [vec![*constant], row.to_vec(), col_hat.to_vec()].concat(),
+                        ),
+                    };
+
+                    region.assign_fixed(|| "poseidon hash sel", sel, offset, || Ok(F::one()))?;
+                    for (col, coeff) in self.config.coeffs.iter().zip(coeffs.iter()) {
+                        region.assign_fixed(
+                            || "poseidon hash coeff",
+                            *col,
+                            offset,
+                            || Ok(*coeff),
+                        )?;
+                    }
+
+                    for (col, value) in self.config.state.iter().zip(state.iter()) {
+                        if offset == 0 {
+                            region.assign_advice_from_constant(
+                                || "poseidon hash initial state",
+                                *col,
+                                offset,
+                                *value,
+                            )?;
+                        } else {
+                            region.assign_advice(
+                                || "poseidon hash state",
+                                *col,
+                                offset,
+                                || Ok(*value),
+                            )?;
+                        }
+                    }
+
+                    for (i, (col, value)) in self.config.aux.iter().zip(aux.iter()).enumerate() {
+                        let index = offset / rounds * RATE + i;
+
+                        match round {
+                            Round::Absorb(_) if index < input_cells.len() => {
+                                let cell = region
+                                    .assign_advice(
+                                        || "poseidon hash input",
+                                        *col,
+                                        offset,
+                                        || Ok(*value),
+                                    )?
+                                    .cell();
+                                region.constrain_equal(cell, input_cells[index])?;
+                            }
+                            // Padding inputs are constants.
+                            Round::Absorb(_) => {
+                                region.assign_advice_from_constant(
+                                    || "poseidon hash padding",
+                                    *col,
+                                    offset,
+                                    *value,
+                                )?;
+                            }
+                            _ => {
+                                region.assign_advice(
+                                    || "poseidon hash aux",
+                                    *col,
+                                    offset,
+                                    || Ok(*value),
+                                )?;
+                            }
+                        }
+                    }
+                }
+
+                let mut hash_cell = None;
+                for (i, (col, value)) in self.config.state.iter().zip(last_state.iter()).enumerate()
+                {
+                    let cell = region.assign_advice(
+                        || "poseidon hash state",
+                        *col,
+                        rows.len(),
+                        || Ok(*value),
+                    )?;
+
+                    if i == 1 {
+                        hash_cell = Some(cell.cell());
+                    }
+                }
+
+                Ok(hash_cell.unwrap())
+            },
+        )
+    }
+}
@@ -0,0 +1,145 @@
+//! Mutations of the witness for the soundness tests.
+//!
+//! `EventTableChip::assign`, `MemoryTableChip::assign` and the context helper
+//! table of `TestCircuit` apply the mutations installed by `with_mutations` to
+//! the witness right before assigning it, so
+//! a test can check that `MockProver` rejects a tampered trace. The mutations
+//! are installed per thread, as `MockProver` synthesizes on the calling thread.
+//!
+//! The names of the constraints and lookups of each op config are recorded
+//! when the event table is configured, so that a test can check that a
+//! tampered step is rejected by the op config of its opcode class.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use specs::itable::OpcodeClass;
+use specs::itable::OpcodeClassPlain;
+use specs::mtable::AccessType;
+use specs::step::StepInfo;
+
+use crate::circuits::utils::table_entry::EventTableWithMemoryInfo;
+use crate::circuits::utils::table_entry::MemoryWritingTable;
+
+#[derive(Clone, Debug)]
+pub(crate) enum Mutation {
+    /// Replace the step info of the step `eid`.
+    StepInfo { eid: u32, step_info: StepInfo },
+    /// Replace the value written by the step `eid`, in both the etable and the
+    /// mtable, so that the memory lookups of the step still hold.
+    WrittenValue { eid: u32, value: u64 },
+    /// Remove the entries written by the step `eid` from the mtable.
+    DropWrite { eid: u32 },
+    /// Replace the context outputs assigned to the context helper table and hashed.
+    #[cfg(feature = "continuation")]
+    ContextOutputs { outputs: Vec<u64> },
+}
+
+thread_local! {
+    static MUTATIONS: RefCell<Vec<Mutation>> = RefCell::new(vec![]);
+    static OP_CONSTRAINT_NAMES: RefCell<BTreeMap<OpcodeClassPlain, Vec<&'static str>>> =
+        RefCell::new(BTreeMap::new());
+}
+
+pub(in crate::circuits) fn record_op_constraint_names(
+    op: OpcodeClassPlain,
+    names: Vec<&'static str>,
+) {
+    OP_CONSTRAINT_NAMES.with(|recorded| recorded.borrow_mut().insert(op, names));
+}
+
+/// The names of the constraints and lookups of the op config of `class`, which
+/// are recorded once a circuit is configured on this thread.
+pub(crate) fn op_constraint_names(class: OpcodeClass) -> Vec<&'static str> {
+    OP_CONSTRAINT_NAMES.with(|recorded| {
+        recorded
+            .borrow()
+            .get(&OpcodeClassPlain(class as usize))
+            .cloned()
+            .unwrap_or_default()
+    })
+}
+
+/// Run `f` with `mutations` applied to the witness of the circuits synthesized by it.
+pub(crate) fn with_mutations<R>(mutations: Vec<Mutation>, f: impl FnOnce() -> R) -> R {
+    MUTATIONS.with(|installed| *installed.borrow_mut() = mutations);
+    let result = f();
+    MUTATIONS.with(|installed| installed.borrow_mut().clear());
+
+    result
+}
+
+fn installed_mutations() -> Vec<Mutation> {
+    MUTATIONS.with(|installed| installed.borrow().clone())
+}
+
+pub(in crate::circuits) fn tamper_event_table(
+    event_table: &EventTableWithMemoryInfo,
+) -> Cow<EventTableWithMemoryInfo> {
+    let mutations = installed_mutations();
+    if mutations.is_empty() {
+        return Cow::Borrowed(event_table);
+    }
+
+    let mut event_table = event_table.clone();
+    for mutation in mutations {
+        match mutation {
+            Mutation::StepInfo { eid, step_info } => event_table
+                .0
+                .iter_mut()
+                .filter(|entry| entry.eentry.eid == eid)
+                .for_each(|entry| entry.eentry.step_info = step_info.clone()),
+            Mutation::WrittenValue { eid, value } => event_table
+                .0
+                .iter_mut()
+                .filter(|entry| entry.eentry.eid == eid)
+                .flat_map(|entry| entry.memory_rw_entires.iter_mut())
+                .filter(|rw| rw.entry.atype == AccessType::Write)
+                .for_each(|rw| rw.entry.value = value),
+            Mutation::DropWrite { .. } => (),
+            #[cfg(feature = "continuation")]
+            Mutation::ContextOutputs { .. } => (),
+        }
+    }
+
+    Cow::Owned(event_table)
+}
+
+pub(in crate::circuits) fn tamper_memory_writing_table(
+    mtable: &MemoryWritingTable,
+) -> Cow<MemoryWritingTable> {
+    let mutations = installed_mutations();
+    if mutations.is_empty() {
+        return Cow::Borrowed(mtable);
+    }
+
+    let mut mtable = mtable.clone();
+    for mutation in mutations {
+        match mutation {
+            Mutation::WrittenValue { eid, value } => mtable
+                .0
+                .iter_mut()
+                .filter(|entry| entry.entry.eid == eid && entry.entry.atype == AccessType::Write)
+                .for_each(|entry| entry.entry.value = value),
+            Mutation::DropWrite { eid } => mtable
+                .0
+                .retain(|entry| entry.entry.eid != eid || entry.entry.atype != AccessType::Write),
+            Mutation::StepInfo { .. } => (),
+            #[cfg(feature = "continuation")]
+            Mutation::ContextOutputs { .. } => (),
+        }
+    }
+
+    Cow::Owned(mtable)
+}
+
+#[cfg(feature = "continuation")]
+pub(in crate::circuits) fn tamper_context_outputs(outputs: Vec<u64>) -> Vec<u64> {
+    installed_mutations()
+        .into_iter()
+        .fold(outputs, |outputs, mutation| match mutation {
+            Mutation::ContextOutputs { outputs } => outputs,
+            _ => outputs,
+        })
+}
@@ -10,12 +10,20 @@ use halo2_proofs::plonk::Column;
 use halo2_proofs::plonk::ConstraintSystem;
 use halo2_proofs::plonk::Error;
 use halo2_proofs::plonk::Fixed;
+#[cfg(any(
+    feature = "uniform-circuit",
+    feature = "continuation",
+    feature = "public-eid"
+))]
+use halo2_proofs::plonk::Instance;
 use log::debug;
 use specs::ExecutionTable;
 use specs::Tables;
 
 use crate::circuits::bit_table::BitTableChip;
 use crate::circuits::bit_table::BitTableConfig;
+#[cfg(feature = "continuation")]
+use crate::circuits::capacity::CircuitTable;
 use crate::circuits::etable::EventTableChip;
 use crate::circuits::etable::EventTableConfig;
 use crate::circuits::external_host_call_table::ExternalHostCallChip;
@@ -23,10 +31,16 @@ use crate::circuits::external_host_call_table::ExternalHostCallTableConfig;
 use crate::circuits::image_table::EncodeCompilationTableValues;
 use crate::circuits::image_table::ImageTableChip;
 use crate::circuits::image_table::ImageTableLayouter;
+use crate::circuits::info::ColumnProfiler;
+use crate::circuits::info::TableColumns;
 use crate::circuits::jtable::JumpTableChip;
 use crate::circuits::jtable::JumpTableConfig;
 use crate::circuits::mtable::MemoryTableChip;
 use crate::circuits::mtable::MemoryTableConfig;
+#[cfg(any(feature = "uniform-circuit", feature = "continuation"))]
+use crate::circuits::poseidon::PoseidonHashChip;
+#[cfg(any(feature = "uniform-circuit", feature = "continuation"))]
+use crate::circuits::poseidon::PoseidonHashConfig;
 use crate::circuits::rtable::RangeTableChip;
 use crate::circuits::rtable::RangeTableConfig;
 use crate::circuits::utils::table_entry::EventTableWithMemoryInfo;
@@ -38,6 +52,8 @@ use crate::foreign::context::circuits::assign::ContextContHelperTableChip;
 use crate::foreign::context::circuits::assign::ExtractContextFromTrace;
 use crate::foreign::context::circuits::ContextContHelperTableConfig;
 use crate::foreign::context::circuits::CONTEXT_FOREIGN_TABLE_KEY;
+#[cfg(feature = "continuation")]
+use crate::foreign::context::context_hash_message;
 use crate::foreign::foreign_table_enable_lines;
 use crate::foreign::wasm_input_helper::circuits::WasmInputHelperTableConfig;
 use crate::foreign::wasm_input_helper::circuits::WASM_INPUT_FOREIGN_TABLE_KEY;
@@ -50,7 +66,7 @@ pub const VAR_COLUMNS: usize = 51;
 
 // Reserve a few rows to keep usable rows away from blind rows.
 // The maximal step size of all tables is bit_table::STEP_SIZE.
-const RESERVE_ROWS: usize = crate::circuits::bit_table::STEP_SIZE;
+pub(crate) const RESERVE_ROWS: usize = crate::circuits::bit_table::STEP_SIZE;
 
 #[derive(Clone)]
 pub struct TestCircuitConfig<F: FieldExt> {
@@ -62,10 +78,19 @@ pub struct TestCircuitConfig<F: FieldExt> {
     bit_table: BitTableConfig<F>,
     external_host_call_table: ExternalHostCallTableConfig<F>,
     context_helper_table: ContextContHelperTableConfig<F>,
+    #[cfg(any(feature = "uniform-circuit", feature = "continuation"))]
+    poseidon_hash: PoseidonHashConfig<F>,
+    #[cfg(feature = "uniform-circuit")]
+    image_hash: Column<Instance>,
+    #[cfg(feature = "continuation")]
+    context_hash: Column<Instance>,
+    #[cfg(feature = "public-eid")]
+    termination_eid: Column<Instance>,
 
     foreign_table_from_zero_index: Column<Fixed>,
 
-    max_available_rows: usize,
+    pub(crate) max_available_rows: usize,
+    pub(crate) table_columns: Vec<TableColumns>,
 }
 
 impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
@@ -81,6 +106,8 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let mut profiler = ColumnProfiler::new(meta);
+
         /*
          * Allocate a column to enable assign_advice_from_constant.
          */
@@ -92,18 +119,27 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
         let foreign_table_from_zero_index = meta.fixed_column();
 
         let mut cols = [(); VAR_COLUMNS].map(|_| meta.advice_column()).into_iter();
+        profiler.record("common", meta, cols.len());
 
         let rtable = RangeTableConfig::configure(meta);
+        profiler.record("rtable", meta, cols.len());
         let image_table = ImageTableConfig::configure(meta);
+        profiler.record("image_table", meta, cols.len());
         let mtable = MemoryTableConfig::configure(meta, &mut cols, &rtable, &image_table);
+        profiler.record("mtable", meta, cols.len());
         let jtable = JumpTableConfig::configure(meta, &mut cols);
+        profiler.record("jtable", meta, cols.len());
         let external_host_call_table = ExternalHostCallTableConfig::configure(meta);
+        profiler.record("external_host_call_table", meta, cols.len());
         let bit_table = BitTableConfig::configure(meta, &rtable);
+        profiler.record("bit_table", meta, cols.len());
 
         let wasm_input_helper_table =
             WasmInputHelperTableConfig::configure(meta, foreign_table_from_zero_index);
+        profiler.record("wasm_input_helper_table", meta, cols.len());
         let context_helper_table =
             ContextContHelperTableConfig::configure(meta, foreign_table_from_zero_index);
+        profiler.record("context_helper_table", meta, cols.len());
 
         let mut foreign_table_configs: BTreeMap<_, Box<(dyn ForeignTableConfig<F>)>> =
             BTreeMap::new();
@@ -127,9 +163,38 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
             &external_host_call_table,
             &foreign_table_configs,
         );
+        profiler.record("etable", meta, cols.len());
 
         assert_eq!(cols.count(), 0);
 
+        #[cfg(any(feature = "uniform-circuit", feature = "continuation"))]
+        let poseidon_hash = PoseidonHashConfig::configure(meta);
+        #[cfg(any(feature = "uniform-circuit", feature = "continuation"))]
+        profiler.record("poseidon_hash", meta, 0);
+
+        // The instance columns of the image hash, the context hashes and the
+        // termination eid follow the one of wasm input.
+        #[cfg(feature = "uniform-circuit")]
+        let image_hash = {
+            let col = meta.instance_column();
+            meta.enable_equality(col);
+            col
+        };
+        #[cfg(feature = "continuation")]
+        let context_hash = {
+            let col = meta.instance_column();
+            meta.enable_equality(col);
+            col
+        };
+        #[cfg(feature = "public-eid")]
+        let termination_eid = {
+            let col = meta.instance_column();
+            meta.enable_equality(col);
+            col
+        };
+
+        profiler.record("instances", meta, 0);
+
         let max_available_rows = (1 << zkwasm_k()) - (meta.blinding_factors() + 1 + RESERVE_ROWS);
         debug!("max_available_rows: {:?}", max_available_rows);
 
@@ -142,9 +207,18 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
             bit_table,
             external_host_call_table,
             context_helper_table,
+            #[cfg(any(feature = "uniform-circuit", feature = "continuation"))]
+            poseidon_hash,
+            #[cfg(feature = "uniform-circuit")]
+            image_hash,
+            #[cfg(feature = "continuation")]
+            context_hash,
+            #[cfg(feature = "public-eid")]
+            termination_eid,
             foreign_table_from_zero_index,
 
             max_available_rows,
+            table_columns: profiler.finish(),
         }
     }
 
@@ -164,6 +238,8 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
         let external_host_call_chip =
             ExternalHostCallChip::new(config.external_host_call_table, config.max_available_rows);
         let context_chip = ContextContHelperTableChip::new(config.context_helper_table);
+        #[cfg(any(feature = "uniform-circuit", feature = "continuation"))]
+        let poseidon_hash_chip = PoseidonHashChip::new(config.poseidon_hash);
 
         layouter.assign_region(
             || "foreign helper",
@@ -195,103 +271,178 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
             )?
         );
 
-        let (entry_fid, static_frame_entries, initial_memory_pages, maximal_memory_pages) =
-            layouter.assign_region(
-                || "jtable mtable etable",
-                |region| {
-                    let mut ctx = Context::new(region);
-
-                    let memory_writing_table: MemoryWritingTable =
-                        self.tables.execution_tables.mtable.clone().into();
-
-                    let etable = exec_with_profile!(
-                        || "Prepare memory info for etable",
-                        EventTableWithMemoryInfo::new(
-                            &self.tables.execution_tables.etable,
+        let (etable_permutation_cells, static_frame_entries) = layouter.assign_region(
+            || "jtable mtable etable",
+            |region| {
+                let mut ctx = Context::new(region);
+
+                let memory_writing_table: MemoryWritingTable =
+                    (&self.tables.execution_tables.mtable).into();
+
+                let etable = exec_with_profile!(
+                    || "Prepare memory info for etable",
+                    EventTableWithMemoryInfo::new(
+                        &self.tables.execution_tables.etable,
+                        &self.tables.execution_tables.memory_events,
+                        &self.tables.execution_tables.mtable,
+                        &memory_writing_table,
+                    )
+                );
+
+                let etable_permutation_cells = exec_with_profile!(
+                    || "Assign etable",
+                    echip.assign(
+                        &mut ctx,
+                        &self.tables.compilation_tables.itable,
+                        &etable,
+                        &self.tables.compilation_tables.configure_table,
+                        self.tables.compilation_tables.fid_of_entry,
+                    )?
+                );
+
+                {
+                    ctx.reset();
+                    exec_with_profile!(
+                        || "Assign mtable",
+                        mchip.assign(
+                            &mut ctx,
+                            etable_permutation_cells.rest_mops,
                             &memory_writing_table,
-                        )
+                            &self.tables.compilation_tables.imtable
+                        )?
                     );
+                }
 
-                    let etable_permutation_cells = exec_with_profile!(
-                        || "Assign etable",
-                        echip.assign(
+                let jtable_info = {
+                    ctx.reset();
+                    exec_with_profile!(
+                        || "Assign frame table",
+                        jchip.assign(
                             &mut ctx,
-                            &self.tables.compilation_tables.itable,
-                            &etable,
-                            &self.tables.compilation_tables.configure_table,
-                            self.tables.compilation_tables.fid_of_entry,
+                            &self.tables.execution_tables.jtable,
+                            etable_permutation_cells.rest_jops,
+                            &self.tables.compilation_tables.static_jtable,
                         )?
-                    );
+                    )
+                };
 
-                    {
-                        ctx.reset();
-                        exec_with_profile!(
-                            || "Assign mtable",
-                            mchip.assign(
-                                &mut ctx,
-                                etable_permutation_cells.rest_mops,
-                                &memory_writing_table,
-                                &self.tables.compilation_tables.imtable
-                            )?
-                        );
-                    }
-
-                    let jtable_info = {
-                        ctx.reset();
-                        exec_with_profile!(
-                            || "Assign frame table",
-                            jchip.assign(
-                                &mut ctx,
-                                &self.tables.execution_tables.jtable,
-                                etable_permutation_cells.rest_jops,
-                                &self.tables.compilation_tables.static_jtable,
-                            )?
-                        )
-                    };
-
-                    {
-                        ctx.reset();
-                        exec_with_profile!(
-                            || "Assign bit table",
-                            bit_chip.assign(&mut ctx, &etable)?
-                        );
-                    }
-
-                    Ok((
-                        etable_permutation_cells.fid_of_entry,
-                        jtable_info,
-                        etable_permutation_cells.initial_memory_pages,
-                        etable_permutation_cells.maximal_memory_pages,
-                    ))
-                },
-            )?;
+                {
+                    ctx.reset();
+                    exec_with_profile!(|| "Assign bit table", bit_chip.assign(&mut ctx, &etable)?);
+                }
 
-        exec_with_profile!(
+                Ok((etable_permutation_cells, jtable_info))
+            },
+        )?;
+
+        // The termination eid is the count of executed steps plus one.
+        #[cfg(feature = "public-eid")]
+        layouter.constrain_instance(
+            etable_permutation_cells.termination_eid,
+            config.termination_eid,
+            0,
+        )?;
+
+        let context_inputs = self.tables.execution_tables.etable.get_context_inputs();
+        let context_outputs = self.tables.execution_tables.etable.get_context_outputs();
+        #[cfg(all(test, feature = "continuation"))]
+        let context_outputs = crate::circuits::tamper::tamper_context_outputs(context_outputs);
+
+        #[cfg_attr(not(feature = "continuation"), allow(unused_variables))]
+        let (context_input_cells, context_output_cells) = exec_with_profile!(
             || "Assign context cont chip",
-            context_chip.assign(
-                &mut layouter,
-                &self.tables.execution_tables.etable.get_context_inputs(),
-                &self.tables.execution_tables.etable.get_context_outputs()
-            )?
+            context_chip.assign(&mut layouter, &context_inputs, &context_outputs)?
         );
 
-        exec_with_profile!(
+        #[cfg(feature = "continuation")]
+        {
+            // The hashed counts of the context values are the ones of the event table.
+            layouter.assign_region(
+                || "context indices",
+                |mut region| {
+                    let (input_index, output_index) = etable_permutation_cells.context_indices;
+
+                    region.constrain_equal(context_input_cells[0], input_index)?;
+                    region.constrain_equal(context_output_cells[0], output_index)
+                },
+            )?;
+
+            let hash = |layouter: &mut _, values: &Vec<u64>, table, cells: &Vec<_>| {
+                let message = context_hash_message(values, table).map_err(|_| Error::Synthesis)?;
+
+                poseidon_hash_chip.assign(
+                    layouter,
+                    message.into_iter().map(|v| F::from(v)).collect(),
+                    cells,
+                )
+            };
+
+            let context_input_hash = exec_with_profile!(
+                || "Assign Context Input Hash",
+                hash(
+                    &mut layouter,
+                    &context_inputs,
+                    CircuitTable::ContextInputHelperTable,
+                    &context_input_cells
+                )?
+            );
+            let context_output_hash = exec_with_profile!(
+                || "Assign Context Output Hash",
+                hash(
+                    &mut layouter,
+                    &context_outputs,
+                    CircuitTable::ContextOutputHelperTable,
+                    &context_output_cells
+                )?
+            );
+
+            layouter.constrain_instance(context_input_hash, config.context_hash, 0)?;
+            layouter.constrain_instance(context_output_hash, config.context_hash, 1)?;
+        }
+
+        #[cfg_attr(not(feature = "uniform-circuit"), allow(unused_variables))]
+        let (image_cells, init_memory_cells) = exec_with_profile!(
             || "Assign Image Table",
             image_chip.assign(
                 &mut layouter,
                 self.tables
                     .compilation_tables
                     .encode_compilation_table_values(),
+                self.tables
+                    .compilation_tables
+                    .encode_init_memory_table_values(),
                 ImageTableLayouter {
-                    entry_fid,
+                    entry_fid: etable_permutation_cells.fid_of_entry,
                     static_frame_entries,
-                    initial_memory_pages,
-                    maximal_memory_pages,
+                    initial_memory_pages: etable_permutation_cells.initial_memory_pages,
+                    maximal_memory_pages: etable_permutation_cells.maximal_memory_pages,
                     lookup_entries: None
                 }
             )?
         );
 
+        // The image hash covers both the image table and the init memory table.
+        #[cfg(feature = "uniform-circuit")]
+        {
+            let compilation_tables = &self.tables.compilation_tables;
+            let message = [
+                compilation_tables.encode_compilation_table_values().plain(),
+                compilation_tables.encode_init_memory_table_values(),
+            ]
+            .concat();
+
+            let image_hash = exec_with_profile!(
+                || "Assign Image Hash",
+                poseidon_hash_chip.assign(
+                    &mut layouter,
+                    message,
+                    &[image_cells, init_memory_cells].concat(),
+                )?
+            );
+
+            layouter.constrain_instance(image_hash, config.image_hash, 0)?;
+        }
+
         end_timer!(assign_timer);
 
         Ok(())
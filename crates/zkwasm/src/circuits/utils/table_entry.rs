@@ -2,17 +2,14 @@ use serde::Serialize;
 use specs::etable::EventTable;
 use specs::etable::EventTableEntry;
 use specs::mtable::AccessType;
-use specs::mtable::LocationType;
 use specs::mtable::MTable;
+use specs::mtable::MemoryEvents;
 use specs::mtable::MemoryTableEntry;
-use std::cmp::Ordering;
-use std::collections::BTreeMap;
 use std::env;
 use std::io::Write;
 use std::path::PathBuf;
 
 use crate::circuits::config::zkwasm_k;
-use crate::runtime::memory_event_of_step;
 
 #[derive(Clone, Debug, Serialize)]
 pub(in crate::circuits) struct MemoryWritingEntry {
@@ -27,11 +24,11 @@ impl MemoryWritingEntry {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 pub struct MemoryWritingTable(pub(in crate::circuits) Vec<MemoryWritingEntry>);
 
-impl From<MTable> for MemoryWritingTable {
-    fn from(value: MTable) -> Self {
+impl From<&MTable> for MemoryWritingTable {
+    fn from(value: &MTable) -> Self {
         let maximal_eid = (1u32 << (zkwasm_k() - 1)) - 1;
         let mut index = 0;
 
@@ -69,26 +66,6 @@ impl From<MTable> for MemoryWritingTable {
 }
 
 impl MemoryWritingTable {
-    // (location, offset) |-> Vec<(start_eid, end_eid)>
-    fn build_lookup_mapping(&self) -> BTreeMap<(LocationType, u32), Vec<(u32, u32)>> {
-        let mut mapping = BTreeMap::<_, Vec<(u32, u32)>>::new();
-
-        for entry in &self.0 {
-            let ltype = entry.entry.ltype;
-            let offset = entry.entry.offset;
-            let start_eid = entry.entry.eid;
-            let end_eid = entry.end_eid;
-
-            if let Some(entries) = mapping.get_mut(&(ltype, offset)) {
-                entries.push((start_eid, end_eid));
-            } else {
-                mapping.insert((ltype, offset), vec![(start_eid, end_eid)]);
-            }
-        }
-
-        mapping
-    }
-
     pub fn write_json(&self, dir: Option<PathBuf>) {
         fn write_file(folder: &PathBuf, filename: &str, buf: &String) {
             let mut folder = folder.clone();
@@ -106,20 +83,20 @@ impl MemoryWritingTable {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct MemoryRWEntry {
     pub entry: MemoryTableEntry,
     pub start_eid: u32,
     pub end_eid: u32,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct EventTableEntryWithMemoryInfo {
     pub eentry: EventTableEntry,
     pub memory_rw_entires: Vec<MemoryRWEntry>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct EventTableWithMemoryInfo(
     pub(in crate::circuits) Vec<EventTableEntryWithMemoryInfo>,
 );
@@ -127,54 +104,46 @@ pub(crate) struct EventTableWithMemoryInfo(
 impl EventTableWithMemoryInfo {
     pub(in crate::circuits) fn new(
         event_table: &EventTable,
+        memory_events: &MemoryEvents,
+        mtable: &MTable,
         memory_writing_table: &MemoryWritingTable,
     ) -> Self {
-        let lookup = memory_writing_table.build_lookup_mapping();
-
-        let lookup_mtable_eid = |(eid, ltype, offset, is_writing)| {
-            let records = lookup.get(&(ltype, offset)).unwrap();
-
-            if is_writing {
-                let idx = records
-                    .binary_search_by(|(start_eid, _)| start_eid.cmp(eid))
-                    .unwrap();
-                records[idx]
-            } else {
-                let idx = records
-                    .binary_search_by(|(start_eid, end_eid)| {
-                        if eid <= start_eid {
-                            Ordering::Greater
-                        } else if eid > end_eid {
-                            Ordering::Less
-                        } else {
-                            Ordering::Equal
-                        }
-                    })
-                    .unwrap();
-                records[idx]
-            }
+        // The index in the memory writing table of the last writing entry at or
+        // before each row of the memory table, which is the writing entry a read
+        // of the row is from.
+        let writing_index_of_row = {
+            let mut writing_entries = 0;
+
+            mtable
+                .entries()
+                .iter()
+                .map(|entry| {
+                    if entry.atype != AccessType::Read {
+                        writing_entries += 1;
+                    }
+
+                    writing_entries - 1
+                })
+                .collect::<Vec<usize>>()
         };
 
         EventTableWithMemoryInfo(
             event_table
                 .entries()
                 .iter()
-                .map(|eentry| EventTableEntryWithMemoryInfo {
+                .enumerate()
+                .map(|(step, eentry)| EventTableEntryWithMemoryInfo {
                     eentry: eentry.clone(),
-                    memory_rw_entires: memory_event_of_step(eentry, &mut 1)
-                        .iter()
-                        .map(|mentry| {
-                            let (start_eid, end_eid) = lookup_mtable_eid((
-                                &eentry.eid,
-                                mentry.ltype,
-                                mentry.offset,
-                                mentry.atype == AccessType::Write,
-                            ));
+                    memory_rw_entires: memory_events
+                        .of_step(step, mtable)
+                        .zip(memory_events.mtable_rows_of_step(step))
+                        .map(|(mentry, row)| {
+                            let writing_entry = &memory_writing_table.0[writing_index_of_row[*row]];
 
                             MemoryRWEntry {
                                 entry: mentry.clone(),
-                                start_eid,
-                                end_eid,
+                                start_eid: writing_entry.entry.eid,
+                                end_eid: writing_entry.end_eid,
                             }
                         })
                         .collect(),
@@ -1,13 +1,25 @@
 use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::Cell;
 use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::Region;
+use halo2_proofs::plonk::Advice;
+use halo2_proofs::plonk::Column;
 use halo2_proofs::plonk::Error;
 use specs::etable::EventTable;
 use specs::host_function::HostPlugin;
 use specs::step::StepInfo;
 
+#[cfg(feature = "continuation")]
+use crate::circuits::capacity::CircuitTable;
+#[cfg(feature = "continuation")]
+use crate::foreign::context::padded_context;
 use crate::foreign::context::Op;
+#[cfg(feature = "continuation")]
+use crate::foreign::context::CONTEXT_HASH_CAPACITY;
 
 use super::ContextContHelperTableConfig;
+#[cfg(feature = "continuation")]
+use super::ContextPaddingConfig;
 
 pub struct ContextContHelperTableChip<F: FieldExt> {
     pub(crate) config: ContextContHelperTableConfig<F>,
@@ -18,38 +30,130 @@ impl<F: FieldExt> ContextContHelperTableChip<F> {
         Self { config }
     }
 
+    fn assign_values(
+        &self,
+        region: &mut Region<'_, F>,
+        column: Column<Advice>,
+        values: &Vec<u64>,
+    ) -> Result<Vec<Cell>, Error> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(offset, value)| {
+                let cell = region.assign_advice(
+                    || "context cont value",
+                    column,
+                    offset + 1, // The first fixed index should be 1.
+                    || Ok(F::from(*value)),
+                )?;
+
+                Ok(cell.cell())
+            })
+            .collect()
+    }
+
+    /// Assign the values padded with zeros to `CONTEXT_HASH_CAPACITY`, and
+    /// return the cells of the message of the context hash.
+    #[cfg(feature = "continuation")]
+    fn assign_padded_values(
+        &self,
+        region: &mut Region<'_, F>,
+        column: Column<Advice>,
+        padding: ContextPaddingConfig,
+        values: &Vec<u64>,
+        table: CircuitTable,
+    ) -> Result<Vec<Cell>, Error> {
+        let padded_values = padded_context(values, table).map_err(|_| Error::Synthesis)?;
+        let value_cells = self.assign_values(region, column, &padded_values)?;
+
+        // The first row leads the used rows and holds no value.
+        region.assign_advice_from_constant(|| "context padding used", padding.used, 0, F::one())?;
+        let mut next_index_cell = region.assign_advice_from_constant(
+            || "context padding next index",
+            padding.next_index,
+            0,
+            F::one(),
+        )?;
+
+        for offset in 1..=CONTEXT_HASH_CAPACITY {
+            region.assign_advice(
+                || "context padding used",
+                padding.used,
+                offset,
+                || Ok(F::from((offset <= values.len()) as u64)),
+            )?;
+            next_index_cell = region.assign_advice(
+                || "context padding next index",
+                padding.next_index,
+                offset,
+                || Ok(F::from(offset.min(values.len()) as u64 + 1)),
+            )?;
+        }
+
+        Ok([vec![next_index_cell.cell()], value_cells].concat())
+    }
+
+    #[cfg(feature = "continuation")]
+    fn assign_columns(
+        &self,
+        region: &mut Region<'_, F>,
+        inputs: &Vec<u64>,
+        outputs: &Vec<u64>,
+    ) -> Result<(Vec<Cell>, Vec<Cell>), Error> {
+        for offset in 0..CONTEXT_HASH_CAPACITY {
+            region.assign_fixed(
+                || "context padding sel",
+                self.config.padding_sel,
+                offset,
+                || Ok(F::one()),
+            )?;
+        }
+
+        let input_cells = self.assign_padded_values(
+            region,
+            self.config.input,
+            self.config.input_padding,
+            inputs,
+            CircuitTable::ContextInputHelperTable,
+        )?;
+        let output_cells = self.assign_padded_values(
+            region,
+            self.config.output,
+            self.config.output_padding,
+            outputs,
+            CircuitTable::ContextOutputHelperTable,
+        )?;
+
+        Ok((input_cells, output_cells))
+    }
+
+    #[cfg(not(feature = "continuation"))]
+    fn assign_columns(
+        &self,
+        region: &mut Region<'_, F>,
+        inputs: &Vec<u64>,
+        outputs: &Vec<u64>,
+    ) -> Result<(Vec<Cell>, Vec<Cell>), Error> {
+        let input_cells = self.assign_values(region, self.config.input, inputs)?;
+        let output_cells = self.assign_values(region, self.config.output, outputs)?;
+
+        Ok((input_cells, output_cells))
+    }
+
+    /// Assign the context values and return the cells of the inputs and the
+    /// outputs. The continuation circuit pads the values with zeros to
+    /// `CONTEXT_HASH_CAPACITY` and returns the cells of the messages of the
+    /// context hashes, which start with the index following the last value.
     pub fn assign(
         &self,
         layouter: &mut impl Layouter<F>,
         inputs: &Vec<u64>,
         outputs: &Vec<u64>,
-    ) -> Result<(), Error> {
+    ) -> Result<(Vec<Cell>, Vec<Cell>), Error> {
         layouter.assign_region(
             || "context cont helper assign",
-            |mut region| {
-                for (offset, input) in inputs.iter().enumerate() {
-                    region.assign_advice(
-                        || "context cont input index",
-                        self.config.input,
-                        offset + 1, // The first fixed index should be 1.
-                        || Ok(F::from(*input)),
-                    )?;
-                }
-
-                for (offset, output) in outputs.iter().enumerate() {
-                    region.assign_advice(
-                        || "context cont output index",
-                        self.config.output,
-                        offset + 1, // The first fixed index should be 1.
-                        || Ok(F::from(*output)),
-                    )?;
-                }
-
-                Ok(())
-            },
-        )?;
-
-        Ok(())
+            |mut region| self.assign_columns(&mut region, inputs, outputs),
+        )
     }
 }
 
@@ -1,17 +1,56 @@
 use std::marker::PhantomData;
 
 use halo2_proofs::arithmetic::FieldExt;
+#[cfg(feature = "continuation")]
+use halo2_proofs::plonk::Advice;
 use halo2_proofs::plonk::Column;
 use halo2_proofs::plonk::ConstraintSystem;
 use halo2_proofs::plonk::Expression;
 use halo2_proofs::plonk::Fixed;
 use halo2_proofs::plonk::VirtualCells;
 
+#[cfg(feature = "continuation")]
+use crate::constant_from;
 use crate::curr;
 use crate::fixed_curr;
 use crate::foreign::ForeignTableConfig;
+#[cfg(feature = "continuation")]
+use crate::next;
 
 use super::ContextContHelperTableConfig;
+#[cfg(feature = "continuation")]
+use super::ContextPaddingConfig;
+
+#[cfg(feature = "continuation")]
+impl ContextPaddingConfig {
+    fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        name: &'static str,
+        sel: Column<Fixed>,
+        values: Column<Advice>,
+    ) -> Self {
+        let used = meta.named_advice_column(format!("{}_used", name));
+        let next_index = meta.named_advice_column(format!("{}_next_index", name));
+        meta.enable_equality(used);
+        meta.enable_equality(next_index);
+
+        meta.create_gate(name, |meta| {
+            let used_next = next!(meta, used);
+
+            vec![
+                used_next.clone() * (constant_from!(1) - used_next.clone()),
+                used_next.clone() * (constant_from!(1) - curr!(meta, used)),
+                (constant_from!(1) - used_next.clone()) * next!(meta, values),
+                next!(meta, next_index) - curr!(meta, next_index) - used_next,
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel))
+            .collect::<Vec<_>>()
+        });
+
+        ContextPaddingConfig { used, next_index }
+    }
+}
 
 impl<F: FieldExt> ContextContHelperTableConfig<F> {
     pub fn configure(meta: &mut ConstraintSystem<F>, from_zero_index: Column<Fixed>) -> Self {
@@ -20,13 +59,50 @@ impl<F: FieldExt> ContextContHelperTableConfig<F> {
         meta.enable_equality(input);
         meta.enable_equality(output);
 
+        #[cfg(feature = "continuation")]
+        let padding_sel = meta.fixed_column();
+
         ContextContHelperTableConfig {
             from_zero_index,
             input,
             output,
+            #[cfg(feature = "continuation")]
+            padding_sel,
+            #[cfg(feature = "continuation")]
+            input_padding: ContextPaddingConfig::configure(
+                meta,
+                "context_input_padding",
+                padding_sel,
+                input,
+            ),
+            #[cfg(feature = "continuation")]
+            output_padding: ContextPaddingConfig::configure(
+                meta,
+                "context_output_padding",
+                padding_sel,
+                output,
+            ),
             _mark: PhantomData,
         }
     }
+
+    /// The index of the row in the lookup, the index of an unused row of the
+    /// continuation circuit is zero so that the etable can not look it up.
+    #[cfg(feature = "continuation")]
+    fn lookup_index(&self, meta: &mut VirtualCells<'_, F>, is_input: bool) -> Expression<F> {
+        let padding = if is_input {
+            self.input_padding
+        } else {
+            self.output_padding
+        };
+
+        fixed_curr!(meta, self.from_zero_index) * curr!(meta, padding.used)
+    }
+
+    #[cfg(not(feature = "continuation"))]
+    fn lookup_index(&self, meta: &mut VirtualCells<'_, F>, _is_input: bool) -> Expression<F> {
+        fixed_curr!(meta, self.from_zero_index)
+    }
 }
 
 impl<F: FieldExt> ForeignTableConfig<F> for ContextContHelperTableConfig<F> {
@@ -40,7 +116,7 @@ impl<F: FieldExt> ForeignTableConfig<F> for ContextContHelperTableConfig<F> {
             let mut exprs = expr(meta);
 
             vec![
-                (exprs.remove(0), fixed_curr!(meta, self.from_zero_index)),
+                (exprs.remove(0), self.lookup_index(meta, true)),
                 (exprs.remove(0), curr!(meta, self.input)),
             ]
         });
@@ -52,7 +128,7 @@ impl<F: FieldExt> ForeignTableConfig<F> for ContextContHelperTableConfig<F> {
             let _ = exprs.remove(0);
 
             vec![
-                (exprs.remove(0), fixed_curr!(meta, self.from_zero_index)),
+                (exprs.remove(0), self.lookup_index(meta, false)),
                 (exprs.remove(0), curr!(meta, self.output)),
             ]
         });
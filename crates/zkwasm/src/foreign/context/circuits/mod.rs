@@ -15,5 +15,23 @@ pub struct ContextContHelperTableConfig<F: FieldExt> {
     from_zero_index: Column<Fixed>,
     input: Column<Advice>,
     output: Column<Advice>,
+    /// Enables the padding gates on the rows up to `CONTEXT_HASH_CAPACITY`.
+    #[cfg(feature = "continuation")]
+    padding_sel: Column<Fixed>,
+    #[cfg(feature = "continuation")]
+    input_padding: ContextPaddingConfig,
+    #[cfg(feature = "continuation")]
+    output_padding: ContextPaddingConfig,
     _mark: PhantomData<F>,
 }
+
+/// The columns which constrain a context column of the continuation circuit
+/// to zero past its last value, and count the values for the context hash.
+#[cfg(feature = "continuation")]
+#[derive(Clone, Copy)]
+struct ContextPaddingConfig {
+    /// Whether the row holds a context value, the rows holding values come first.
+    used: Column<Advice>,
+    /// The index following the last value up to the row.
+    next_index: Column<Advice>,
+}
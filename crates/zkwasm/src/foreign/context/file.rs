@@ -0,0 +1,78 @@
+//! File format of context inputs and outputs.
+//!
+//! A context file is a JSON document holding the context values together with
+//! the md5 of the image which wrote them, so the outputs of one run can be fed
+//! into the inputs of the next run of a compatible image.
+
+use anyhow::anyhow;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt::Display;
+use std::path::Path;
+
+/// Bumped whenever the layout of `ContextFile` changes.
+pub const CONTEXT_FILE_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum ContextFileErr {
+    VersionMismatch { expected: u32, found: u32 },
+    /// The context file is written by another image.
+    ImageMismatch { expected: String, found: String },
+}
+
+impl Display for ContextFileErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContextFile {
+    pub version: u32,
+    /// The md5 of the image which wrote the context, None for hand-written files
+    /// which are accepted by any image.
+    pub image_md5: Option<String>,
+    pub values: Vec<u64>,
+}
+
+impl ContextFile {
+    pub fn new(image_md5: Option<String>, values: Vec<u64>) -> Self {
+        ContextFile {
+            version: CONTEXT_FILE_VERSION,
+            image_md5,
+            values,
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let file: ContextFile = serde_json::from_slice(&std::fs::read(path)?)
+            .map_err(|e| anyhow!("Failed to parse context file {:?}: {}", path, e))?;
+
+        if file.version != CONTEXT_FILE_VERSION {
+            return Err(anyhow!(ContextFileErr::VersionMismatch {
+                expected: CONTEXT_FILE_VERSION,
+                found: file.version,
+            }));
+        }
+
+        Ok(file)
+    }
+
+    /// Context values to be read by the image with `image_md5`.
+    pub fn values_for(self, image_md5: &str) -> Result<Vec<u64>> {
+        match self.image_md5 {
+            Some(found) if found != image_md5 => Err(anyhow!(ContextFileErr::ImageMismatch {
+                expected: image_md5.to_owned(),
+                found,
+            })),
+            _ => Ok(self.values),
+        }
+    }
+}
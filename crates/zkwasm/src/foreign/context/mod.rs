@@ -1,8 +1,89 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::checksum::poseidon_hash::PoseidonSpec;
+use crate::circuits::capacity::CircuitTable;
+use crate::loader::err::Error;
+use crate::loader::err::RuntimeErr;
+
 pub mod circuits;
 pub mod etable_op_configure;
+pub mod file;
 pub mod runtime;
 
 enum Op {
     ReadContext = 0,
     WriteContext = 1,
 }
+
+/// The number of context values covered by the context hashes.
+pub const CONTEXT_HASH_CAPACITY: usize = 4095;
+
+/// The context values padded with zeros to `CONTEXT_HASH_CAPACITY`, `table`
+/// is the helper table of the values reported if they exceed the capacity.
+pub fn padded_context(values: &Vec<u64>, table: CircuitTable) -> Result<Vec<u64>> {
+    if values.len() > CONTEXT_HASH_CAPACITY {
+        return Err(anyhow!(Error::Runtime(RuntimeErr::TableOverflow {
+            table,
+            // Helper tables for context start from index 1.
+            required_rows: values.len() + 1,
+            available_rows: CONTEXT_HASH_CAPACITY + 1,
+            minimal_k: None,
+        })));
+    }
+
+    let mut values = values.clone();
+    values.resize(CONTEXT_HASH_CAPACITY, 0);
+    Ok(values)
+}
+
+/// The message of a context hash: the index following the last context value
+/// and the padded values. The index commits the count of the values, so the
+/// padding zeros can not be taken for context values.
+pub fn context_hash_message(values: &Vec<u64>, table: CircuitTable) -> Result<Vec<u64>> {
+    Ok([
+        vec![values.len() as u64 + 1],
+        padded_context(values, table)?,
+    ]
+    .concat())
+}
+
+/// Poseidon hashes of the context inputs and outputs, which are exposed as
+/// the context hash instance column of the continuation circuit so that
+/// consecutive proofs can be chained by their context hashes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContextHashes<F: FieldExt> {
+    pub input: F,
+    pub output: F,
+}
+
+impl<F: FieldExt> ContextHashes<F> {
+    pub fn new(inputs: &Vec<u64>, outputs: &Vec<u64>) -> Result<Self> {
+        let spec = PoseidonSpec::new();
+        let hash = |values: &Vec<u64>, table| -> Result<F> {
+            Ok(spec.hash(
+                &context_hash_message(values, table)?
+                    .into_iter()
+                    .map(|v| F::from(v))
+                    .collect::<Vec<_>>(),
+            ))
+        };
+
+        Ok(ContextHashes {
+            input: hash(inputs, CircuitTable::ContextInputHelperTable)?,
+            output: hash(outputs, CircuitTable::ContextOutputHelperTable)?,
+        })
+    }
+
+    pub fn from_instances(instances: &Vec<F>) -> Option<Self> {
+        match instances[..] {
+            [input, output] => Some(ContextHashes { input, output }),
+            _ => None,
+        }
+    }
+
+    pub fn instances(&self) -> Vec<F> {
+        vec![self.input, self.output]
+    }
+}
@@ -3,7 +3,7 @@ pub mod etable_op_configure;
 pub mod runtime;
 pub mod test;
 
-enum Op {
+pub(crate) enum Op {
     WasmInput = 0,
     WasmOutput = 1,
 }
@@ -7,7 +7,7 @@ use specs::types::ValueType;
 
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::ForeignContext;
-use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
+use crate::runtime::WasmRuntimeIO;
 
 use super::Op;
 
@@ -146,5 +146,6 @@ pub fn register_wasm_input_foreign(
     WasmRuntimeIO {
         public_inputs_and_outputs,
         outputs,
+        step_limit: None,
     }
 }
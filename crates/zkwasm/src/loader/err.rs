@@ -1,19 +1,90 @@
 use std::fmt::Display;
 
+use crate::circuits::capacity::CircuitTable;
+
+/// The reason why a function is not pure.
+#[derive(Debug, Clone)]
+pub enum Impurity {
+    HasReturnValue,
+    WriteMemory { iid: u32 },
+    GrowMemory { iid: u32 },
+    WriteGlobal { iid: u32, global: u32 },
+    CallIndirect { iid: u32 },
+    CallHostFunction,
+}
+
 #[derive(Debug)]
 pub enum PreCheckErr {
     ZkmainNotExists,
     ZkmainIsNotFunction,
     // ZkmainTypeNotMatch,
+    /// The regex of the phantom function does not match any function.
+    PhantomFunctionNotExists(String),
+    /// `function` is matched as a phantom function, but `callee`(maybe itself) is not pure.
+    PhantomFunctionNotPure {
+        function: String,
+        callee: String,
+        reason: Impurity,
+    },
+    /// The initial memory pages of the image exceed the pages addressable under K.
+    InitMemoryPagesExceeded {
+        init_pages: u32,
+        maximal_pages: u32,
+        k: u32,
+    },
+    /// The image table of the image exceeds the rows available under K.
+    ImageTableOverflow {
+        required_rows: usize,
+        available_rows: usize,
+        k: u32,
+        /// The minimal K whose image table can hold the image, None if no K can hold it.
+        minimal_k: Option<u32>,
+    },
+    /// The init memory of the image exceeds the rows committed by the uniform
+    /// circuit under K.
+    InitMemoryTableOverflow {
+        required_rows: usize,
+        available_rows: usize,
+        k: u32,
+        /// The minimal K whose init memory table can hold the image, None if no K can hold it.
+        minimal_k: Option<u32>,
+    },
 }
 
 #[derive(Debug)]
-pub enum RuntimeErr {}
+pub enum RuntimeErr {
+    /// The trace requires more rows than the circuit provides.
+    TableOverflow {
+        table: CircuitTable,
+        required_rows: usize,
+        available_rows: usize,
+        /// The minimal K which can hold the whole trace, None if no K can hold it.
+        minimal_k: Option<u32>,
+    },
+    /// The execution does not terminate within the step limit of `ExecutionArg`.
+    OutOfFuel { step_limit: u64 },
+    /// The traced execution makes other host calls than the execution checked
+    /// against the step limit, whose host calls it replays.
+    HostCallsDiverged,
+    /// The step of `eid` violates the semantics checked by `runtime::auditor`.
+    InconsistentTrace { eid: u32, reason: String },
+}
+
+#[derive(Debug)]
+pub enum VerifyErr {
+    /// The proof is not generated from the image with the expected checksum.
+    ImageChecksumMismatch,
+    /// The proof does not verify against the verifying key and instances.
+    InvalidProof,
+    /// The verifying key has no image column, it is not a key of the uniform circuit.
+    ImageColumnNotFound,
+}
 
 #[derive(Debug)]
 pub enum Error {
     PreCheck(PreCheckErr),
-    // Runtime(RuntimeErr),
+    Runtime(RuntimeErr),
+    Verify(VerifyErr),
 }
 
 impl Display for Error {
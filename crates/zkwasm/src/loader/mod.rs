@@ -1,44 +1,79 @@
 use anyhow::Result;
 use halo2_proofs::arithmetic::MultiMillerLoop;
 use halo2_proofs::dev::MockProver;
+use halo2_proofs::plonk::keygen_pk;
 use halo2_proofs::plonk::keygen_vk;
 use halo2_proofs::plonk::verify_proof;
+use halo2_proofs::plonk::ProvingKey;
 use halo2_proofs::plonk::SingleVerifier;
 use halo2_proofs::plonk::VerifyingKey;
 use halo2_proofs::poly::commitment::Params;
 use halo2_proofs::poly::commitment::ParamsVerifier;
+use halo2_proofs::transcript::Blake2bRead;
+use halo2_proofs::transcript::Blake2bWrite;
+use halo2_proofs::transcript::Challenge255;
+use log::info;
 use log::warn;
+use parity_wasm::elements::MemoryType;
+use parity_wasm::elements::Module;
 use std::marker::PhantomData;
 
 use halo2aggregator_s::circuits::utils::load_or_create_proof;
-use halo2aggregator_s::circuits::utils::TranscriptHash;
+pub use halo2aggregator_s::circuits::utils::TranscriptHash;
 use halo2aggregator_s::transcript::poseidon::PoseidonRead;
+use halo2aggregator_s::transcript::poseidon::PoseidonWrite;
+use halo2aggregator_s::transcript::sha256::ShaRead;
+use halo2aggregator_s::transcript::sha256::ShaWrite;
+use rand::rngs::OsRng;
 
+use specs::CompilationTable;
 use specs::ExecutionTable;
 use specs::Tables;
-use wasmi::tracer::Tracer;
-use wasmi::ImportsBuilder;
-use wasmi::NotStartedModuleRef;
-use wasmi::RuntimeValue;
 
 use crate::checksum::CompilationTableWithParams;
+use crate::checksum::CompilationTableWithPoseidon;
 use crate::checksum::ImageCheckSum;
+use crate::circuits::capacity::image_table_entries;
+use crate::circuits::capacity::image_table_minimal_k;
+#[cfg(feature = "uniform-circuit")]
+use crate::circuits::capacity::init_memory_table_entries;
+#[cfg(feature = "uniform-circuit")]
+use crate::circuits::capacity::init_memory_table_minimal_k;
+use crate::circuits::capacity::CapacityReport;
+use crate::circuits::config::compute_max_image_table_rows;
+#[cfg(feature = "uniform-circuit")]
+use crate::circuits::config::compute_max_init_memory_table_rows;
+use crate::circuits::config::compute_maximal_pages;
 use crate::circuits::config::init_zkwasm_runtime;
 use crate::circuits::config::set_zkwasm_k;
+#[cfg(feature = "public-eid")]
+use crate::circuits::termination_eid;
+use crate::circuits::PublicInputs;
 use crate::circuits::TestCircuit;
 use crate::circuits::ZkWasmCircuitBuilder;
 use crate::loader::err::Error;
 use crate::loader::err::PreCheckErr;
+use crate::loader::err::RuntimeErr;
+use crate::loader::err::VerifyErr;
+use crate::loader::phantom::check_phantom_functions;
+use crate::loader::phantom::function_name;
+use crate::loader::phantom::match_phantom_functions;
+use crate::loader::phantom::suggest_phantom_functions;
+use crate::loader::phantom::PhantomFunctionMatch;
+use crate::loader::phantom::PhantomFunctionSuggestion;
 use crate::profile::Profiler;
+use crate::runtime::auditor::audit_trace;
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::HostEnvBuilder;
-use crate::runtime::wasmi_interpreter::Execution;
-use crate::runtime::CompiledImage;
+use crate::runtime::DryRunResult;
 use crate::runtime::ExecutionResult;
 use crate::runtime::WasmInterpreter;
+use crate::runtime::WasmRuntime;
+use crate::runtime::WasmValue;
 use anyhow::anyhow;
 
-mod err;
+pub mod err;
+pub mod phantom;
 
 const ENTRY: &str = "zkmain";
 
@@ -46,19 +81,66 @@ pub struct ExecutionReturn {
     pub context_output: Vec<u64>,
 }
 
-pub struct ZkWasmLoader<E: MultiMillerLoop, Arg, EnvBuilder: HostEnvBuilder<Arg = Arg>> {
+pub struct ZkWasmLoader<
+    E: MultiMillerLoop,
+    Arg,
+    EnvBuilder: HostEnvBuilder<Arg = Arg>,
+    Runtime: WasmRuntime = WasmInterpreter,
+> {
     k: u32,
-    module: wasmi::Module,
+    /// The image after name parsing and memory clamping, for static analyses.
+    module: Module,
+    runtime_module: Runtime::Module,
     phantom_functions: Vec<String>,
+    /// Whether `circuit_with_witness` audits the trace, see `with_audit`.
+    audit: bool,
     _mark: PhantomData<(Arg, EnvBuilder, E)>,
 }
 
-impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E, T, EnvBuilder> {
+/// Clamp the maximal memory pages declared by the image to the pages
+/// addressable under K, so that `memory.grow` beyond them returns -1.
+fn limit_memory_pages(module: &mut Module, k: u32) -> Result<()> {
+    let maximal_pages = compute_maximal_pages(k);
+
+    if let Some(memory_section) = module.memory_section_mut() {
+        for memory in memory_section.entries_mut() {
+            let init_pages = memory.limits().initial();
+
+            if init_pages > maximal_pages {
+                return Err(anyhow!(Error::PreCheck(
+                    PreCheckErr::InitMemoryPagesExceeded {
+                        init_pages,
+                        maximal_pages,
+                        k,
+                    }
+                )));
+            }
+
+            match memory.limits().maximum() {
+                Some(pages) if pages <= maximal_pages => (),
+                declared => {
+                    info!(
+                        "maximal memory pages {:?} is clamped to {} with K = {}",
+                        declared, maximal_pages, k
+                    );
+
+                    *memory = MemoryType::new(init_pages, Some(maximal_pages));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>, Runtime: WasmRuntime>
+    ZkWasmLoader<E, T, EnvBuilder, Runtime>
+{
     fn precheck(&self) -> Result<()> {
-        fn check_zkmain_exists(module: &wasmi::Module) -> Result<()> {
+        fn check_zkmain_exists(module: &Module) -> Result<()> {
             use parity_wasm::elements::Internal;
 
-            let export = module.module().export_section().unwrap();
+            let export = module.export_section().unwrap();
 
             if let Some(entry) = export
                 .entries()
@@ -76,25 +158,78 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
 
         check_zkmain_exists(&self.module)?;
         // TODO: check the signature of zkmain function.
-        // TODO: check the relation between maximal pages and K.
-        // TODO: check the instructions of phantom functions.
-        // TODO: check phantom functions exists.
         // TODO: check if instructions are supported.
 
+        self.check_image_table_rows()?;
+
+        for matched in check_phantom_functions(&self.module, &self.phantom_functions)? {
+            info!(
+                "phantom function {} matches {:?}",
+                matched.regex,
+                matched
+                    .functions
+                    .iter()
+                    .map(|(_, name)| name)
+                    .collect::<Vec<_>>()
+            );
+        }
+
         Ok(())
     }
 
-    pub fn compile(
-        &self,
-        env: &HostEnv,
-        dryrun: bool,
-    ) -> Result<CompiledImage<NotStartedModuleRef<'_>, Tracer>> {
-        let imports = ImportsBuilder::new().with_resolver("env", env);
+    /// The image table, and the init memory table of the uniform circuit, are
+    /// determined by the image only, so an image which does not fit is rejected
+    /// before any execution.
+    fn check_image_table_rows(&self) -> Result<()> {
+        let (env, _) = EnvBuilder::create_env_without_value();
+        let tables = self.compile(&env, true)?;
+
+        let required_rows = image_table_entries(&tables);
+        let available_rows = compute_max_image_table_rows(self.k) as usize;
+
+        if required_rows > available_rows {
+            return Err(anyhow!(Error::PreCheck(PreCheckErr::ImageTableOverflow {
+                required_rows,
+                available_rows,
+                k: self.k,
+                minimal_k: image_table_minimal_k(required_rows),
+            })));
+        }
 
-        WasmInterpreter::compile(
-            &self.module,
-            &imports,
-            &env.function_description_table(),
+        #[cfg(feature = "uniform-circuit")]
+        {
+            let required_rows = init_memory_table_entries(&tables);
+            let available_rows = compute_max_init_memory_table_rows(self.k) as usize;
+
+            if required_rows > available_rows {
+                return Err(anyhow!(Error::PreCheck(
+                    PreCheckErr::InitMemoryTableOverflow {
+                        required_rows,
+                        available_rows,
+                        k: self.k,
+                        minimal_k: init_memory_table_minimal_k(required_rows),
+                    }
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Report the functions matched by each phantom function regex.
+    pub fn phantom_functions_report(&self) -> Result<Vec<PhantomFunctionMatch>> {
+        match_phantom_functions(&self.module, &self.phantom_functions)
+    }
+
+    /// The name of the function `fid` in the name section of the image.
+    pub fn function_name(&self, fid: u32) -> Option<String> {
+        function_name(&self.module, fid)
+    }
+
+    pub fn compile(&self, env: &HostEnv, dryrun: bool) -> Result<CompilationTable> {
+        Runtime::compile(
+            &self.runtime_module,
+            env,
             ENTRY,
             dryrun,
             &self.phantom_functions,
@@ -104,11 +239,11 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
     fn circuit_without_witness(&self) -> Result<TestCircuit<E::Scalar>> {
         let (env, wasm_runtime_io) = EnvBuilder::create_env_without_value();
 
-        let compiled_module = self.compile(&env, true)?;
+        let compilation_tables = self.compile(&env, true)?;
 
         let builder = ZkWasmCircuitBuilder {
             tables: Tables {
-                compilation_tables: compiled_module.tables,
+                compilation_tables,
                 execution_tables: ExecutionTable::default(),
             },
             public_inputs_and_outputs: wasm_runtime_io.public_inputs_and_outputs.borrow().clone(),
@@ -125,17 +260,25 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
     pub fn new(k: u32, image: Vec<u8>, phantom_functions: Vec<String>) -> Result<Self> {
         set_zkwasm_k(k);
 
-        let mut module = wasmi::Module::from_buffer(&image)?;
-        if let Ok(parity_module) = module.module().clone().parse_names() {
-            module.module = parity_module;
-        } else {
-            warn!("Failed to parse name section of the wasm binary.");
-        }
+        let mut module = parity_wasm::deserialize_buffer::<Module>(&image)?;
+        module = match module.parse_names() {
+            Ok(module) => module,
+            Err((_, module)) => {
+                warn!("Failed to parse name section of the wasm binary.");
+                module
+            }
+        };
+
+        limit_memory_pages(&mut module, k)?;
+
+        let runtime_module = Runtime::load(&module)?;
 
         let loader = Self {
             k,
             module,
+            runtime_module,
             phantom_functions,
+            audit: false,
             _mark: PhantomData,
         };
 
@@ -145,36 +288,94 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
         Ok(loader)
     }
 
+    /// Audit the trace by `runtime::auditor` in `circuit_with_witness` before
+    /// the circuit is built. Traces of images with phantom functions are not
+    /// audited, as the tracer elides the steps of phantom functions.
+    pub fn with_audit(mut self, audit: bool) -> Self {
+        self.audit = audit;
+        self
+    }
+
     pub fn create_vkey(&self, params: &Params<E::G1Affine>) -> Result<VerifyingKey<E::G1Affine>> {
         let circuit = self.circuit_without_witness()?;
 
         Ok(keygen_vk(&params, &circuit).unwrap())
     }
 
+    /// Create the proving key of the image, it can be cached and reused by
+    /// `create_proof_with_pkey` across executions.
+    pub fn create_pkey(
+        &self,
+        params: &Params<E::G1Affine>,
+        vkey: VerifyingKey<E::G1Affine>,
+    ) -> Result<ProvingKey<E::G1Affine>> {
+        let circuit = self.circuit_without_witness()?;
+
+        Ok(keygen_pk(&params, vkey, &circuit).unwrap())
+    }
+
     pub fn checksum(&self, params: &Params<E::G1Affine>) -> Result<Vec<E::G1Affine>> {
         let (env, _) = EnvBuilder::create_env_without_value();
-        let compiled = self.compile(&env, true)?;
+        let tables = self.compile(&env, true)?;
 
         let table_with_params = CompilationTableWithParams {
-            table: &compiled.tables,
+            table: &tables,
             params,
         };
 
         Ok(table_with_params.checksum())
     }
+
+    /// The poseidon hash of the image, which is exposed as a public instance
+    /// by the uniform circuit.
+    pub fn image_hash(&self) -> Result<E::Scalar> {
+        let (env, _) = EnvBuilder::create_env_without_value();
+        let tables = self.compile(&env, true)?;
+
+        Ok(CompilationTableWithPoseidon { table: &tables }.checksum())
+    }
+
+    /// Instance columns of proofs of the image with `public_inputs`, see
+    /// `TestCircuit::instance_columns`.
+    pub fn instance_columns(
+        &self,
+        public_inputs: &PublicInputs<E::Scalar>,
+    ) -> Result<Vec<Vec<E::Scalar>>> {
+        #[allow(unused_mut)]
+        let mut columns = vec![public_inputs.instances.clone()];
+
+        #[cfg(feature = "uniform-circuit")]
+        columns.push(vec![self.image_hash()?]);
+
+        #[cfg(feature = "continuation")]
+        columns.push(public_inputs.context_hashes.instances());
+
+        #[cfg(feature = "public-eid")]
+        columns.push(vec![termination_eid(public_inputs.consumed_steps)]);
+
+        Ok(columns)
+    }
 }
 
-impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E, T, EnvBuilder> {
+impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>, Runtime: WasmRuntime>
+    ZkWasmLoader<E, T, EnvBuilder, Runtime>
+{
     pub fn run(
         &self,
         arg: T,
         dryrun: bool,
         write_to_file: bool,
-    ) -> Result<ExecutionResult<RuntimeValue>> {
+    ) -> Result<ExecutionResult<WasmValue>> {
         let (mut env, wasm_runtime_io) = EnvBuilder::create_env(arg);
-        let compiled_module = self.compile(&env, dryrun)?;
 
-        let result = compiled_module.run(&mut env, dryrun, wasm_runtime_io)?;
+        let result = Runtime::run(
+            &self.runtime_module,
+            &mut env,
+            wasm_runtime_io,
+            ENTRY,
+            dryrun,
+            &self.phantom_functions,
+        )?;
 
         if !dryrun {
             result.tables.profile_tables();
@@ -187,11 +388,88 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
         Ok(result)
     }
 
+    /// Execute the image without tracing, for validating inputs before proving.
+    pub fn dry_run(&self, arg: T) -> Result<DryRunResult> {
+        let (mut env, wasm_runtime_io) = EnvBuilder::create_env(arg);
+
+        Runtime::dry_run(
+            &self.runtime_module,
+            &mut env,
+            wasm_runtime_io,
+            ENTRY,
+            &self.phantom_functions,
+        )
+    }
+
+    /// Estimate the rows required by each table from the step counts of the
+    /// non-tracing dry run, which gives upper bounds of `estimate`.
+    pub fn estimate_by_dry_run(&self, arg: T) -> Result<CapacityReport> {
+        let result = self.dry_run(arg)?;
+
+        let (env, _) = EnvBuilder::create_env_without_value();
+        let compilation_tables = self.compile(&env, true)?;
+
+        Ok(CapacityReport::from_step_counts::<E::Scalar>(
+            &compilation_tables,
+            &result.step_counts,
+            result.public_inputs_and_outputs.len(),
+            self.k,
+        ))
+    }
+
+    /// Execute the image and estimate the rows required by each table.
+    pub fn estimate(&self, arg: T) -> Result<CapacityReport> {
+        let execution_result = self.run(arg, false, false)?;
+
+        Ok(CapacityReport::new::<E::Scalar>(
+            &execution_result.tables,
+            execution_result.public_inputs_and_outputs.len(),
+            self.k,
+        ))
+    }
+
+    /// Execute the image and rank the pure functions which could be marked as
+    /// phantom by the etable rows they would save.
+    pub fn suggest_phantom_functions(&self, arg: T) -> Result<Vec<PhantomFunctionSuggestion>> {
+        let execution_result = self.run(arg, false, false)?;
+
+        Ok(suggest_phantom_functions(
+            &self.module,
+            &execution_result.tables,
+        ))
+    }
+
     pub fn circuit_with_witness(
         &self,
         arg: T,
     ) -> Result<(TestCircuit<E::Scalar>, Vec<E::Scalar>, Vec<u64>)> {
         let execution_result = self.run(arg, false, true)?;
+
+        let capacity = CapacityReport::new::<E::Scalar>(
+            &execution_result.tables,
+            execution_result.public_inputs_and_outputs.len(),
+            self.k,
+        );
+        if let Some(usage) = capacity.overflows().first() {
+            return Err(anyhow!(Error::Runtime(RuntimeErr::TableOverflow {
+                table: usage.table,
+                required_rows: usage.required_rows,
+                available_rows: usage.available_rows,
+                minimal_k: capacity.minimal_k(),
+            })));
+        }
+
+        if self.audit {
+            if self.phantom_functions.is_empty() {
+                audit_trace(
+                    &execution_result.tables,
+                    &execution_result.public_inputs_and_outputs,
+                )?;
+            } else {
+                warn!("The trace is not audited, as the steps of phantom functions are not traced");
+            }
+        }
+
         let instance: Vec<E::Scalar> = execution_result
             .public_inputs_and_outputs
             .clone()
@@ -204,9 +482,6 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
             public_inputs_and_outputs: execution_result.public_inputs_and_outputs,
         };
 
-        println!("output:");
-        println!("{:?}", execution_result.outputs);
-
         Ok((builder.build_circuit(), instance, execution_result.outputs))
     }
 
@@ -215,30 +490,83 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
         circuit: &TestCircuit<E::Scalar>,
         instances: &Vec<E::Scalar>,
     ) -> Result<()> {
-        let prover = MockProver::run(self.k, circuit, vec![instances.clone()])?;
+        let prover = MockProver::run(self.k, circuit, circuit.instance_columns(instances)?)?;
         assert_eq!(prover.verify(), Ok(()));
 
         Ok(())
     }
 
+    /// Create a proof with the transcript `hash`, Poseidon for proofs to be
+    /// aggregated and Sha for proofs verified by external verifiers such as EVM.
     pub fn create_proof(
         &self,
         params: &Params<E::G1Affine>,
         vkey: VerifyingKey<E::G1Affine>,
         circuit: TestCircuit<E::Scalar>,
         instances: &Vec<E::Scalar>,
+        hash: TranscriptHash,
     ) -> Result<Vec<u8>> {
+        let instances = circuit.instance_columns(instances)?;
+
         Ok(load_or_create_proof::<E, _>(
             &params,
             vkey,
             circuit,
-            &[instances],
+            &instances.iter().collect::<Vec<_>>(),
             None,
-            TranscriptHash::Poseidon,
+            hash,
             false,
         ))
     }
 
+    pub fn create_proof_with_pkey(
+        &self,
+        params: &Params<E::G1Affine>,
+        pkey: &ProvingKey<E::G1Affine>,
+        circuit: TestCircuit<E::Scalar>,
+        instances: &Vec<E::Scalar>,
+        hash: TranscriptHash,
+    ) -> Result<Vec<u8>> {
+        let instances = circuit.instance_columns(instances)?;
+        let instances = instances.iter().map(|v| &v[..]).collect::<Vec<_>>();
+
+        macro_rules! create_proof_with_transcript {
+            ($transcript: expr) => {{
+                let mut transcript = $transcript;
+
+                halo2_proofs::plonk::create_proof(
+                    params,
+                    pkey,
+                    &[circuit],
+                    &[&instances[..]],
+                    OsRng,
+                    &mut transcript,
+                )?;
+
+                transcript.finalize()
+            }};
+        }
+
+        let proof = match hash {
+            TranscriptHash::Blake2b => {
+                create_proof_with_transcript!(Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]))
+            }
+            TranscriptHash::Poseidon => create_proof_with_transcript!(PoseidonWrite::init(vec![])),
+            TranscriptHash::Sha => {
+                create_proof_with_transcript!(
+                    ShaWrite::<_, _, Challenge255<_>, sha2::Sha256>::init(vec![])
+                )
+            }
+            TranscriptHash::Keccak => {
+                create_proof_with_transcript!(
+                    ShaWrite::<_, _, Challenge255<_>, sha3::Keccak256>::init(vec![])
+                )
+            }
+        };
+
+        Ok(proof)
+    }
+
     pub fn init_env(&self) -> Result<()> {
         init_zkwasm_runtime(self.k);
 
@@ -249,48 +577,152 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
         &self,
         params: &Params<E::G1Affine>,
         vkey: VerifyingKey<E::G1Affine>,
-        instances: Vec<E::Scalar>,
+        public_inputs: PublicInputs<E::Scalar>,
         proof: Vec<u8>,
+        hash: TranscriptHash,
     ) -> Result<()> {
-        let params_verifier: ParamsVerifier<E> = params.verifier(instances.len()).unwrap();
-        let strategy = SingleVerifier::new(&params_verifier);
-
-        verify_proof(
-            &params_verifier,
-            &vkey,
-            strategy,
-            &[&[&instances]],
-            &mut PoseidonRead::init(&proof[..]),
-        )
-        .unwrap();
+        let instances = self.instance_columns(&public_inputs)?;
 
+        verify_single_proof::<E>(params, &vkey, &instances, &proof, hash)?;
+
+        // The image hash instance only covers the assigned prefix of the image
+        // columns, their commitments bind the rest of the rows to the image.
         #[cfg(feature = "uniform-circuit")]
-        {
-            use crate::circuits::image_table::IMAGE_COL_NAME;
-            use halo2_proofs::plonk::get_advice_commitments_from_transcript;
+        check_image_commitment::<E>(&vkey, &proof, &self.checksum(params)?, hash)?;
 
-            let img_col_idx = vkey
-                .cs
-                .named_advices
-                .iter()
-                .find(|(k, _)| k == IMAGE_COL_NAME)
-                .unwrap()
-                .1;
-            let img_col_commitment: Vec<E::G1Affine> =
-                get_advice_commitments_from_transcript::<E, _, _>(
-                    &vkey,
-                    &mut PoseidonRead::init(&proof[..]),
-                )
-                .unwrap();
-            let checksum = self.checksum(params)?;
+        Ok(())
+    }
+}
+
+/// Verify a single proof of `vkey` with the instance columns `instances`.
+pub fn verify_single_proof<E: MultiMillerLoop>(
+    params: &Params<E::G1Affine>,
+    vkey: &VerifyingKey<E::G1Affine>,
+    instances: &Vec<Vec<E::Scalar>>,
+    proof: &Vec<u8>,
+    hash: TranscriptHash,
+) -> Result<()> {
+    let params_verifier: ParamsVerifier<E> = params
+        .verifier(instances.iter().map(|v| v.len()).max().unwrap())
+        .unwrap();
 
-            assert!(vec![img_col_commitment[img_col_idx as usize]] == checksum)
+    let instances = instances.iter().map(|v| &v[..]).collect::<Vec<_>>();
+
+    macro_rules! verify_proof_with_transcript {
+        ($transcript: expr) => {
+            verify_proof(
+                &params_verifier,
+                vkey,
+                SingleVerifier::new(&params_verifier),
+                &[&instances[..]],
+                &mut $transcript,
+            )
+        };
+    }
+
+    // Report an invalid proof as an error rather than panicking, so that
+    // long-running verifiers such as the prover server survive bad proofs.
+    match hash {
+        TranscriptHash::Blake2b => {
+            verify_proof_with_transcript!(Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]))
+        }
+        TranscriptHash::Poseidon => verify_proof_with_transcript!(PoseidonRead::init(&proof[..])),
+        TranscriptHash::Sha => {
+            verify_proof_with_transcript!(ShaRead::<_, _, Challenge255<_>, sha2::Sha256>::init(
+                &proof[..]
+            ))
         }
+        TranscriptHash::Keccak => {
+            verify_proof_with_transcript!(ShaRead::<_, _, Challenge255<_>, sha3::Keccak256>::init(
+                &proof[..]
+            ))
+        }
+    }
+    .map_err(|_| anyhow!(Error::Verify(VerifyErr::InvalidProof)))?;
 
-        Ok(())
+    Ok(())
+}
+
+#[cfg(feature = "uniform-circuit")]
+impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>, Runtime: WasmRuntime>
+    ZkWasmLoader<E, T, EnvBuilder, Runtime>
+{
+    /// Create the verifying key shared by all images, the image table is
+    /// committed as an advice column and checked against the image checksum
+    /// during verification instead.
+    pub fn create_uniform_vkey(
+        k: u32,
+        params: &Params<E::G1Affine>,
+    ) -> Result<VerifyingKey<E::G1Affine>> {
+        let image = wabt::wat2wasm(UNIFORM_CIRCUIT_IMAGE)?;
+
+        Self::new(k, image, vec![])?.create_vkey(params)
     }
 }
 
+/// The image used to generate the uniform verifying key. The key does not depend
+/// on the image, as the image table and the init memory are padded to the
+/// capacities derived from K.
+#[cfg(feature = "uniform-circuit")]
+const UNIFORM_CIRCUIT_IMAGE: &str = r#"(module (func (export "zkmain")))"#;
+
+/// Check that the proof is generated from the image with `checksum`.
+#[cfg(feature = "uniform-circuit")]
+pub fn check_image_commitment<E: MultiMillerLoop>(
+    vkey: &VerifyingKey<E::G1Affine>,
+    proof: &Vec<u8>,
+    checksum: &Vec<E::G1Affine>,
+    hash: TranscriptHash,
+) -> Result<()> {
+    use crate::circuits::image_table::IMAGE_COL_NAME;
+    use crate::circuits::image_table::INIT_MEMORY_COL_NAME;
+    use halo2_proofs::plonk::get_advice_commitments_from_transcript;
+
+    let named_advice_idx = |name: &str| {
+        vkey.cs
+            .named_advices
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, idx)| *idx as usize)
+            .ok_or(anyhow!(Error::Verify(VerifyErr::ImageColumnNotFound)))
+    };
+    let img_col_idx = named_advice_idx(IMAGE_COL_NAME)?;
+    let init_memory_col_idx = named_advice_idx(INIT_MEMORY_COL_NAME)?;
+
+    let advice_commitments: Vec<E::G1Affine> = match hash {
+        TranscriptHash::Blake2b => get_advice_commitments_from_transcript::<E, _, _>(
+            vkey,
+            &mut Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]),
+        ),
+        TranscriptHash::Poseidon => get_advice_commitments_from_transcript::<E, _, _>(
+            vkey,
+            &mut PoseidonRead::init(&proof[..]),
+        ),
+        TranscriptHash::Sha => get_advice_commitments_from_transcript::<E, _, _>(
+            vkey,
+            &mut ShaRead::<_, _, Challenge255<_>, sha2::Sha256>::init(&proof[..]),
+        ),
+        TranscriptHash::Keccak => get_advice_commitments_from_transcript::<E, _, _>(
+            vkey,
+            &mut ShaRead::<_, _, Challenge255<_>, sha3::Keccak256>::init(&proof[..]),
+        ),
+    }
+    // A malformed or truncated proof is reported rather than panicking the verifier.
+    .map_err(|_| anyhow!(Error::Verify(VerifyErr::InvalidProof)))?;
+
+    let commitments = [img_col_idx, init_memory_col_idx]
+        .iter()
+        .map(|idx| advice_commitments.get(*idx).cloned())
+        .collect::<Option<Vec<_>>>()
+        .ok_or(anyhow!(Error::Verify(VerifyErr::InvalidProof)))?;
+
+    if commitments != *checksum {
+        return Err(anyhow!(Error::Verify(VerifyErr::ImageChecksumMismatch)));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use ark_std::end_timer;
@@ -336,11 +768,25 @@ mod tests {
 
             let params = prepare_param(self.k);
             let vkey = self.create_vkey(&params).unwrap();
+            let public_inputs = circuit.public_inputs(&instances).unwrap();
 
             let proof = self
-                .create_proof(&params, vkey.clone(), circuit, &instances)
+                .create_proof(
+                    &params,
+                    vkey.clone(),
+                    circuit,
+                    &instances,
+                    TranscriptHash::Poseidon,
+                )
                 .unwrap();
-            self.verify_proof(&params, vkey, instances, proof).unwrap();
+            self.verify_proof(
+                &params,
+                vkey,
+                public_inputs,
+                proof,
+                TranscriptHash::Poseidon,
+            )
+            .unwrap();
         }
     }
 }
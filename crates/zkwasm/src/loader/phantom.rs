@@ -0,0 +1,304 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use parity_wasm::elements::External;
+use parity_wasm::elements::Instruction;
+use parity_wasm::elements::Module;
+use parity_wasm::elements::Type;
+use regex::Regex;
+use specs::Tables;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::circuits::etable::EVENT_TABLE_ENTRY_ROWS;
+use crate::profile::function_statistic::FunctionStatistic;
+
+use super::err::Error;
+use super::err::Impurity;
+use super::err::PreCheckErr;
+
+/// Functions matched by a phantom function regex.
+#[derive(Debug, Clone)]
+pub struct PhantomFunctionMatch {
+    pub regex: String,
+    /// (function index, function name)
+    pub functions: Vec<(u32, String)>,
+}
+
+pub(crate) fn imported_functions(module: &Module) -> Vec<(String, String)> {
+    module.import_section().map_or(vec![], |section| {
+        section
+            .entries()
+            .iter()
+            .filter(|entry| matches!(entry.external(), External::Function(_)))
+            .map(|entry| (entry.module().to_owned(), entry.field().to_owned()))
+            .collect()
+    })
+}
+
+pub(crate) fn function_name(module: &Module, fid: u32) -> Option<String> {
+    module
+        .names_section()
+        .and_then(|section| section.functions())
+        .and_then(|functions| functions.names().get(fid))
+        .cloned()
+}
+
+/// Find the functions matched by each phantom regex, in the same way as the tracer does.
+pub(crate) fn match_phantom_functions(
+    module: &Module,
+    phantom_functions: &Vec<String>,
+) -> Result<Vec<PhantomFunctionMatch>> {
+    let names = module
+        .names_section()
+        .and_then(|section| section.functions())
+        .map_or(vec![], |functions| {
+            functions
+                .names()
+                .iter()
+                .map(|(fid, name)| (fid, name.clone()))
+                .collect::<Vec<_>>()
+        });
+
+    phantom_functions
+        .iter()
+        .map(|regex| {
+            let re = Regex::new(regex)?;
+
+            Ok(PhantomFunctionMatch {
+                regex: regex.clone(),
+                functions: names
+                    .iter()
+                    .filter(|(_, name)| re.is_match(name))
+                    .cloned()
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Static purity analysis of wasm functions.
+///
+/// A function is pure if neither itself nor any function it transitively calls
+/// writes memory or globals, grows memory, calls a host function or performs an
+/// indirect call which cannot be resolved statically.
+pub(crate) struct PurityAnalyzer<'a> {
+    module: &'a Module,
+    imported_functions: u32,
+    /// None if pure, otherwise the first impure callee and the reason.
+    cache: HashMap<u32, Option<(u32, Impurity)>>,
+}
+
+impl<'a> PurityAnalyzer<'a> {
+    pub(crate) fn new(module: &'a Module) -> Self {
+        PurityAnalyzer {
+            module,
+            imported_functions: imported_functions(module).len() as u32,
+            cache: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn has_return_value(&self, fid: u32) -> bool {
+        let type_ref = if fid < self.imported_functions {
+            self.module
+                .import_section()
+                .unwrap()
+                .entries()
+                .iter()
+                .filter_map(|entry| match entry.external() {
+                    External::Function(type_ref) => Some(*type_ref),
+                    _ => None,
+                })
+                .nth(fid as usize)
+                .unwrap()
+        } else {
+            self.module.function_section().unwrap().entries()
+                [(fid - self.imported_functions) as usize]
+                .type_ref()
+        };
+
+        match &self.module.type_section().unwrap().types()[type_ref as usize] {
+            Type::Function(func_type) => !func_type.results().is_empty(),
+        }
+    }
+
+    /// Returns the first impure function reachable from `fid` and the reason.
+    pub(crate) fn check(&mut self, fid: u32) -> Option<(u32, Impurity)> {
+        if let Some(result) = self.cache.get(&fid) {
+            return result.clone();
+        }
+
+        let mut visited = HashSet::new();
+        let mut stack = vec![fid];
+        let mut impurity = None;
+
+        while let Some(current) = stack.pop() {
+            if !visited.insert(current) {
+                continue;
+            }
+
+            match self.check_instructions(current) {
+                Ok(callees) => stack.extend(callees),
+                Err(reason) => {
+                    impurity = Some((current, reason));
+                    break;
+                }
+            }
+        }
+
+        self.cache.insert(fid, impurity.clone());
+        impurity
+    }
+
+    /// Returns the callees of `fid`, or the reason if the function itself is impure.
+    fn check_instructions(&self, fid: u32) -> std::result::Result<Vec<u32>, Impurity> {
+        if fid < self.imported_functions {
+            return Err(Impurity::CallHostFunction);
+        }
+
+        let body =
+            &self.module.code_section().unwrap().bodies()[(fid - self.imported_functions) as usize];
+
+        let mut callees = vec![];
+        for (iid, instruction) in body.code().elements().iter().enumerate() {
+            let iid = iid as u32;
+
+            match instruction {
+                Instruction::I32Store(..)
+                | Instruction::I64Store(..)
+                | Instruction::F32Store(..)
+                | Instruction::F64Store(..)
+                | Instruction::I32Store8(..)
+                | Instruction::I32Store16(..)
+                | Instruction::I64Store8(..)
+                | Instruction::I64Store16(..)
+                | Instruction::I64Store32(..) => return Err(Impurity::WriteMemory { iid }),
+                Instruction::GrowMemory(_) => return Err(Impurity::GrowMemory { iid }),
+                Instruction::SetGlobal(global) => {
+                    return Err(Impurity::WriteGlobal {
+                        iid,
+                        global: *global,
+                    })
+                }
+                Instruction::CallIndirect(..) => return Err(Impurity::CallIndirect { iid }),
+                Instruction::Call(callee) => callees.push(*callee),
+                _ => (),
+            }
+        }
+
+        Ok(callees)
+    }
+
+    pub(crate) fn function_name(&self, fid: u32) -> String {
+        if fid < self.imported_functions {
+            let (module, field) = &imported_functions(self.module)[fid as usize];
+            format!("{}.{}", module, field)
+        } else {
+            function_name(self.module, fid).unwrap_or(format!("func[{}]", fid))
+        }
+    }
+}
+
+/// Check that every phantom regex matches at least one function and all matched
+/// functions are pure and have no return value.
+///
+/// With the `phantom-hint` feature, a phantom function may return a value, e.g.
+/// the index found by a search. As its body is not traced, the value is an
+/// unconstrained hint of the prover and the caller must check it, as
+/// `crates/playground/c/phantom.c` does.
+pub(crate) fn check_phantom_functions(
+    module: &Module,
+    phantom_functions: &Vec<String>,
+) -> Result<Vec<PhantomFunctionMatch>> {
+    let report = match_phantom_functions(module, phantom_functions)?;
+    let mut analyzer = PurityAnalyzer::new(module);
+
+    for matched in report.iter() {
+        if matched.functions.is_empty() {
+            return Err(anyhow!(Error::PreCheck(
+                PreCheckErr::PhantomFunctionNotExists(matched.regex.clone())
+            )));
+        }
+
+        for (fid, name) in matched.functions.iter() {
+            if !cfg!(feature = "phantom-hint") && analyzer.has_return_value(*fid) {
+                return Err(anyhow!(Error::PreCheck(
+                    PreCheckErr::PhantomFunctionNotPure {
+                        function: name.clone(),
+                        callee: name.clone(),
+                        reason: Impurity::HasReturnValue,
+                    }
+                )));
+            }
+
+            if let Some((callee, reason)) = analyzer.check(*fid) {
+                return Err(anyhow!(Error::PreCheck(
+                    PreCheckErr::PhantomFunctionNotPure {
+                        function: name.clone(),
+                        callee: analyzer.function_name(callee),
+                        reason,
+                    }
+                )));
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// A pure function which could be marked as phantom.
+#[derive(Debug, Clone)]
+pub struct PhantomFunctionSuggestion {
+    pub fid: u32,
+    pub name: String,
+    /// The regex which matches exactly this function.
+    pub regex: String,
+    /// The etable entries produced by the function and its callees.
+    pub steps: usize,
+    /// The ratio of `steps` in the whole etable.
+    pub share: f64,
+    /// The etable rows saved if the function is phantom.
+    pub saved_rows: usize,
+    /// The return value of a phantom function is unconstrained and must be
+    /// checked by its callers, only suggested with the `phantom-hint` feature.
+    pub has_return_value: bool,
+}
+
+/// Rank the named pure functions by the etable entries they produce in `tables`.
+pub(crate) fn suggest_phantom_functions(
+    module: &Module,
+    tables: &Tables,
+) -> Vec<PhantomFunctionSuggestion> {
+    let total_steps = tables.execution_tables.etable.entries().len();
+    let mut analyzer = PurityAnalyzer::new(module);
+
+    let mut suggestions = tables
+        .function_steps()
+        .into_iter()
+        .filter(|(_, steps)| *steps > 0)
+        .filter_map(|(fid, steps)| {
+            let name = function_name(module, fid)?;
+
+            let has_return_value = analyzer.has_return_value(fid);
+
+            if (has_return_value && !cfg!(feature = "phantom-hint"))
+                || analyzer.check(fid).is_some()
+            {
+                return None;
+            }
+
+            Some(PhantomFunctionSuggestion {
+                fid,
+                regex: format!("^{}$", regex::escape(&name)),
+                name,
+                steps,
+                share: steps as f64 / total_steps as f64,
+                saved_rows: steps * EVENT_TABLE_ENTRY_ROWS as usize,
+                has_return_value,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    suggestions.sort_by(|a, b| b.saved_rows.cmp(&a.saved_rows));
+
+    suggestions
+}
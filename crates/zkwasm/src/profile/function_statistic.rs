@@ -0,0 +1,47 @@
+use specs::step::StepInfo;
+use specs::Tables;
+use std::collections::BTreeMap;
+
+pub(crate) trait FunctionStatistic {
+    /// The number of etable entries produced by all invocations of each function,
+    /// including its callees but excluding the call instruction itself.
+    /// Recursive invocations are only counted once, by the outermost frame.
+    fn function_steps(&self) -> BTreeMap<u32, usize>;
+}
+
+impl FunctionStatistic for Tables {
+    fn function_steps(&self) -> BTreeMap<u32, usize> {
+        // fid -> [(call eid, steps)] of the outermost frames seen so far
+        let mut frames = BTreeMap::<u32, Vec<(u32, usize)>>::new();
+
+        for entry in self.execution_tables.etable.entries() {
+            if let StepInfo::Return { .. } = entry.step_info {
+                // The frame of the entry function is not created by a call instruction.
+                if entry.last_jump_eid == 0 {
+                    continue;
+                }
+
+                let frames = frames.entry(entry.fid).or_insert(vec![]);
+
+                // Inner recursive frames always return before the outer ones.
+                while let Some((call_eid, _)) = frames.last() {
+                    if *call_eid > entry.last_jump_eid {
+                        frames.pop();
+                    } else {
+                        break;
+                    }
+                }
+
+                frames.push((
+                    entry.last_jump_eid,
+                    (entry.eid - entry.last_jump_eid) as usize,
+                ));
+            }
+        }
+
+        frames
+            .into_iter()
+            .map(|(fid, frames)| (fid, frames.iter().map(|(_, steps)| steps).sum()))
+            .collect()
+    }
+}
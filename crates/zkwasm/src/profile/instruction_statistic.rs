@@ -6,22 +6,24 @@ use specs::Tables;
 use std::collections::BTreeMap;
 use std::fmt::Debug;
 
-use crate::runtime::memory_event_of_step;
-
 pub trait InstructionStatistic {
     fn profile_instruction(&self);
 }
 
 impl InstructionStatistic for Tables {
     fn profile_instruction(&self) {
-        struct Counter {
+        struct Counter<'a> {
             counter: usize,
-            mentries: Vec<MemoryTableEntry>,
+            mentries: Vec<&'a MemoryTableEntry>,
         }
 
         let mut map = BTreeMap::<OpcodeClass, Counter>::new();
-        for entry in self.execution_tables.etable.entries() {
-            let mut mentries = memory_event_of_step(entry, &mut 1);
+        for (step, entry) in self.execution_tables.etable.entries().iter().enumerate() {
+            let mut mentries = self
+                .execution_tables
+                .memory_events
+                .of_step(step, &self.execution_tables.mtable)
+                .collect::<Vec<_>>();
 
             let opcode = &entry
                 .get_instruction(&self.compilation_tables.itable)
@@ -1,6 +1,7 @@
 use instruction_statistic::InstructionStatistic;
 use specs::Tables;
 
+pub(crate) mod function_statistic;
 mod helper;
 mod instruction_statistic;
 
@@ -0,0 +1,757 @@
+//! A native auditor of the traced tables.
+//!
+//! The auditor replays the semantics the circuit enforces over `Tables`
+//! without building the circuit: each step must match the decoded opcode of
+//! its instruction, and the sp, the pc, the frame, the allocated memory pages
+//! and the public host calls must transit as the instruction specifies. The
+//! memory events of each step must stay within the stack the step reads and
+//! leaves, write the slots it pushes and read the latest write of the location
+//! in execution order, and each frame of the jtable must be entered by one
+//! call and left by one return.
+//!
+//! The memory events are derived from the steps after tracing, so the auditor
+//! checks them against the steps and the imtable instead of deriving them again.
+//!
+//! The first inconsistent step is reported by `RuntimeErr::InconsistentTrace`,
+//! which is much faster and more readable than a failure of `MockProver`.
+//! Traces of images with phantom functions are not supported, as the tracer
+//! elides the steps of phantom functions.
+
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use specs::etable::EventTableEntry;
+use specs::host_function::HostPlugin;
+use specs::itable::Opcode;
+use specs::itable::OpcodeClass;
+use specs::jtable::JumpTableEntry;
+use specs::mtable::AccessType;
+use specs::mtable::LocationType;
+use specs::mtable::VarType;
+use specs::step::StepInfo;
+use specs::Tables;
+
+use crate::foreign::wasm_input_helper::Op as HostInputOp;
+use crate::loader::err::Error;
+use crate::loader::err::RuntimeErr;
+
+/// Check the trace of `tables`, the public host calls are checked against
+/// `public_inputs_and_outputs`.
+pub fn audit_trace(tables: &Tables, public_inputs_and_outputs: &[u64]) -> Result<()> {
+    let mut auditor = Auditor {
+        tables,
+        first_inconsistency: None,
+    };
+
+    auditor.audit_steps(public_inputs_and_outputs);
+    auditor.audit_memory();
+
+    match auditor.first_inconsistency {
+        Some((eid, reason)) => Err(anyhow!(Error::Runtime(RuntimeErr::InconsistentTrace {
+            eid,
+            reason
+        }))),
+        None => Ok(()),
+    }
+}
+
+/// The opcode class a step is traced from.
+fn opcode_class_of_step(step_info: &StepInfo) -> OpcodeClass {
+    match step_info {
+        StepInfo::Br { .. } => OpcodeClass::Br,
+        StepInfo::BrIfEqz { .. } => OpcodeClass::BrIfEqz,
+        StepInfo::BrIfNez { .. } => OpcodeClass::BrIf,
+        StepInfo::BrTable { .. } => OpcodeClass::BrTable,
+        StepInfo::Return { .. } => OpcodeClass::Return,
+        StepInfo::Drop => OpcodeClass::Drop,
+        StepInfo::Select { .. } => OpcodeClass::Select,
+        StepInfo::Call { .. } => OpcodeClass::Call,
+        StepInfo::CallIndirect { .. } => OpcodeClass::CallIndirect,
+        StepInfo::CallHost { .. } => OpcodeClass::ForeignPluginStart,
+        StepInfo::ExternalHostCall { .. } => OpcodeClass::CallHost,
+        StepInfo::GetLocal { .. } => OpcodeClass::LocalGet,
+        StepInfo::SetLocal { .. } => OpcodeClass::LocalSet,
+        StepInfo::TeeLocal { .. } => OpcodeClass::LocalTee,
+        StepInfo::GetGlobal { .. } => OpcodeClass::GlobalGet,
+        StepInfo::SetGlobal { .. } => OpcodeClass::GlobalSet,
+        StepInfo::Load { .. } => OpcodeClass::Load,
+        StepInfo::Store { .. } => OpcodeClass::Store,
+        StepInfo::MemorySize => OpcodeClass::MemorySize,
+        StepInfo::MemoryGrow { .. } => OpcodeClass::MemoryGrow,
+        StepInfo::I32Const { .. } | StepInfo::I64Const { .. } => OpcodeClass::Const,
+        StepInfo::I32BinOp { .. } | StepInfo::I64BinOp { .. } => OpcodeClass::Bin,
+        StepInfo::I32BinShiftOp { .. } | StepInfo::I64BinShiftOp { .. } => OpcodeClass::BinShift,
+        StepInfo::I32BinBitOp { .. } | StepInfo::I64BinBitOp { .. } => OpcodeClass::BinBit,
+        StepInfo::UnaryOp { .. } => OpcodeClass::Unary,
+        StepInfo::Test { .. } => OpcodeClass::Test,
+        StepInfo::I32Comp { .. } | StepInfo::I64Comp { .. } => OpcodeClass::Rel,
+        StepInfo::I32WrapI64 { .. }
+        | StepInfo::I64ExtendI32 { .. }
+        | StepInfo::I32SignExtendI8 { .. }
+        | StepInfo::I32SignExtendI16 { .. }
+        | StepInfo::I64SignExtendI8 { .. }
+        | StepInfo::I64SignExtendI16 { .. }
+        | StepInfo::I64SignExtendI32 { .. } => OpcodeClass::Conversion,
+    }
+}
+
+/// Whether the operands of the step agree with the opcode, only the operands
+/// affecting the control flow and the stack are compared.
+fn step_matches_opcode(step_info: &StepInfo, opcode: &Opcode) -> bool {
+    match (step_info, opcode) {
+        (
+            StepInfo::Br {
+                drop, keep, dst_pc, ..
+            },
+            Opcode::Br {
+                drop: op_drop,
+                keep: op_keep,
+                dst_pc: op_dst_pc,
+            },
+        )
+        | (
+            StepInfo::BrIfEqz {
+                drop, keep, dst_pc, ..
+            },
+            Opcode::BrIfEqz {
+                drop: op_drop,
+                keep: op_keep,
+                dst_pc: op_dst_pc,
+            },
+        )
+        | (
+            StepInfo::BrIfNez {
+                drop, keep, dst_pc, ..
+            },
+            Opcode::BrIf {
+                drop: op_drop,
+                keep: op_keep,
+                dst_pc: op_dst_pc,
+            },
+        ) => drop == op_drop && keep == op_keep && dst_pc == op_dst_pc,
+        (
+            StepInfo::BrTable {
+                index,
+                drop,
+                keep,
+                dst_pc,
+                ..
+            },
+            Opcode::BrTable { targets },
+        ) => targets
+            .get(*index as u32 as usize)
+            .or(targets.last())
+            .map_or(false, |target| {
+                target.drop == *drop && &target.keep == keep && target.dst_pc == *dst_pc
+            }),
+        (
+            StepInfo::Return { drop, keep, .. },
+            Opcode::Return {
+                drop: op_drop,
+                keep: op_keep,
+            },
+        ) => drop == op_drop && keep == op_keep,
+        (StepInfo::Call { index }, Opcode::Call { index: op_index }) => index == op_index,
+        (StepInfo::CallIndirect { type_index, .. }, Opcode::CallIndirect { type_idx }) => {
+            type_index == type_idx
+        }
+        (
+            StepInfo::CallHost {
+                plugin,
+                op_index_in_plugin,
+                ..
+            },
+            Opcode::InternalHostCall {
+                plugin: op_plugin,
+                op_index_in_plugin: op_op_index_in_plugin,
+                ..
+            },
+        ) => plugin == op_plugin && op_index_in_plugin == op_op_index_in_plugin,
+        (
+            StepInfo::ExternalHostCall { op, sig, .. },
+            Opcode::ExternalHostCall {
+                op: op_op,
+                sig: op_sig,
+            },
+        ) => op == op_op && sig == op_sig,
+        (
+            StepInfo::GetLocal { vtype, depth, .. },
+            Opcode::LocalGet {
+                vtype: op_vtype,
+                offset,
+            },
+        )
+        | (
+            StepInfo::SetLocal { vtype, depth, .. },
+            Opcode::LocalSet {
+                vtype: op_vtype,
+                offset,
+            },
+        )
+        | (
+            StepInfo::TeeLocal { vtype, depth, .. },
+            Opcode::LocalTee {
+                vtype: op_vtype,
+                offset,
+            },
+        ) => vtype == op_vtype && *depth as u64 == *offset,
+        (StepInfo::GetGlobal { idx, .. }, Opcode::GlobalGet { idx: op_idx })
+        | (StepInfo::SetGlobal { idx, .. }, Opcode::GlobalSet { idx: op_idx }) => {
+            *idx as u64 == *op_idx
+        }
+        (StepInfo::I32Const { .. }, Opcode::Const { vtype, .. }) => *vtype == VarType::I32,
+        (StepInfo::I64Const { .. }, Opcode::Const { vtype, .. }) => *vtype == VarType::I64,
+        (step_info, opcode) => {
+            let class: OpcodeClass = opcode.into();
+
+            opcode_class_of_step(step_info) == class
+        }
+    }
+}
+
+/// The change of sp by the step, as `sp_diff` of the op configures.
+fn sp_diff(step_info: &StepInfo) -> i64 {
+    match step_info {
+        StepInfo::Br { drop, .. } | StepInfo::Return { drop, .. } => *drop as i64,
+        StepInfo::BrIfEqz {
+            condition, drop, ..
+        } => 1 + if *condition == 0 { *drop as i64 } else { 0 },
+        StepInfo::BrIfNez {
+            condition, drop, ..
+        } => 1 + if *condition != 0 { *drop as i64 } else { 0 },
+        StepInfo::BrTable { drop, .. } => *drop as i64 + 1,
+        StepInfo::CallHost { args, ret_val, .. } => args.len() as i64 - ret_val.is_some() as i64,
+        // The argument is popped and the return value is pushed.
+        StepInfo::ExternalHostCall { sig, .. } => {
+            if sig.is_ret() {
+                -1
+            } else {
+                1
+            }
+        }
+        StepInfo::I32Const { .. }
+        | StepInfo::I64Const { .. }
+        | StepInfo::GetLocal { .. }
+        | StepInfo::GetGlobal { .. }
+        | StepInfo::MemorySize => -1,
+        StepInfo::Drop
+        | StepInfo::SetLocal { .. }
+        | StepInfo::SetGlobal { .. }
+        | StepInfo::CallIndirect { .. }
+        | StepInfo::I32BinOp { .. }
+        | StepInfo::I64BinOp { .. }
+        | StepInfo::I32BinShiftOp { .. }
+        | StepInfo::I64BinShiftOp { .. }
+        | StepInfo::I32BinBitOp { .. }
+        | StepInfo::I64BinBitOp { .. }
+        | StepInfo::I32Comp { .. }
+        | StepInfo::I64Comp { .. } => 1,
+        StepInfo::Select { .. } | StepInfo::Store { .. } => 2,
+        StepInfo::Call { .. }
+        | StepInfo::TeeLocal { .. }
+        | StepInfo::Load { .. }
+        | StepInfo::MemoryGrow { .. }
+        | StepInfo::UnaryOp { .. }
+        | StepInfo::Test { .. }
+        | StepInfo::I32WrapI64 { .. }
+        | StepInfo::I64ExtendI32 { .. }
+        | StepInfo::I32SignExtendI8 { .. }
+        | StepInfo::I32SignExtendI16 { .. }
+        | StepInfo::I64SignExtendI8 { .. }
+        | StepInfo::I64SignExtendI16 { .. }
+        | StepInfo::I64SignExtendI32 { .. } => 0,
+    }
+}
+
+/// The frame a step returns to.
+struct Frame {
+    last_jump_eid: u32,
+    fid: u32,
+    iid: u32,
+}
+
+struct Auditor<'a> {
+    tables: &'a Tables,
+    first_inconsistency: Option<(u32, String)>,
+}
+
+impl<'a> Auditor<'a> {
+    fn inconsistent(&mut self, eid: u32, reason: String) {
+        if self
+            .first_inconsistency
+            .as_ref()
+            .map_or(true, |(first_eid, _)| eid < *first_eid)
+        {
+            self.first_inconsistency = Some((eid, reason));
+        }
+    }
+
+    fn audit_steps(&mut self, public_inputs_and_outputs: &[u64]) {
+        let tables = self.tables;
+        let etable = tables.execution_tables.etable.entries();
+
+        let jtable = tables
+            .execution_tables
+            .jtable
+            .entries()
+            .iter()
+            .map(|entry| (entry.eid, entry))
+            .collect::<HashMap<u32, &JumpTableEntry>>();
+        // The calls and returns of each frame in the jtable.
+        let mut jops = HashMap::<u32, (usize, usize)>::new();
+        let mut public_index = 0;
+
+        for (step, entry) in etable.iter().enumerate() {
+            let next = etable.get(step + 1);
+
+            let opcode = match tables
+                .compilation_tables
+                .itable
+                .try_get(entry.fid, entry.iid)
+            {
+                Some(instruction) => &instruction.opcode,
+                None => {
+                    self.inconsistent(
+                        entry.eid,
+                        format!("no instruction at fid {} iid {}", entry.fid, entry.iid),
+                    );
+                    return;
+                }
+            };
+
+            if !step_matches_opcode(&entry.step_info, opcode) {
+                self.inconsistent(
+                    entry.eid,
+                    format!(
+                        "step {:?} does not match the instruction {:?}",
+                        entry.step_info, opcode
+                    ),
+                );
+                return;
+            }
+
+            if let StepInfo::CallHost {
+                plugin: HostPlugin::HostInput,
+                op_index_in_plugin,
+                args,
+                ret_val,
+                ..
+            } = &entry.step_info
+            {
+                let public_value = if *op_index_in_plugin == HostInputOp::WasmInput as usize {
+                    ret_val.filter(|_| args[0] != 0)
+                } else {
+                    Some(args[0])
+                };
+
+                if let Some(value) = public_value {
+                    if public_inputs_and_outputs.get(public_index) != Some(&value) {
+                        self.inconsistent(
+                            entry.eid,
+                            format!(
+                                "public host call {} has value {}, but the instance is {:?}",
+                                public_index,
+                                value,
+                                public_inputs_and_outputs.get(public_index)
+                            ),
+                        );
+                        return;
+                    }
+
+                    public_index += 1;
+                }
+            }
+
+            let frame = match &entry.step_info {
+                StepInfo::Call { index } => {
+                    self.audit_call(entry, *index, &jtable, &mut jops);
+                    Some(Frame {
+                        last_jump_eid: entry.eid,
+                        fid: *index,
+                        iid: 0,
+                    })
+                }
+                StepInfo::CallIndirect { func_index, .. } => {
+                    self.audit_call(entry, *func_index, &jtable, &mut jops);
+                    Some(Frame {
+                        last_jump_eid: entry.eid,
+                        fid: *func_index,
+                        iid: 0,
+                    })
+                }
+                StepInfo::Return { .. } => self.audit_return(entry, &jtable, &mut jops),
+                _ => None,
+            };
+            if self.first_inconsistency.is_some() {
+                return;
+            }
+
+            let next = match next {
+                Some(next) => next,
+                None => {
+                    if !matches!(entry.step_info, StepInfo::Return { .. }) {
+                        self.inconsistent(
+                            entry.eid,
+                            "the trace does not terminate by a return".to_owned(),
+                        );
+                    }
+                    break;
+                }
+            };
+
+            if next.eid != entry.eid + 1 {
+                self.inconsistent(entry.eid, format!("the next step has eid {}", next.eid));
+                return;
+            }
+
+            let expected_sp = entry.sp as i64 + sp_diff(&entry.step_info);
+            if next.sp as i64 != expected_sp {
+                self.inconsistent(
+                    entry.eid,
+                    format!(
+                        "sp of the next step is {}, but {:?} leaves sp {}",
+                        next.sp,
+                        opcode_class_of_step(&entry.step_info),
+                        expected_sp
+                    ),
+                );
+                return;
+            }
+
+            let expected_pages = match &entry.step_info {
+                StepInfo::MemoryGrow { grow_size, result } if *result != -1 => {
+                    entry.allocated_memory_pages + *grow_size as u32
+                }
+                _ => entry.allocated_memory_pages,
+            };
+            if next.allocated_memory_pages != expected_pages {
+                self.inconsistent(
+                    entry.eid,
+                    format!(
+                        "allocated memory pages of the next step is {}, expected {}",
+                        next.allocated_memory_pages, expected_pages
+                    ),
+                );
+                return;
+            }
+
+            let expected = frame.unwrap_or_else(|| Frame {
+                last_jump_eid: entry.last_jump_eid,
+                fid: entry.fid,
+                iid: match &entry.step_info {
+                    StepInfo::Br { dst_pc, .. } | StepInfo::BrTable { dst_pc, .. } => *dst_pc,
+                    StepInfo::BrIfEqz {
+                        condition, dst_pc, ..
+                    } if *condition == 0 => *dst_pc,
+                    StepInfo::BrIfNez {
+                        condition, dst_pc, ..
+                    } if *condition != 0 => *dst_pc,
+                    _ => entry.iid + 1,
+                },
+            });
+            if (next.fid, next.iid, next.last_jump_eid)
+                != (expected.fid, expected.iid, expected.last_jump_eid)
+            {
+                self.inconsistent(
+                    entry.eid,
+                    format!(
+                        "the next step is at fid {} iid {} in frame {}, expected fid {} iid {} in frame {}",
+                        next.fid,
+                        next.iid,
+                        next.last_jump_eid,
+                        expected.fid,
+                        expected.iid,
+                        expected.last_jump_eid
+                    ),
+                );
+                return;
+            }
+        }
+
+        if public_index != public_inputs_and_outputs.len() {
+            let eid = etable.last().map_or(0, |entry| entry.eid);
+            self.inconsistent(
+                eid,
+                format!(
+                    "{} public host calls are traced, but there are {} instances",
+                    public_index,
+                    public_inputs_and_outputs.len()
+                ),
+            );
+        }
+
+        for eid in jtable.keys() {
+            let (calls, returns) = jops.get(eid).cloned().unwrap_or_default();
+
+            if calls != 1 || returns != 1 {
+                self.inconsistent(
+                    *eid,
+                    format!(
+                        "the frame of the call is entered {} times and left {} times",
+                        calls, returns
+                    ),
+                );
+            }
+        }
+    }
+
+    fn audit_call(
+        &mut self,
+        entry: &EventTableEntry,
+        callee_fid: u32,
+        jtable: &HashMap<u32, &JumpTableEntry>,
+        jops: &mut HashMap<u32, (usize, usize)>,
+    ) {
+        match jtable.get(&entry.eid) {
+            Some(frame)
+                if frame.last_jump_eid == entry.last_jump_eid
+                    && frame.callee_fid == callee_fid
+                    && frame.fid == entry.fid
+                    && frame.iid == entry.iid + 1 =>
+            {
+                jops.entry(entry.eid).or_insert((0, 0)).0 += 1;
+            }
+            frame => self.inconsistent(
+                entry.eid,
+                format!(
+                    "the call to fid {} is recorded as {:?} in the jtable",
+                    callee_fid, frame
+                ),
+            ),
+        }
+    }
+
+    fn audit_return(
+        &mut self,
+        entry: &EventTableEntry,
+        jtable: &HashMap<u32, &JumpTableEntry>,
+        jops: &mut HashMap<u32, (usize, usize)>,
+    ) -> Option<Frame> {
+        if let Some(frame) = jtable
+            .get(&entry.last_jump_eid)
+            .filter(|frame| frame.callee_fid == entry.fid)
+        {
+            jops.entry(frame.eid).or_insert((0, 0)).1 += 1;
+
+            return Some(Frame {
+                last_jump_eid: frame.last_jump_eid,
+                fid: frame.fid,
+                iid: frame.iid,
+            });
+        }
+
+        let static_frame = self
+            .tables
+            .compilation_tables
+            .static_jtable
+            .iter()
+            .find(|frame| {
+                frame.enable
+                    && frame.frame_id == entry.last_jump_eid
+                    && frame.callee_fid == entry.fid
+            });
+
+        match static_frame {
+            Some(frame) => Some(Frame {
+                last_jump_eid: frame.next_frame_id,
+                fid: frame.fid,
+                iid: frame.iid,
+            }),
+            None => {
+                self.inconsistent(
+                    entry.eid,
+                    format!(
+                        "returns from fid {} to frame {}, which is not in the jtable",
+                        entry.fid, entry.last_jump_eid
+                    ),
+                );
+                None
+            }
+        }
+    }
+
+    fn audit_memory(&mut self) {
+        let tables = self.tables;
+        let etable = tables.execution_tables.etable.entries();
+        let mtable = tables.execution_tables.mtable.entries();
+        let memory_events = &tables.execution_tables.memory_events;
+        let imtable = &tables.compilation_tables.imtable;
+
+        if memory_events.steps() != etable.len() {
+            let eid = etable
+                .get(memory_events.steps())
+                .map_or(0, |entry| entry.eid);
+            self.inconsistent(eid, "the memory events of the step are missing".to_owned());
+            return;
+        }
+
+        // The rows of the steps are replayed in execution order against a
+        // shadow memory initialized by the imtable, rather than compared with
+        // the events derived from the steps again.
+        let mut memory = HashMap::<(LocationType, u32), u64>::new();
+        let mut accessed_rows = vec![false; mtable.len()];
+
+        for (step, entry) in etable.iter().enumerate() {
+            let next_sp = (entry.sp as i64 + sp_diff(&entry.step_info)) as u32;
+            let mut pushed = (next_sp + 1..=entry.sp).collect::<Vec<_>>();
+            let mut global = match &entry.step_info {
+                StepInfo::GetGlobal { idx, value, .. } => Some((*idx, AccessType::Read, *value)),
+                StepInfo::SetGlobal { idx, value, .. } => Some((*idx, AccessType::Write, *value)),
+                _ => None,
+            };
+
+            for row in memory_events.mtable_rows_of_step(step) {
+                let event = match mtable.get(*row) {
+                    Some(event) if !accessed_rows[*row] => event,
+                    _ => {
+                        self.inconsistent(
+                            entry.eid,
+                            format!(
+                                "row {} of the memory table is not an event of the step",
+                                row
+                            ),
+                        );
+                        return;
+                    }
+                };
+                accessed_rows[*row] = true;
+
+                if event.eid != entry.eid || event.atype == AccessType::Init {
+                    self.inconsistent(
+                        entry.eid,
+                        format!("memory event {:?} is not an access of the step", event),
+                    );
+                    return;
+                }
+
+                // Reads are below the sp of the step, writes below the sp it leaves.
+                if event.ltype == LocationType::Stack {
+                    let bound = match event.atype {
+                        AccessType::Read => entry.sp,
+                        _ => next_sp,
+                    };
+
+                    if event.offset <= bound {
+                        self.inconsistent(
+                            entry.eid,
+                            format!(
+                                "{:?} of stack {} is above sp {}",
+                                event.atype, event.offset, bound
+                            ),
+                        );
+                        return;
+                    }
+                }
+
+                if event.ltype == LocationType::Stack && event.atype == AccessType::Write {
+                    pushed.retain(|offset| *offset != event.offset);
+                }
+
+                if event.ltype == LocationType::Global
+                    && global == Some((event.offset, event.atype, event.value))
+                {
+                    global = None;
+                }
+
+                let location = (event.ltype, event.offset);
+                match event.atype {
+                    AccessType::Read => {
+                        let latest = memory.get(&location).copied().or_else(|| {
+                            (event.ltype != LocationType::Stack)
+                                .then(|| imtable.try_find(event.ltype, event.offset))
+                                .flatten()
+                                .map(|(_, _, value)| value)
+                        });
+
+                        if latest != Some(event.value) {
+                            self.inconsistent(
+                                entry.eid,
+                                format!(
+                                    "reads {} from {:?} {}, but the latest write is {:?}",
+                                    event.value, event.ltype, event.offset, latest
+                                ),
+                            );
+                            return;
+                        }
+                    }
+                    _ => {
+                        memory.insert(location, event.value);
+                    }
+                }
+            }
+
+            if let Some(offset) = pushed.first() {
+                self.inconsistent(
+                    entry.eid,
+                    format!("stack {} is pushed without being written", offset),
+                );
+                return;
+            }
+
+            if let Some((idx, atype, value)) = global {
+                self.inconsistent(
+                    entry.eid,
+                    format!(
+                        "{:?} of global {} with value {} is missing",
+                        atype, idx, value
+                    ),
+                );
+                return;
+            }
+        }
+
+        if let Some(row) = accessed_rows
+            .iter()
+            .zip(mtable)
+            .position(|(accessed, entry)| !accessed && entry.atype != AccessType::Init)
+        {
+            self.inconsistent(
+                mtable[row].eid,
+                format!(
+                    "row {} of the memory table is not an event of any step",
+                    row
+                ),
+            );
+        }
+
+        let mut last_value = None;
+        for (row, entry) in mtable.iter().enumerate() {
+            let is_first_access = row == 0 || !mtable[row - 1].is_same_location(entry);
+
+            if row > 0 && mtable[row - 1].sort_key() >= entry.sort_key() {
+                self.inconsistent(
+                    entry.eid,
+                    format!("the memory table is not sorted at row {}", row),
+                );
+            }
+
+            if is_first_access {
+                last_value = None;
+            } else if entry.atype == AccessType::Init {
+                self.inconsistent(
+                    entry.eid,
+                    format!(
+                        "{:?} {} is initialized after being accessed",
+                        entry.ltype, entry.offset
+                    ),
+                );
+            }
+
+            match entry.atype {
+                AccessType::Read => {
+                    if last_value != Some(entry.value) {
+                        self.inconsistent(
+                            entry.eid,
+                            format!(
+                                "reads {} from {:?} {}, but the latest write is {:?}",
+                                entry.value, entry.ltype, entry.offset, last_value
+                            ),
+                        );
+                    }
+                }
+                AccessType::Write | AccessType::Init => last_value = Some(entry.value),
+            }
+        }
+    }
+}
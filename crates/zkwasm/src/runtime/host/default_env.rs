@@ -5,12 +5,13 @@ use crate::foreign::context::runtime::register_context_foreign;
 use crate::foreign::log_helper::register_log_foreign;
 use crate::foreign::require_helper::register_require_foreign;
 use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
-use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
+use crate::runtime::WasmRuntimeIO;
 use specs::args::parse_args;
 
 use super::host_env::HostEnv;
 use super::HostEnvBuilder;
 
+#[derive(Default)]
 pub struct ExecutionArg {
     /// Public inputs for `wasm_input(1)`
     pub public_inputs: Vec<u64>,
@@ -20,6 +21,8 @@ pub struct ExecutionArg {
     pub context_inputs: Vec<u64>,
     /// Context outputs for `wasm_write_context()`
     pub context_outputs: Arc<Mutex<Vec<u64>>>,
+    /// The maximal steps of the execution, unlimited if None
+    pub step_limit: Option<u64>,
 }
 
 impl super::ContextOutput for ExecutionArg {
@@ -33,12 +36,11 @@ impl From<super::Sequence> for ExecutionArg {
         let private_inputs = parse_args(seq.private_inputs.iter().map(|s| s.as_str()).collect());
         let public_inputs = parse_args(seq.public_inputs.iter().map(|s| s.as_str()).collect());
         let context_inputs = parse_args(seq.context_input.iter().map(|s| s.as_str()).collect());
-        let context_outputs = Arc::new(Mutex::new(vec![]));
         ExecutionArg {
             private_inputs,
             public_inputs,
             context_inputs,
-            context_outputs,
+            ..Default::default()
         }
     }
 }
@@ -61,8 +63,9 @@ impl HostEnvBuilder for DefaultHostEnvBuilder {
 
     fn create_env(arg: Self::Arg) -> (HostEnv, WasmRuntimeIO) {
         let mut env = HostEnv::new();
-        let wasm_runtime_io =
+        let mut wasm_runtime_io =
             register_wasm_input_foreign(&mut env, arg.public_inputs, arg.private_inputs);
+        wasm_runtime_io.step_limit = arg.step_limit;
         register_require_foreign(&mut env);
         register_log_foreign(&mut env);
         register_context_foreign(&mut env, arg.context_inputs, arg.context_outputs);
@@ -4,15 +4,14 @@ use std::collections::HashMap;
 use std::rc::Rc;
 use std::time::Instant;
 
+use anyhow::Result;
 use log::debug;
 use specs::host_function::HostFunctionDesc;
-use wasmi::Externals;
-use wasmi::ModuleImportResolver;
 use wasmi::RuntimeArgs;
 use wasmi::RuntimeValue;
-use wasmi::Trap;
 
 use crate::runtime::host::HostFunctionExecutionEnv;
+use crate::runtime::WasmValue;
 
 use super::external_circuit_plugin::ExternalCircuitEnv;
 use super::internal_circuit_plugin::InternalCircuitEnv;
@@ -128,32 +127,34 @@ impl HostEnv {
             .collect()
     }
 
-    pub fn display_time_profile(&self) {
-        debug!("Execution time(ms) of Foreign Functions:");
-        self.time_profile.iter().for_each(|(func, ms)| {
-            debug!("{}:\t{}", func, ms);
-        })
+    /// Returns the index of the host function `name`, which is passed to `invoke`.
+    pub fn resolve_function(&self, name: &str) -> Option<usize> {
+        self.cached_lookup
+            .as_ref()
+            .unwrap()
+            .iter()
+            .find(|(_, host_function)| host_function.desc.name() == name)
+            .map(|(index, _)| *index)
     }
-}
 
-impl ModuleImportResolver for HostEnv {
-    fn resolve_func(
-        &self,
-        function_name: &str,
-        signature: &wasmi::Signature,
-    ) -> Result<wasmi::FuncRef, wasmi::Error> {
-        self.external_env
-            .resolve_func(function_name, signature)
-            .or_else(|_| self.internal_env.resolve_func(function_name, signature))
+    /// Invokes the host function at `index` with the arguments of the image,
+    /// a return value other than an integer is reported as an error.
+    pub fn invoke(&mut self, index: usize, args: &[WasmValue]) -> Result<Option<WasmValue>> {
+        let args = args
+            .iter()
+            .map(|arg| RuntimeValue::from(*arg))
+            .collect::<Vec<_>>();
+
+        self.invoke_callback(index, RuntimeArgs::from(&args[..]))
+            .map(WasmValue::try_from)
+            .transpose()
     }
-}
 
-impl Externals for HostEnv {
-    fn invoke_index(
+    pub(crate) fn invoke_callback(
         &mut self,
         index: usize,
         args: RuntimeArgs,
-    ) -> Result<Option<RuntimeValue>, Trap> {
+    ) -> Option<RuntimeValue> {
         match self.cached_lookup.as_ref().unwrap().get(&index).clone() {
             Some(HostFunction {
                 desc,
@@ -171,9 +172,16 @@ impl Externals for HostEnv {
                     .and_modify(|d| *d += duration.as_millis())
                     .or_insert(duration.as_millis());
 
-                Ok(r)
+                r
             }
             None => unreachable!(),
         }
     }
+
+    pub fn display_time_profile(&self) {
+        debug!("Execution time(ms) of Foreign Functions:");
+        self.time_profile.iter().for_each(|(func, ms)| {
+            debug!("{}:\t{}", func, ms);
+        })
+    }
 }
@@ -1,5 +1,5 @@
 use self::host_env::HostEnv;
-use super::wasmi_interpreter::WasmRuntimeIO;
+use super::WasmRuntimeIO;
 use downcast_rs::impl_downcast;
 use downcast_rs::Downcast;
 use serde::Deserialize;
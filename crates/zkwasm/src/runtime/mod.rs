@@ -1,6 +1,8 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use anyhow::Result;
+use parity_wasm::elements::Module;
 use specs::etable::EventTableEntry;
 use specs::external_host_call_table::ExternalHostCallSignature;
 use specs::mtable::AccessType;
@@ -11,9 +13,14 @@ use specs::step::StepInfo;
 use specs::CompilationTable;
 use specs::Tables;
 
+use self::host::host_env::HostEnv;
+use self::step_counter::StepCounts;
 use self::wasmi_interpreter::WasmiRuntime;
 
+pub mod auditor;
 pub mod host;
+pub mod mtable_builder;
+pub mod step_counter;
 pub mod wasmi_interpreter;
 
 pub struct CompiledImage<I, T> {
@@ -23,15 +30,94 @@ pub struct CompiledImage<I, T> {
     pub tracer: Rc<RefCell<T>>,
 }
 
+/// A value passed to or returned from the image, independent of the runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WasmValue {
+    I32(i32),
+    I64(i64),
+}
+
+pub struct WasmRuntimeIO {
+    pub public_inputs_and_outputs: Rc<RefCell<Vec<u64>>>,
+    pub outputs: Rc<RefCell<Vec<u64>>>,
+    /// The maximal steps of the execution, unlimited if None.
+    pub step_limit: Option<u64>,
+}
+
+impl WasmRuntimeIO {
+    pub fn empty() -> Self {
+        Self {
+            public_inputs_and_outputs: Rc::new(RefCell::new(vec![])),
+            outputs: Rc::new(RefCell::new(vec![])),
+            step_limit: None,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct ExecutionResult<R> {
     pub tables: Tables,
     pub result: Option<R>,
+    /// Steps of the execution, i.e. the entries of the event table. In dry run,
+    /// the upper bound counted by `step_counter`.
+    pub consumed_steps: u64,
     pub public_inputs_and_outputs: Vec<u64>,
     pub outputs: Vec<u64>,
 }
 
-// TODO: use feature
+/// The result of the non-tracing dry run.
+#[derive(Clone, Debug)]
+pub struct DryRunResult {
+    pub step_counts: StepCounts,
+    pub public_inputs_and_outputs: Vec<u64>,
+    pub outputs: Vec<u64>,
+}
+
+/// The interpreter which compiles and executes images for `ZkWasmLoader`.
+///
+/// Host imports are resolved by `HostEnv`, the runtime traces the image into
+/// the `CompilationTable` and, unless in dry run, the `ExecutionTable`. A
+/// runtime resolves imports with `HostEnv::resolve_function` and calls them with
+/// `HostEnv::invoke`.
+pub trait WasmRuntime {
+    /// The image loaded into the runtime.
+    type Module;
+
+    fn load(module: &Module) -> Result<Self::Module>;
+
+    fn compile(
+        module: &Self::Module,
+        env: &HostEnv,
+        entry: &str,
+        dryrun: bool,
+        phantom_functions: &Vec<String>,
+    ) -> Result<CompilationTable>;
+
+    /// Compile then execute the image, the execution tables are left empty in dry run.
+    ///
+    /// The execution fails with `RuntimeErr::OutOfFuel` once it exceeds the
+    /// step limit of `wasm_io`, before any trace is produced.
+    fn run(
+        module: &Self::Module,
+        env: &mut HostEnv,
+        wasm_io: WasmRuntimeIO,
+        entry: &str,
+        dryrun: bool,
+        phantom_functions: &Vec<String>,
+    ) -> Result<ExecutionResult<WasmValue>>;
+
+    /// Execute the image without tracing and only count the steps of each table,
+    /// see `step_counter`.
+    fn dry_run(
+        module: &Self::Module,
+        env: &mut HostEnv,
+        wasm_io: WasmRuntimeIO,
+        entry: &str,
+        phantom_functions: &Vec<String>,
+    ) -> Result<DryRunResult>;
+}
+
+/// The default runtime of `ZkWasmLoader`.
 pub type WasmInterpreter = WasmiRuntime;
 
 pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<MemoryTableEntry> {
@@ -0,0 +1,279 @@
+//! Building the memory table by an external sort.
+//!
+//! Memory events are buffered in chunks, a full chunk is sorted by
+//! `MemoryTableEntry::sort_key` and spilled to a run file, and the runs are
+//! merged into the memory table at last. The init entries are emitted during
+//! the merge before the first access of each heap and global location, and
+//! the row of each event in the memory table is recorded in `MemoryEvents`.
+//!
+//! Only the sort is bounded by `MTABLE_CHUNK_ENTRIES`: the merged `MTable` is
+//! kept in memory as the circuit assigns it as a whole, and so is the event
+//! table of the tracer.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use specs::imtable::InitMemoryTable;
+use specs::mtable::AccessType;
+use specs::mtable::LocationType;
+use specs::mtable::MTable;
+use specs::mtable::MemoryEvents;
+use specs::mtable::MemoryTableEntry;
+use specs::mtable::VarType;
+
+/// Memory events buffered before a chunk is spilled, about 128MB.
+pub const MTABLE_CHUNK_ENTRIES: usize = 1 << 22;
+
+/// The directory of the spilled runs, `ZKWASM_SPILL_DIR` or the temporary directory.
+pub fn spill_dir() -> PathBuf {
+    std::env::var("ZKWASM_SPILL_DIR").map_or(std::env::temp_dir(), PathBuf::from)
+}
+
+const ENCODED_ENTRY_SIZE: usize = 32;
+
+static SPILL_ID: AtomicUsize = AtomicUsize::new(0);
+
+fn encode(entry: &MemoryTableEntry, event_index: usize) -> [u8; ENCODED_ENTRY_SIZE] {
+    let mut buf = [0u8; ENCODED_ENTRY_SIZE];
+
+    buf[0..4].copy_from_slice(&entry.eid.to_le_bytes());
+    buf[4..8].copy_from_slice(&entry.emid.to_le_bytes());
+    buf[8..12].copy_from_slice(&entry.offset.to_le_bytes());
+    buf[12] = entry.ltype as u8;
+    buf[13] = entry.atype as u8;
+    buf[14] = entry.vtype as u8;
+    buf[15] = entry.is_mutable as u8;
+    buf[16..24].copy_from_slice(&entry.value.to_le_bytes());
+    buf[24..32].copy_from_slice(&(event_index as u64).to_le_bytes());
+
+    buf
+}
+
+fn decode(buf: &[u8; ENCODED_ENTRY_SIZE]) -> Result<(MemoryTableEntry, usize)> {
+    let u32_at = |i: usize| u32::from_le_bytes(buf[i..i + 4].try_into().unwrap());
+    let u64_at = |i: usize| u64::from_le_bytes(buf[i..i + 8].try_into().unwrap());
+
+    let entry = MemoryTableEntry {
+        eid: u32_at(0),
+        emid: u32_at(4),
+        offset: u32_at(8),
+        ltype: match buf[12] {
+            1 => LocationType::Stack,
+            2 => LocationType::Heap,
+            3 => LocationType::Global,
+            v => return Err(anyhow!("invalid location type {} in mtable run", v)),
+        },
+        atype: match buf[13] {
+            1 => AccessType::Read,
+            2 => AccessType::Write,
+            3 => AccessType::Init,
+            v => return Err(anyhow!("invalid access type {} in mtable run", v)),
+        },
+        vtype: match buf[14] {
+            0 => VarType::I64,
+            1 => VarType::I32,
+            v => return Err(anyhow!("invalid var type {} in mtable run", v)),
+        },
+        is_mutable: buf[15] != 0,
+        value: u64_at(16),
+    };
+
+    Ok((entry, u64_at(24) as usize))
+}
+
+/// A sorted run spilled to disk.
+struct Run {
+    reader: BufReader<File>,
+}
+
+impl Run {
+    fn next(&mut self) -> Result<Option<(MemoryTableEntry, usize)>> {
+        let mut buf = [0u8; ENCODED_ENTRY_SIZE];
+
+        match self.reader.read_exact(&mut buf) {
+            Ok(()) => Ok(Some(decode(&buf)?)),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+pub struct MTableBuilder {
+    spill_dir: PathBuf,
+    chunk_entries: usize,
+    events: MemoryEvents,
+    // The buffered events with their indices in `events`.
+    buffer: Vec<(MemoryTableEntry, usize)>,
+    runs: Vec<PathBuf>,
+}
+
+impl MTableBuilder {
+    /// Runs are spilled into a fresh directory under `spill_dir` once more than
+    /// `chunk_entries` events are buffered.
+    pub fn new(spill_dir: PathBuf, chunk_entries: usize) -> Self {
+        let spill_dir = spill_dir.join(format!(
+            "zkwasm-mtable-{}-{}",
+            std::process::id(),
+            SPILL_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        MTableBuilder {
+            spill_dir,
+            chunk_entries,
+            events: MemoryEvents::default(),
+            buffer: vec![],
+            runs: vec![],
+        }
+    }
+
+    /// Push the memory events of the next step of the event table.
+    pub fn push(&mut self, entries: Vec<MemoryTableEntry>) -> Result<()> {
+        let first_index = self.events.events();
+
+        self.events.push_step(entries.len());
+        self.buffer.extend(
+            entries
+                .into_iter()
+                .enumerate()
+                .map(|(i, entry)| (entry, first_index + i)),
+        );
+
+        if self.buffer.len() >= self.chunk_entries {
+            self.spill()?;
+        }
+
+        Ok(())
+    }
+
+    fn spill(&mut self) -> Result<()> {
+        if self.runs.is_empty() {
+            std::fs::create_dir_all(&self.spill_dir)?;
+        }
+
+        let path = self.spill_dir.join(format!("run.{}", self.runs.len()));
+        let mut writer = BufWriter::new(File::create(&path)?);
+
+        self.buffer.sort_by_key(|(entry, _)| entry.sort_key());
+        for (entry, event_index) in self.buffer.drain(..) {
+            writer.write_all(&encode(&entry, event_index))?;
+        }
+        writer.flush()?;
+
+        self.runs.push(path);
+
+        Ok(())
+    }
+
+    /// Merge the runs into the memory table, the spilled runs are removed.
+    /// The pushed events are returned linked to their rows in the table.
+    pub fn finalize(mut self, imtable: &InitMemoryTable) -> Result<(MTable, MemoryEvents)> {
+        let result = self.merge(imtable);
+
+        if !self.runs.is_empty() {
+            self.runs.clear();
+            std::fs::remove_dir_all(&self.spill_dir)?;
+        }
+
+        let (mtable, mtable_rows) = result?;
+        let mut events = std::mem::take(&mut self.events);
+        events.set_mtable_rows(mtable_rows);
+
+        Ok((mtable, events))
+    }
+
+    fn merge(&mut self, imtable: &InitMemoryTable) -> Result<(MTable, Vec<usize>)> {
+        self.buffer.sort_by_key(|(entry, _)| entry.sort_key());
+
+        let mut runs = self
+            .runs
+            .iter()
+            .map(|path| {
+                Ok(Run {
+                    reader: BufReader::new(File::open(path)?),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut buffer = std::mem::take(&mut self.buffer).into_iter();
+
+        // The buffer in memory is the run with index `runs.len()`.
+        let mut next_of = |index: usize| -> Result<Option<(MemoryTableEntry, usize)>> {
+            match runs.get_mut(index) {
+                Some(run) => run.next(),
+                None => Ok(buffer.next()),
+            }
+        };
+
+        let mut heap = BinaryHeap::new();
+        for index in 0..=self.runs.len() {
+            if let Some(entry) = next_of(index)? {
+                heap.push(Reverse((entry.0.sort_key(), index, HeapEntry(entry))));
+            }
+        }
+
+        let mut entries: Vec<MemoryTableEntry> = vec![];
+        let mut mtable_rows = vec![0; self.events.events()];
+        while let Some(Reverse((_, index, HeapEntry((entry, event_index))))) = heap.pop() {
+            let is_first_access = entries
+                .last()
+                .map_or(true, |last| !last.is_same_location(&entry));
+            if is_first_access {
+                if let Some(init_entry) = entry.init_entry(imtable) {
+                    entries.push(init_entry);
+                }
+            }
+
+            mtable_rows[event_index] = entries.len();
+            entries.push(entry);
+
+            if let Some(entry) = next_of(index)? {
+                heap.push(Reverse((entry.0.sort_key(), index, HeapEntry(entry))));
+            }
+        }
+
+        Ok((MTable::from_sorted_entries(entries), mtable_rows))
+    }
+}
+
+impl Drop for MTableBuilder {
+    /// The spilled runs are removed if the builder is not finalized, e.g. when
+    /// the execution fails after some chunks have been spilled.
+    fn drop(&mut self) {
+        if !self.runs.is_empty() {
+            let _ = std::fs::remove_dir_all(&self.spill_dir);
+        }
+    }
+}
+
+/// Entries in the heap are ordered by the sort key and the run index only.
+struct HeapEntry((MemoryTableEntry, usize));
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, _: &Self) -> std::cmp::Ordering {
+        std::cmp::Ordering::Equal
+    }
+}
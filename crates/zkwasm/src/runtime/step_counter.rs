@@ -0,0 +1,431 @@
+//! Step counting of the non-tracing dry run.
+//!
+//! Instead of tracing each instruction, the image is instrumented with a
+//! mutable i64 global per table class. Each straight-line segment of a
+//! function body is prefixed by the increments of the instructions of the
+//! segment, so the overhead is a few instructions per basic block.
+//!
+//! The counts are upper bounds of the traced tables: memory accesses are
+//! counted as the worst case of each instruction, and the callees of phantom
+//! functions are counted although they are not traced. Each access of a global
+//! or heap word is charged one more memory table entry, for the `Init` entry of
+//! the location which is accessed for the first time.
+//!
+//! With a step limit, each segment traps by `unreachable` once the event table
+//! counter exceeds the limit, so a segment is charged before it is executed.
+//! The bodies of phantom functions are not traced, their instructions are
+//! charged to a separate counter which is limited likewise.
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+use parity_wasm::elements::BlockType;
+use parity_wasm::elements::ExportEntry;
+use parity_wasm::elements::ExportSection;
+use parity_wasm::elements::External;
+use parity_wasm::elements::GlobalEntry;
+use parity_wasm::elements::GlobalSection;
+use parity_wasm::elements::GlobalType;
+use parity_wasm::elements::ImportCountType;
+use parity_wasm::elements::InitExpr;
+use parity_wasm::elements::Instruction;
+use parity_wasm::elements::Internal;
+use parity_wasm::elements::Module;
+use parity_wasm::elements::Section;
+use parity_wasm::elements::SignExtInstruction;
+use parity_wasm::elements::Type;
+use parity_wasm::elements::ValueType;
+
+/// Steps of each table class counted by the dry run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StepCounts {
+    /// Entries of the event table.
+    pub etable: u64,
+    /// Memory accesses, the entries of the memory table.
+    pub mtable: u64,
+    /// Calls of wasm functions, the entries of the jump table.
+    pub jtable: u64,
+    /// Bit operations, the entries of the bit table.
+    pub bit_table: u64,
+    /// Calls of external host functions.
+    pub external_host_calls: u64,
+    /// Instructions of the bodies of phantom functions, which are not traced.
+    pub phantom: u64,
+}
+
+#[derive(Clone, Copy)]
+enum StepClass {
+    EventTable,
+    MemoryTable,
+    JumpTable,
+    BitTable,
+    ExternalHostCall,
+    Phantom,
+}
+
+const STEP_CLASSES: [StepClass; 6] = [
+    StepClass::EventTable,
+    StepClass::MemoryTable,
+    StepClass::JumpTable,
+    StepClass::BitTable,
+    StepClass::ExternalHostCall,
+    StepClass::Phantom,
+];
+
+impl StepClass {
+    fn export_name(&self) -> &'static str {
+        match self {
+            StepClass::EventTable => "__zkwasm_steps_etable",
+            StepClass::MemoryTable => "__zkwasm_steps_mtable",
+            StepClass::JumpTable => "__zkwasm_steps_jtable",
+            StepClass::BitTable => "__zkwasm_steps_bit_table",
+            StepClass::ExternalHostCall => "__zkwasm_steps_external_host_calls",
+            StepClass::Phantom => "__zkwasm_steps_phantom",
+        }
+    }
+}
+
+impl StepCounts {
+    fn add(&mut self, class: StepClass, steps: u64) {
+        match class {
+            StepClass::EventTable => self.etable += steps,
+            StepClass::MemoryTable => self.mtable += steps,
+            StepClass::JumpTable => self.jtable += steps,
+            StepClass::BitTable => self.bit_table += steps,
+            StepClass::ExternalHostCall => self.external_host_calls += steps,
+            StepClass::Phantom => self.phantom += steps,
+        }
+    }
+
+    fn get(&self, class: StepClass) -> u64 {
+        match class {
+            StepClass::EventTable => self.etable,
+            StepClass::MemoryTable => self.mtable,
+            StepClass::JumpTable => self.jtable,
+            StepClass::BitTable => self.bit_table,
+            StepClass::ExternalHostCall => self.external_host_calls,
+            StepClass::Phantom => self.phantom,
+        }
+    }
+
+    /// Whether the execution trapped by the step limit of `instrument`.
+    pub fn exceeds_step_limit(&self, step_limit: Option<u64>) -> bool {
+        step_limit.map_or(false, |step_limit| {
+            self.etable > step_limit || self.phantom > step_limit
+        })
+    }
+
+    /// Read the counters exported by the instrumented image.
+    pub fn read(mut read_global: impl FnMut(&str) -> Option<u64>) -> Option<Self> {
+        let mut counts = StepCounts::default();
+
+        for class in STEP_CLASSES {
+            counts.add(class, read_global(class.export_name())?);
+        }
+
+        Some(counts)
+    }
+}
+
+/// A host function imported by the image, with its arity.
+struct HostImport {
+    external: bool,
+    params: u64,
+    has_return: bool,
+}
+
+struct Instrumenter {
+    imports: Vec<HostImport>,
+    /// Index of the counter global of each step class.
+    counters: Vec<u32>,
+    step_limit: Option<u64>,
+}
+
+impl Instrumenter {
+    /// Steps of one instruction, `is_last` is the end of the function body which returns.
+    fn steps_of(&self, instruction: &Instruction, is_last: bool) -> StepCounts {
+        let mut counts = StepCounts::default();
+
+        macro_rules! step {
+            ($mops: expr) => {{
+                counts.etable = 1;
+                counts.mtable = $mops;
+            }};
+        }
+
+        match instruction {
+            Instruction::Nop | Instruction::Block(_) | Instruction::Loop(_) => (),
+            Instruction::End => {
+                if is_last {
+                    step!(2)
+                }
+            }
+            Instruction::Unreachable => (),
+            Instruction::If(_) | Instruction::BrIf(_) | Instruction::BrTable(_) => step!(3),
+            Instruction::Else | Instruction::Br(_) | Instruction::Return => step!(2),
+
+            Instruction::Call(fid) => match self.imports.get(*fid as usize) {
+                Some(import) => {
+                    step!(import.params + import.has_return as u64);
+                    counts.external_host_calls = import.external as u64;
+                }
+                None => {
+                    step!(0);
+                    counts.jtable = 1;
+                }
+            },
+            Instruction::CallIndirect(..) => {
+                step!(1);
+                counts.jtable = 1;
+            }
+
+            Instruction::Drop => step!(0),
+            Instruction::Select => step!(4),
+
+            Instruction::GetLocal(_) | Instruction::SetLocal(_) | Instruction::TeeLocal(_) => {
+                step!(2)
+            }
+            // The value, the global and its initialization.
+            Instruction::GetGlobal(_) | Instruction::SetGlobal(_) => step!(3),
+
+            // The address, at most two heap words with their initializations and
+            // the loaded value.
+            Instruction::I32Load(..)
+            | Instruction::I64Load(..)
+            | Instruction::I32Load8S(..)
+            | Instruction::I32Load8U(..)
+            | Instruction::I32Load16S(..)
+            | Instruction::I32Load16U(..)
+            | Instruction::I64Load8S(..)
+            | Instruction::I64Load8U(..)
+            | Instruction::I64Load16S(..)
+            | Instruction::I64Load16U(..)
+            | Instruction::I64Load32S(..)
+            | Instruction::I64Load32U(..) => step!(6),
+            // The value, the address, reading, writing and initializing at most
+            // two heap words.
+            Instruction::I32Store(..)
+            | Instruction::I64Store(..)
+            | Instruction::I32Store8(..)
+            | Instruction::I32Store16(..)
+            | Instruction::I64Store8(..)
+            | Instruction::I64Store16(..)
+            | Instruction::I64Store32(..) => step!(8),
+
+            Instruction::CurrentMemory(_) => step!(1),
+            Instruction::GrowMemory(_) => step!(2),
+
+            Instruction::I32Const(_) | Instruction::I64Const(_) => step!(1),
+
+            Instruction::I32And
+            | Instruction::I32Or
+            | Instruction::I32Xor
+            | Instruction::I64And
+            | Instruction::I64Or
+            | Instruction::I64Xor => {
+                step!(3);
+                counts.bit_table = 1;
+            }
+            Instruction::I32Popcnt | Instruction::I64Popcnt => {
+                step!(2);
+                counts.bit_table = 1;
+            }
+
+            Instruction::I32Eqz
+            | Instruction::I64Eqz
+            | Instruction::I32Clz
+            | Instruction::I32Ctz
+            | Instruction::I64Clz
+            | Instruction::I64Ctz
+            | Instruction::I32WrapI64
+            | Instruction::I64ExtendSI32
+            | Instruction::I64ExtendUI32
+            | Instruction::SignExt(
+                SignExtInstruction::I32Extend8S
+                | SignExtInstruction::I32Extend16S
+                | SignExtInstruction::I64Extend8S
+                | SignExtInstruction::I64Extend16S
+                | SignExtInstruction::I64Extend32S,
+            ) => step!(2),
+
+            // Binary operations and comparisons.
+            _ => step!(3),
+        }
+
+        counts
+    }
+
+    fn counter(&self, class: StepClass) -> u32 {
+        self.counters[class as usize]
+    }
+
+    /// Prefix each straight-line segment of `code` by the increments of its steps,
+    /// the instructions of a `phantom` body are only charged to `StepClass::Phantom`.
+    fn instrument_body(&self, code: &Vec<Instruction>, phantom: bool) -> Vec<Instruction> {
+        let mut instrumented = vec![];
+        let mut segment = vec![];
+        let mut counts = StepCounts::default();
+
+        for (i, instruction) in code.iter().enumerate() {
+            let steps = self.steps_of(instruction, i == code.len() - 1);
+            for class in STEP_CLASSES {
+                counts.add(class, steps.get(class));
+            }
+            segment.push(instruction.clone());
+
+            let ends_segment = matches!(
+                instruction,
+                Instruction::Block(_)
+                    | Instruction::Loop(_)
+                    | Instruction::If(_)
+                    | Instruction::Else
+                    | Instruction::End
+                    | Instruction::Br(_)
+                    | Instruction::BrIf(_)
+                    | Instruction::BrTable(_)
+                    | Instruction::Return
+                    | Instruction::Unreachable
+            );
+
+            if ends_segment || i == code.len() - 1 {
+                let (charged, limited_class) = if phantom {
+                    let charged = StepCounts {
+                        phantom: segment.len() as u64,
+                        ..Default::default()
+                    };
+
+                    (charged, StepClass::Phantom)
+                } else {
+                    (counts.clone(), StepClass::EventTable)
+                };
+
+                for class in STEP_CLASSES {
+                    let steps = charged.get(class);
+
+                    if steps != 0 {
+                        instrumented.append(&mut vec![
+                            Instruction::GetGlobal(self.counter(class)),
+                            Instruction::I64Const(steps as i64),
+                            Instruction::I64Add,
+                            Instruction::SetGlobal(self.counter(class)),
+                        ]);
+                    }
+                }
+
+                if let Some(step_limit) = self.step_limit {
+                    if charged.get(limited_class) != 0 {
+                        instrumented.append(&mut vec![
+                            Instruction::GetGlobal(self.counter(limited_class)),
+                            Instruction::I64Const(step_limit as i64),
+                            Instruction::I64GtU,
+                            Instruction::If(BlockType::NoResult),
+                            Instruction::Unreachable,
+                            Instruction::End,
+                        ]);
+                    }
+                }
+
+                instrumented.append(&mut segment);
+                counts = StepCounts::default();
+            }
+        }
+
+        instrumented
+    }
+}
+
+fn push_global(module: &mut Module, entry: GlobalEntry) -> Result<()> {
+    match module.global_section_mut() {
+        Some(section) => section.entries_mut().push(entry),
+        None => module.insert_section(Section::Global(GlobalSection::with_entries(vec![entry])))?,
+    }
+
+    Ok(())
+}
+
+fn push_export(module: &mut Module, entry: ExportEntry) -> Result<()> {
+    match module.export_section_mut() {
+        Some(section) => section.entries_mut().push(entry),
+        None => module.insert_section(Section::Export(ExportSection::with_entries(vec![entry])))?,
+    }
+
+    Ok(())
+}
+
+/// Instrument the image with step counters exported by `StepClass::export_name`.
+///
+/// `external_host_functions` are the names of the host functions which are
+/// traced as external host calls, the bodies of `phantom_functions` (function
+/// indices) are counted by `StepClass::Phantom` as they are not traced. The
+/// image traps once the event table or the phantom counter exceeds
+/// `step_limit`, see `exceeds_step_limit`.
+pub fn instrument(
+    module: &Module,
+    external_host_functions: &HashSet<String>,
+    phantom_functions: &HashSet<u32>,
+    step_limit: Option<u64>,
+) -> Result<Module> {
+    let mut module = module.clone();
+
+    let imports = module.import_section().map_or(vec![], |section| {
+        section
+            .entries()
+            .iter()
+            .filter_map(|entry| match entry.external() {
+                External::Function(type_ref) => {
+                    let Type::Function(func_type) =
+                        &module.type_section().unwrap().types()[*type_ref as usize];
+
+                    Some(HostImport {
+                        external: external_host_functions.contains(entry.field()),
+                        params: func_type.params().len() as u64,
+                        has_return: !func_type.results().is_empty(),
+                    })
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let first_counter = module.import_count(ImportCountType::Global) as u32
+        + module
+            .global_section()
+            .map_or(0, |section| section.entries().len() as u32);
+
+    let mut counters = vec![];
+    for (i, class) in STEP_CLASSES.iter().enumerate() {
+        let counter = first_counter + i as u32;
+
+        push_global(
+            &mut module,
+            GlobalEntry::new(
+                GlobalType::new(ValueType::I64, true),
+                InitExpr::new(vec![Instruction::I64Const(0), Instruction::End]),
+            ),
+        )?;
+        push_export(
+            &mut module,
+            ExportEntry::new(class.export_name().to_owned(), Internal::Global(counter)),
+        )?;
+
+        counters.push(counter);
+    }
+
+    let instrumenter = Instrumenter {
+        imports,
+        counters,
+        step_limit,
+    };
+    let imported_functions = instrumenter.imports.len() as u32;
+
+    if let Some(section) = module.code_section_mut() {
+        for (i, body) in section.bodies_mut().iter_mut().enumerate() {
+            let phantom = phantom_functions.contains(&(imported_functions + i as u32));
+
+            let code = instrumenter.instrument_body(body.code().elements(), phantom);
+            *body.code_mut().elements_mut() = code;
+        }
+    }
+
+    Ok(module)
+}
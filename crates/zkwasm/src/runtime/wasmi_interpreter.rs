@@ -1,38 +1,176 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Display;
 use std::rc::Rc;
 
 use crate::circuits::config::zkwasm_k;
+use crate::loader::err::Error;
+use crate::loader::err::RuntimeErr;
+use crate::loader::phantom::match_phantom_functions;
+use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::memory_event_of_step;
+use crate::runtime::mtable_builder::spill_dir;
+use crate::runtime::mtable_builder::MTableBuilder;
+use crate::runtime::mtable_builder::MTABLE_CHUNK_ENTRIES;
+use crate::runtime::step_counter::instrument;
+use crate::runtime::step_counter::StepCounts;
+use anyhow::anyhow;
 use anyhow::Result;
+use parity_wasm::elements::Module;
 use specs::host_function::HostFunctionDesc;
 use specs::jtable::StaticFrameEntry;
-use specs::mtable::MTable;
 use specs::CompilationTable;
 use specs::ExecutionTable;
 use specs::Tables;
 use wasmi::Externals;
+use wasmi::HostError;
 use wasmi::ImportResolver;
+use wasmi::ImportsBuilder;
+use wasmi::ModuleImportResolver;
 use wasmi::ModuleInstance;
+use wasmi::RuntimeArgs;
 use wasmi::RuntimeValue;
+use wasmi::Trap;
 
 use super::CompiledImage;
+use super::DryRunResult;
 use super::ExecutionResult;
+use super::WasmRuntime;
+use super::WasmRuntimeIO;
+use super::WasmValue;
 
-pub struct WasmRuntimeIO {
-    pub public_inputs_and_outputs: Rc<RefCell<Vec<u64>>>,
-    pub outputs: Rc<RefCell<Vec<u64>>>,
+impl TryFrom<RuntimeValue> for WasmValue {
+    type Error = anyhow::Error;
+
+    fn try_from(value: RuntimeValue) -> Result<Self> {
+        match value {
+            RuntimeValue::I32(v) => Ok(WasmValue::I32(v)),
+            RuntimeValue::I64(v) => Ok(WasmValue::I64(v)),
+            _ => Err(anyhow!("unsupported value type: {:?}", value.value_type())),
+        }
+    }
+}
+
+impl From<WasmValue> for RuntimeValue {
+    fn from(value: WasmValue) -> Self {
+        match value {
+            WasmValue::I32(v) => RuntimeValue::I32(v),
+            WasmValue::I64(v) => RuntimeValue::I64(v),
+        }
+    }
+}
+
+fn to_wasm_value(value: Option<RuntimeValue>) -> Result<Option<WasmValue>> {
+    value.map(WasmValue::try_from).transpose()
+}
+
+impl ModuleImportResolver for HostEnv {
+    fn resolve_func(
+        &self,
+        function_name: &str,
+        signature: &wasmi::Signature,
+    ) -> Result<wasmi::FuncRef, wasmi::Error> {
+        self.external_env
+            .resolve_func(function_name, signature)
+            .or_else(|_| self.internal_env.resolve_func(function_name, signature))
+    }
+}
+
+impl Externals for HostEnv {
+    fn invoke_index(
+        &mut self,
+        index: usize,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        Ok(self.invoke_callback(index, args))
+    }
+}
+
+/// Invokes host functions and records them with their results for
+/// `HostCallReplayer`.
+struct HostCallRecorder<'a, E: Externals> {
+    externals: &'a mut E,
+    calls: Vec<(usize, Option<RuntimeValue>)>,
+}
+
+impl<E: Externals> Externals for HostCallRecorder<'_, E> {
+    fn invoke_index(
+        &mut self,
+        index: usize,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        let result = self.externals.invoke_index(index, args)?;
+        self.calls.push((index, result));
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug)]
+struct HostCallsDiverged;
+
+impl Display for HostCallsDiverged {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "host calls diverge from the recorded execution")
+    }
+}
+
+impl HostError for HostCallsDiverged {}
+
+/// Replays the host calls recorded by `HostCallRecorder`, so that the side
+/// effects of host functions happen once although the image is executed twice.
+/// A call other than the recorded one traps with `HostCallsDiverged`.
+struct HostCallReplayer {
+    calls: std::vec::IntoIter<(usize, Option<RuntimeValue>)>,
 }
 
-impl WasmRuntimeIO {
-    pub fn empty() -> Self {
-        Self {
-            public_inputs_and_outputs: Rc::new(RefCell::new(vec![])),
-            outputs: Rc::new(RefCell::new(vec![])),
+impl Externals for HostCallReplayer {
+    fn invoke_index(
+        &mut self,
+        index: usize,
+        _args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        match self.calls.next() {
+            Some((recorded_index, result)) if recorded_index == index => Ok(result),
+            _ => Err(Trap::from(HostCallsDiverged)),
         }
     }
 }
 
+impl HostCallReplayer {
+    /// Replay the recorded calls on `compiled`, the traced execution must make
+    /// exactly the recorded calls.
+    fn replay(
+        calls: Vec<(usize, Option<RuntimeValue>)>,
+        compiled: CompiledImage<wasmi::NotStartedModuleRef<'_>, wasmi::tracer::Tracer>,
+        wasm_io: WasmRuntimeIO,
+    ) -> Result<ExecutionResult<WasmValue>> {
+        let mut replayer = HostCallReplayer {
+            calls: calls.into_iter(),
+        };
+
+        let diverged = || anyhow!(Error::Runtime(RuntimeErr::HostCallsDiverged));
+
+        let result = compiled.run(&mut replayer, false, wasm_io).map_err(|e| {
+            match e
+                .downcast_ref::<wasmi::Error>()
+                .and_then(|e| e.as_host_error())
+                .and_then(|e| e.downcast_ref::<HostCallsDiverged>())
+            {
+                Some(_) => diverged(),
+                None => e,
+            }
+        })?;
+
+        if replayer.calls.next().is_some() {
+            return Err(diverged());
+        }
+
+        Ok(result)
+    }
+}
+
 pub trait Execution<R> {
     fn run<E: Externals>(
         self,
@@ -42,42 +180,45 @@ pub trait Execution<R> {
     ) -> Result<ExecutionResult<R>>;
 }
 
-impl Execution<RuntimeValue>
-    for CompiledImage<wasmi::NotStartedModuleRef<'_>, wasmi::tracer::Tracer>
-{
+impl Execution<WasmValue> for CompiledImage<wasmi::NotStartedModuleRef<'_>, wasmi::tracer::Tracer> {
     fn run<E: Externals>(
         self,
         externals: &mut E,
         dryrun: bool,
         wasm_io: WasmRuntimeIO,
-    ) -> Result<ExecutionResult<RuntimeValue>> {
+    ) -> Result<ExecutionResult<WasmValue>> {
         let instance = self
             .instance
             .run_start_tracer(externals, self.tracer.clone())
-            .unwrap();
+            .map_err(wasmi::Error::from)?;
 
         let result =
             instance.invoke_export_trace(&self.entry, &[], externals, self.tracer.clone())?;
 
+        let consumed_steps = self.tracer.borrow().etable.entries().len() as u64;
+
         let execution_tables = if !dryrun {
-            let tracer = self.tracer.borrow();
-
-            let mtable = {
-                let mentries = tracer
-                    .etable
-                    .entries()
-                    .iter()
-                    .map(|eentry| memory_event_of_step(eentry, &mut 1))
-                    .collect::<Vec<Vec<_>>>()
-                    .concat();
-
-                MTable::new(mentries, &self.tables.imtable)
+            let mut tracer = self.tracer.borrow_mut();
+
+            // The memory events are derived once here and shared by the consumers
+            // of the tables. Only their sort is spilled to disk, the etable of the
+            // tracer and the merged mtable stay in memory.
+            let (mtable, memory_events) = {
+                let mut builder = MTableBuilder::new(spill_dir(), MTABLE_CHUNK_ENTRIES);
+
+                for eentry in tracer.etable.entries() {
+                    builder.push(memory_event_of_step(eentry, &mut 1))?;
+                }
+
+                builder.finalize(&self.tables.imtable)?
             };
 
+            // The tables are moved out of the tracer rather than cloned.
             ExecutionTable {
-                etable: tracer.etable.clone(),
+                etable: std::mem::take(&mut tracer.etable),
                 mtable,
-                jtable: tracer.jtable.clone(),
+                jtable: std::mem::take(&mut tracer.jtable),
+                memory_events,
             }
         } else {
             ExecutionTable::default()
@@ -88,7 +229,8 @@ impl Execution<RuntimeValue>
                 compilation_tables: self.tables.clone(),
                 execution_tables,
             },
-            result,
+            result: to_wasm_value(result)?,
+            consumed_steps,
             public_inputs_and_outputs: wasm_io.public_inputs_and_outputs.borrow().clone(),
             outputs: wasm_io.outputs.borrow().clone(),
         })
@@ -102,7 +244,7 @@ impl WasmiRuntime {
         WasmiRuntime
     }
 
-    pub fn compile<'a, I: ImportResolver>(
+    pub fn compile_image<'a, I: ImportResolver>(
         module: &'a wasmi::Module,
         imports: &I,
         host_plugin_lookup: &HashMap<usize, HostFunctionDesc>,
@@ -175,4 +317,170 @@ impl WasmiRuntime {
             tracer,
         })
     }
+
+    /// Execute the image instrumented by `step_counter` without tracing, and
+    /// return the step counts, the result and the results of host calls.
+    fn run_instrumented(
+        module: &wasmi::Module,
+        env: &mut HostEnv,
+        entry: &str,
+        phantom_functions: &Vec<String>,
+        step_limit: Option<u64>,
+    ) -> Result<(
+        StepCounts,
+        Option<RuntimeValue>,
+        Vec<(usize, Option<RuntimeValue>)>,
+    )> {
+        let external_host_functions = env
+            .function_description_table()
+            .into_values()
+            .filter_map(|desc| match desc {
+                HostFunctionDesc::External { name, .. } => Some(name),
+                HostFunctionDesc::Internal { .. } => None,
+            })
+            .collect::<HashSet<_>>();
+        let phantom_functions = match_phantom_functions(module.module(), phantom_functions)?
+            .into_iter()
+            .flat_map(|matched| matched.functions.into_iter().map(|(fid, _)| fid))
+            .collect::<HashSet<_>>();
+
+        let module = wasmi::Module::from_parity_wasm_module(instrument(
+            module.module(),
+            &external_host_functions,
+            &phantom_functions,
+            step_limit,
+        )?)?;
+
+        let imports = ImportsBuilder::new().with_resolver("env", &*env);
+        let instance = ModuleInstance::new(&module, &imports, None)?;
+        let module_ref = instance.not_started_instance().clone();
+
+        let mut recorder = HostCallRecorder {
+            externals: env,
+            calls: vec![],
+        };
+
+        let result = instance
+            .run_start(&mut recorder)
+            .map_err(wasmi::Error::from)
+            .and_then(|instance| instance.invoke_export(entry, &[], &mut recorder));
+
+        let step_counts = StepCounts::read(|name| {
+            module_ref
+                .export_by_name(name)
+                .and_then(|export| export.as_global().cloned())
+                .and_then(|global| global.get().try_into::<i64>())
+                .map(|steps| steps as u64)
+        })
+        .unwrap();
+
+        match result {
+            Ok(result) => Ok((step_counts, result, recorder.calls)),
+            Err(_) if step_counts.exceeds_step_limit(step_limit) => {
+                Err(anyhow!(Error::Runtime(RuntimeErr::OutOfFuel {
+                    step_limit: step_limit.unwrap(),
+                })))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl WasmRuntime for WasmiRuntime {
+    type Module = wasmi::Module;
+
+    fn load(module: &Module) -> Result<wasmi::Module> {
+        Ok(wasmi::Module::from_parity_wasm_module(module.clone())?)
+    }
+
+    fn compile(
+        module: &wasmi::Module,
+        env: &HostEnv,
+        entry: &str,
+        dryrun: bool,
+        phantom_functions: &Vec<String>,
+    ) -> Result<CompilationTable> {
+        let imports = ImportsBuilder::new().with_resolver("env", env);
+
+        let compiled = Self::compile_image(
+            module,
+            &imports,
+            &env.function_description_table(),
+            entry,
+            dryrun,
+            phantom_functions,
+        )?;
+
+        Ok(compiled.tables)
+    }
+
+    fn run(
+        module: &wasmi::Module,
+        env: &mut HostEnv,
+        wasm_io: WasmRuntimeIO,
+        entry: &str,
+        dryrun: bool,
+        phantom_functions: &Vec<String>,
+    ) -> Result<ExecutionResult<WasmValue>> {
+        if dryrun {
+            let compilation_tables = Self::compile(module, env, entry, true, phantom_functions)?;
+            let (step_counts, result, _) =
+                Self::run_instrumented(module, env, entry, phantom_functions, wasm_io.step_limit)?;
+
+            return Ok(ExecutionResult {
+                tables: Tables {
+                    compilation_tables,
+                    execution_tables: ExecutionTable::default(),
+                },
+                result: to_wasm_value(result)?,
+                consumed_steps: step_counts.etable,
+                public_inputs_and_outputs: wasm_io.public_inputs_and_outputs.borrow().clone(),
+                outputs: wasm_io.outputs.borrow().clone(),
+            });
+        }
+
+        // The tracer cannot interrupt the execution, so the step limit is
+        // enforced by executing the instrumented image before tracing, then the
+        // traced execution replays its host calls rather than invoking them again.
+        let host_calls = match wasm_io.step_limit {
+            Some(_) => Some(
+                Self::run_instrumented(module, env, entry, phantom_functions, wasm_io.step_limit)?
+                    .2,
+            ),
+            None => None,
+        };
+
+        let imports = ImportsBuilder::new().with_resolver("env", &*env);
+
+        let compiled = Self::compile_image(
+            module,
+            &imports,
+            &env.function_description_table(),
+            entry,
+            false,
+            phantom_functions,
+        )?;
+
+        match host_calls {
+            Some(calls) => HostCallReplayer::replay(calls, compiled, wasm_io),
+            None => compiled.run(env, false, wasm_io),
+        }
+    }
+
+    fn dry_run(
+        module: &wasmi::Module,
+        env: &mut HostEnv,
+        wasm_io: WasmRuntimeIO,
+        entry: &str,
+        phantom_functions: &Vec<String>,
+    ) -> Result<DryRunResult> {
+        let (step_counts, _, _) =
+            Self::run_instrumented(module, env, entry, phantom_functions, wasm_io.step_limit)?;
+
+        Ok(DryRunResult {
+            step_counts,
+            public_inputs_and_outputs: wasm_io.public_inputs_and_outputs.borrow().clone(),
+            outputs: wasm_io.outputs.borrow().clone(),
+        })
+    }
 }
@@ -2,11 +2,13 @@ use crate::circuits::config::zkwasm_k;
 use crate::circuits::utils::table_entry::MemoryWritingTable;
 use crate::circuits::TestCircuit;
 use crate::profile::Profiler;
+use crate::runtime::auditor::audit_trace;
 use crate::runtime::host::host_env::HostEnv;
-use crate::runtime::wasmi_interpreter::Execution;
-use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
 use crate::runtime::ExecutionResult;
 use crate::runtime::WasmInterpreter;
+use crate::runtime::WasmRuntime;
+use crate::runtime::WasmRuntimeIO;
+use crate::runtime::WasmValue;
 
 use anyhow::Result;
 use halo2_proofs::arithmetic::FieldExt;
@@ -14,22 +16,33 @@ use halo2_proofs::dev::MockProver;
 use halo2_proofs::pairing::bn256::Fr;
 use wabt::wat2wasm_with_features;
 use wabt::Features;
-use wasmi::ImportsBuilder;
-use wasmi::RuntimeValue;
 
 #[cfg(test)]
 mod test_wasm_instructions;
 
 mod spec;
+mod test_auditor;
+mod test_capacity;
+mod test_circuit_info;
+mod test_context_file;
+#[cfg(feature = "continuation")]
+mod test_context_hash;
+mod test_fuzz;
+mod test_memory_limit;
+mod test_mtable_builder;
+mod test_opcode_decode;
+mod test_phantom;
 mod test_rlp;
+mod test_runtime;
+mod test_soundness;
 mod test_start;
+mod test_step_limit;
+mod test_transcript_hash;
 #[cfg(feature = "uniform-circuit")]
 mod test_uniform_verifier;
 
 /// Create circuit with trace and run mock test.
-fn test_circuit_mock<F: FieldExt>(
-    execution_result: ExecutionResult<wasmi::RuntimeValue>,
-) -> Result<()> {
+fn test_circuit_mock<F: FieldExt>(execution_result: ExecutionResult<WasmValue>) -> Result<()> {
     let instance = {
         let mut v: Vec<F> = vec![];
 
@@ -45,18 +58,19 @@ fn test_circuit_mock<F: FieldExt>(
     };
 
     execution_result.tables.write_json(None);
-    let memory_writing_table: MemoryWritingTable = execution_result
-        .tables
-        .execution_tables
-        .mtable
-        .clone()
-        .into();
+    let memory_writing_table: MemoryWritingTable =
+        (&execution_result.tables.execution_tables.mtable).into();
     memory_writing_table.write_json(None);
 
     execution_result.tables.profile_tables();
 
+    audit_trace(
+        &execution_result.tables,
+        &execution_result.public_inputs_and_outputs,
+    )?;
+
     let circuit = TestCircuit::new(execution_result.tables);
-    let prover = MockProver::run(zkwasm_k(), &circuit, vec![instance])?;
+    let prover = MockProver::run(zkwasm_k(), &circuit, circuit.instance_columns(&instance)?)?;
     assert_eq!(prover.verify(), Ok(()));
 
     Ok(())
@@ -68,22 +82,18 @@ fn compile_then_execute_wasm(
     wasm_runtime_io: WasmRuntimeIO,
     wasm: Vec<u8>,
     function_name: &str,
-) -> Result<ExecutionResult<RuntimeValue>> {
-    let module = wasmi::Module::from_buffer(&wasm).expect("failed to load wasm");
-
-    let imports = ImportsBuilder::new().with_resolver("env", &env);
+) -> Result<ExecutionResult<WasmValue>> {
+    let module = parity_wasm::deserialize_buffer(&wasm).expect("failed to load wasm");
+    let module = WasmInterpreter::load(&module)?;
 
-    let compiled_module = WasmInterpreter::compile(
+    let execution_result = WasmInterpreter::run(
         &module,
-        &imports,
-        &env.function_description_table(),
+        &mut env,
+        wasm_runtime_io,
         function_name,
         false,
         &vec![],
-    )
-    .unwrap();
-
-    let execution_result = compiled_module.run(&mut env, false, wasm_runtime_io)?;
+    )?;
 
     Ok(execution_result)
 }
@@ -94,7 +104,7 @@ pub fn test_circuit_with_env(
     wasm_runtime_io: WasmRuntimeIO,
     wasm: Vec<u8>,
     function_name: &str,
-) -> Result<ExecutionResult<RuntimeValue>> {
+) -> Result<ExecutionResult<WasmValue>> {
     let trace = compile_then_execute_wasm(env, wasm_runtime_io, wasm, function_name)?;
     test_circuit_mock::<Fr>(trace.clone())?;
 
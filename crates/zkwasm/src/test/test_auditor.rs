@@ -0,0 +1,146 @@
+mod tests {
+    use halo2_proofs::pairing::bn256::Bn256;
+    use specs::mtable::AccessType;
+    use specs::mtable::LocationType;
+    use specs::mtable::MTable;
+    use specs::Tables;
+
+    use crate::loader::err::Error;
+    use crate::loader::err::RuntimeErr;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::auditor::audit_trace;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    const K: u32 = 18;
+
+    fn trace() -> (Tables, Vec<u64>) {
+        let textual_repr = r#"
+            (module
+                (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+                (import "env" "wasm_output" (func $wasm_output (param i64)))
+
+                (func $double (param i64) (result i64)
+                  (i64.add (local.get 0) (local.get 0))
+                )
+
+                (func (export "zkmain")
+                  (local $n i64) (local $sum i64)
+                  (local.set $n (call $wasm_input (i32.const 1)))
+                  (block
+                    (loop
+                      (br_if 1 (i64.eqz (local.get $n)))
+                      (local.set $sum (i64.add (local.get $sum) (call $double (local.get $n))))
+                      (local.set $n (i64.sub (local.get $n) (i64.const 1)))
+                      (br 0)
+                    )
+                  )
+                  (call $wasm_output (local.get $sum))
+                )
+            )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(K, wasm, vec![])
+                .unwrap();
+
+        let execution_result = loader
+            .run(
+                ExecutionArg {
+                    public_inputs: vec![10],
+                    ..Default::default()
+                },
+                false,
+                false,
+            )
+            .unwrap();
+
+        (
+            execution_result.tables,
+            execution_result.public_inputs_and_outputs,
+        )
+    }
+
+    fn inconsistent_eid(result: anyhow::Result<()>) -> u32 {
+        match result.err().unwrap().downcast_ref::<Error>() {
+            Some(Error::Runtime(RuntimeErr::InconsistentTrace { eid, .. })) => *eid,
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_auditor_consistent_trace() {
+        let (tables, instances) = trace();
+
+        assert_eq!(instances, vec![10, 110]);
+        audit_trace(&tables, &instances).unwrap();
+    }
+
+    #[test]
+    fn test_auditor_tampered_sp() {
+        let (mut tables, instances) = trace();
+
+        let entries = tables.execution_tables.etable.entries_mut();
+        let eid = entries[2].eid;
+        entries[3].sp += 1;
+
+        assert_eq!(inconsistent_eid(audit_trace(&tables, &instances)), eid);
+    }
+
+    #[test]
+    fn test_auditor_tampered_memory() {
+        let (mut tables, instances) = trace();
+
+        let mut entries = tables.execution_tables.mtable.entries().clone();
+        let row = (0..entries.len() - 1)
+            .find(|row| {
+                entries[*row].ltype == LocationType::Stack
+                    && entries[*row].atype == AccessType::Write
+                    && entries[*row + 1].is_same_location(&entries[*row])
+                    && entries[*row + 1].atype == AccessType::Read
+            })
+            .unwrap();
+        entries[row].value += 1;
+        tables.execution_tables.mtable = MTable::from_sorted_entries(entries.clone());
+
+        // The write is only inconsistent with the step reading it.
+        assert_eq!(
+            inconsistent_eid(audit_trace(&tables, &instances)),
+            entries[row + 1].eid
+        );
+    }
+
+    #[test]
+    fn test_auditor_tampered_memory_events() {
+        let (mut tables, instances) = trace();
+
+        let etable = tables.execution_tables.etable.entries();
+        let memory_events = &mut tables.execution_tables.memory_events;
+        let mut rows = (0..etable.len())
+            .flat_map(|step| memory_events.mtable_rows_of_step(step).to_vec())
+            .collect::<Vec<_>>();
+        let step = (0..etable.len())
+            .find(|step| !memory_events.mtable_rows_of_step(*step).is_empty())
+            .unwrap();
+        let eid = etable[step].eid;
+
+        // The first event of the step is taken from the next step with events.
+        rows.swap(0, memory_events.mtable_rows_of_step(step).len());
+        memory_events.set_mtable_rows(rows);
+
+        assert_eq!(inconsistent_eid(audit_trace(&tables, &instances)), eid);
+    }
+
+    #[test]
+    fn test_auditor_tampered_instance() {
+        let (tables, _) = trace();
+
+        let eid = tables.execution_tables.etable.entries().last().unwrap().eid;
+
+        // The output is checked against the instance.
+        assert!(inconsistent_eid(audit_trace(&tables, &[10, 111])) < eid);
+        // All instances must be consumed.
+        assert_eq!(inconsistent_eid(audit_trace(&tables, &[10, 110, 0])), eid);
+    }
+}
@@ -0,0 +1,271 @@
+mod tests {
+    use halo2_proofs::pairing::bn256::Bn256;
+
+    use crate::circuits::capacity::CircuitTable;
+    use crate::circuits::config::MIN_K;
+    use crate::loader::err::Error;
+    use crate::loader::err::PreCheckErr;
+    use crate::loader::err::RuntimeErr;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    #[test]
+    fn test_estimate_minimal_k() {
+        let textual_repr = r#"
+        (module
+            (func (export "zkmain")
+              (local i32)
+              (local.set 0 (i32.const 100))
+              (loop
+                (local.set 0 (i32.sub (local.get 0) (i32.const 1)))
+                (br_if 0 (local.get 0))
+              )
+            )
+           )
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(MIN_K, wasm, vec![])
+                .unwrap();
+
+        let report = loader.estimate(ExecutionArg::default()).unwrap();
+
+        assert!(report.overflows().is_empty());
+        assert_eq!(report.minimal_k(), Some(MIN_K));
+
+        let etable = report
+            .usages
+            .iter()
+            .find(|usage| usage.table == CircuitTable::EventTable)
+            .unwrap();
+        assert!(etable.required_rows > 400);
+    }
+
+    #[test]
+    fn test_etable_overflow() {
+        let textual_repr = r#"
+        (module
+            (func (export "zkmain")
+              (local i32)
+              (local.set 0 (i32.const 20000))
+              (loop
+                (local.set 0 (i32.sub (local.get 0) (i32.const 1)))
+                (br_if 0 (local.get 0))
+              )
+            )
+           )
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(MIN_K, wasm, vec![])
+                .unwrap();
+
+        let err = loader
+            .circuit_with_witness(ExecutionArg::default())
+            .err()
+            .unwrap();
+
+        match err.downcast_ref::<Error>() {
+            Some(Error::Runtime(RuntimeErr::TableOverflow {
+                table, minimal_k, ..
+            })) => {
+                assert_eq!(*table, CircuitTable::EventTable);
+                assert!(minimal_k.unwrap() > MIN_K);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_image_table_overflow() {
+        let textual_repr = format!(
+            r#"
+            (module
+                (func (export "zkmain")
+                  {}
+                )
+            )
+            "#,
+            "(drop (i32.const 0))\n".repeat(5000)
+        );
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let err = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(
+            MIN_K,
+            wasm.clone(),
+            vec![],
+        )
+        .err()
+        .unwrap();
+
+        match err.downcast_ref::<Error>() {
+            Some(Error::PreCheck(PreCheckErr::ImageTableOverflow { minimal_k, .. })) => {
+                assert_eq!(*minimal_k, Some(MIN_K + 1));
+            }
+            _ => unreachable!(),
+        }
+
+        // The image table grows with K.
+        assert!(
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(
+                MIN_K + 1,
+                wasm,
+                vec![]
+            )
+            .is_ok()
+        );
+    }
+
+    /// Both the step counts and the estimation of the dry run bound the trace.
+    fn assert_dry_run_bounds_trace(textual_repr: &str, public_inputs: Vec<u64>) {
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(MIN_K, wasm, vec![])
+                .unwrap();
+
+        let arg = || ExecutionArg {
+            public_inputs: public_inputs.clone(),
+            ..Default::default()
+        };
+
+        let dry_run = loader.dry_run(arg()).unwrap();
+        let traced = loader.run(arg(), false, false).unwrap();
+
+        assert_eq!(
+            dry_run.public_inputs_and_outputs,
+            traced.public_inputs_and_outputs
+        );
+        assert!(
+            dry_run.step_counts.etable
+                >= traced.tables.execution_tables.etable.entries().len() as u64
+        );
+        assert!(
+            dry_run.step_counts.mtable
+                >= traced.tables.execution_tables.mtable.entries().len() as u64
+        );
+
+        let estimated = loader.estimate_by_dry_run(arg()).unwrap();
+        let exact = loader.estimate(arg()).unwrap();
+
+        for usage in estimated.usages.iter() {
+            let exact = exact
+                .usages
+                .iter()
+                .find(|exact| exact.table == usage.table)
+                .unwrap();
+
+            assert!(usage.required_rows >= exact.required_rows);
+        }
+    }
+
+    #[test]
+    fn test_dry_run_bounds_trace() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (import "env" "wasm_output" (func $wasm_output (param i64)))
+            (memory 1)
+
+            (func $fib (param i64) (result i64)
+              (if (result i64) (i64.lt_u (local.get 0) (i64.const 2))
+                (then (local.get 0))
+                (else
+                  (i64.add
+                    (call $fib (i64.sub (local.get 0) (i64.const 1)))
+                    (call $fib (i64.sub (local.get 0) (i64.const 2)))
+                  )
+                )
+              )
+            )
+
+            (func (export "zkmain")
+              (local i64)
+              (local.set 0 (call $fib (call $wasm_input (i32.const 1))))
+              (i64.store (i32.const 8) (i64.xor (local.get 0) (i64.const 1)))
+              (call $wasm_output (i64.load (i32.const 8)))
+            )
+           )
+        "#;
+
+        assert_dry_run_bounds_trace(textual_repr, vec![10]);
+
+        // Each global and heap word is accessed once, so the memory table is
+        // dominated by the `Init` entries of the locations.
+        let globals = 256;
+        let textual_repr = format!(
+            r#"
+            (module
+                (memory 1)
+                {}
+                (func (export "zkmain")
+                  (local i32)
+                  {}
+                  (loop
+                    (i64.store (local.get 0) (i64.load offset=4096 (local.get 0)))
+                    (local.set 0 (i32.add (local.get 0) (i32.const 8)))
+                    (br_if 0 (i32.lt_u (local.get 0) (i32.const 4096)))
+                  )
+                )
+            )
+            "#,
+            (0..globals)
+                .map(|i| format!("(global (mut i64) (i64.const {}))", i))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            (0..globals)
+                .map(|i| format!(
+                    "(global.set {} (i64.add (global.get {}) (i64.const 1)))",
+                    i, i
+                ))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+
+        assert_dry_run_bounds_trace(&textual_repr, vec![]);
+    }
+
+    /// The poseidon hash of the uniform circuit takes several rows per init
+    /// memory entry, so a large data segment overflows it first.
+    #[cfg(feature = "uniform-circuit")]
+    #[test]
+    fn test_poseidon_hash_overflow() {
+        let textual_repr = format!(
+            r#"
+            (module
+                (memory 5)
+                (data (i32.const 0) "{}")
+                (func (export "zkmain"))
+            )
+            "#,
+            "\\01".repeat(5 * 65536)
+        );
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(MIN_K, wasm, vec![])
+                .unwrap();
+
+        let err = loader
+            .circuit_with_witness(ExecutionArg::default())
+            .err()
+            .unwrap();
+
+        match err.downcast_ref::<Error>() {
+            Some(Error::Runtime(RuntimeErr::TableOverflow {
+                table, minimal_k, ..
+            })) => {
+                assert_eq!(*table, CircuitTable::PoseidonHash);
+                assert!(minimal_k.unwrap() > MIN_K);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,43 @@
+mod tests {
+    use halo2_proofs::pairing::bn256::Fr;
+
+    use crate::circuits::capacity::CircuitTable;
+    use crate::circuits::info::CircuitInfo;
+    use crate::circuits::info::TableColumns;
+    use crate::circuits::test_circuit::VAR_COLUMNS;
+
+    const K: u32 = 18;
+
+    #[test]
+    fn test_circuit_info_accounts_for_constraint_system() {
+        let info = CircuitInfo::new::<Fr>(K);
+
+        let sum = |f: fn(&TableColumns) -> usize| info.tables.iter().map(f).sum::<usize>();
+        assert_eq!(sum(|table| table.advice), info.advice_columns);
+        assert_eq!(sum(|table| table.var_advice), VAR_COLUMNS);
+        assert_eq!(sum(|table| table.fixed), info.fixed_columns);
+        assert_eq!(sum(|table| table.instance), info.instance_columns);
+        assert_eq!(sum(|table| table.lookups), info.lookups);
+        assert_eq!(sum(|table| table.gates), info.gates.len());
+
+        assert!(info.gates.iter().all(|gate| gate.degree <= info.degree));
+
+        for cells in info
+            .opcode_cells
+            .iter()
+            .map(|opcode| &opcode.cells)
+            .chain([&info.etable_common_cells, &info.mtable_cells])
+        {
+            assert!(cells.iter().all(|usage| usage.allocated <= usage.available));
+        }
+
+        assert_eq!(
+            info.capacities
+                .iter()
+                .find(|(table, _)| *table == CircuitTable::EventTable)
+                .unwrap()
+                .1,
+            info.max_available_rows
+        );
+    }
+}
@@ -0,0 +1,73 @@
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use halo2_proofs::pairing::bn256::Bn256;
+
+    use crate::foreign::context::file::ContextFile;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    /// Run the image with `context_inputs` and return its context outputs.
+    fn run(wasm: &Vec<u8>, context_inputs: Vec<u64>) -> Vec<u64> {
+        let loader = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(
+            18,
+            wasm.clone(),
+            vec![],
+        )
+        .unwrap();
+
+        let context_outputs = Arc::new(Mutex::new(vec![]));
+        loader
+            .run(
+                ExecutionArg {
+                    context_inputs,
+                    context_outputs: context_outputs.clone(),
+                    ..Default::default()
+                },
+                true,
+                false,
+            )
+            .unwrap();
+
+        let outputs = context_outputs.lock().unwrap().clone();
+        outputs
+    }
+
+    #[test]
+    fn test_context_file_chain() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_read_context" (func $wasm_read_context (result i64)))
+            (import "env" "wasm_write_context" (func $wasm_write_context (param i64)))
+
+            (func (export "zkmain")
+              (call $wasm_write_context (i64.add (call $wasm_read_context) (i64.const 1)))
+            )
+           )
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+        let md5 = "0123456789ABCDEF0123456789ABCDEF".to_owned();
+        let path = std::env::temp_dir().join(format!(
+            "test_context_file_chain_{}.json",
+            std::process::id()
+        ));
+
+        let outputs = run(&wasm, vec![0]);
+        ContextFile::new(Some(md5.clone()), outputs)
+            .write(&path)
+            .unwrap();
+
+        let inputs = ContextFile::read(&path).unwrap().values_for(&md5).unwrap();
+        assert_eq!(run(&wasm, inputs), vec![2]);
+
+        assert!(ContextFile::read(&path)
+            .unwrap()
+            .values_for("FEDCBA9876543210FEDCBA9876543210")
+            .is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
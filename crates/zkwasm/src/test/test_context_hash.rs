@@ -0,0 +1,130 @@
+mod tests {
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::pairing::bn256::Bn256;
+    use halo2_proofs::pairing::bn256::Fr;
+
+    use crate::circuits::capacity::CircuitTable;
+    use crate::circuits::tamper::with_mutations;
+    use crate::circuits::tamper::Mutation;
+    use crate::circuits::PublicInputs;
+    use crate::foreign::context::ContextHashes;
+    use crate::foreign::context::CONTEXT_HASH_CAPACITY;
+    use crate::loader::err::Error;
+    use crate::loader::err::RuntimeErr;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    const K: u32 = 18;
+
+    #[test]
+    fn test_context_hash_instances() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_read_context" (func $wasm_read_context (result i64)))
+            (import "env" "wasm_write_context" (func $wasm_write_context (param i64)))
+
+            (func (export "zkmain")
+              (call $wasm_write_context (i64.add (call $wasm_read_context) (i64.const 1)))
+              (call $wasm_write_context (i64.add (call $wasm_read_context) (i64.const 1)))
+            )
+           )
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(K, wasm, vec![])
+                .unwrap();
+
+        let (circuit, instances, _) = loader
+            .circuit_with_witness(ExecutionArg {
+                context_inputs: vec![1, 2],
+                ..Default::default()
+            })
+            .unwrap();
+
+        let public_inputs = circuit.public_inputs(&instances).unwrap();
+        assert_eq!(
+            public_inputs.context_hashes,
+            ContextHashes::<Fr>::new(&vec![1, 2], &vec![2, 3]).unwrap()
+        );
+
+        let columns = loader.instance_columns(&public_inputs).unwrap();
+        assert_eq!(columns, circuit.instance_columns(&instances).unwrap());
+
+        let prover = MockProver::run(K, &circuit, columns.clone()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The context hashes must match the context consumed and produced.
+        for i in 0..2 {
+            let mut tampered = columns.clone();
+            tampered.last_mut().unwrap()[i] += Fr::from(1u64);
+            let prover = MockProver::run(K, &circuit, tampered).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_context_hash_rejects_forged_trailing_outputs() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_write_context" (func $wasm_write_context (param i64)))
+
+            (func (export "zkmain")
+              (call $wasm_write_context (i64.const 2))
+              (call $wasm_write_context (i64.const 3))
+            )
+           )
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(K, wasm, vec![])
+                .unwrap();
+
+        let (circuit, instances, _) = loader
+            .circuit_with_witness(ExecutionArg::default())
+            .unwrap();
+
+        let prover =
+            MockProver::run(K, &circuit, circuit.instance_columns(&instances).unwrap()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The padding zeros are not taken for context values.
+        assert_ne!(
+            ContextHashes::<Fr>::new(&vec![], &vec![2, 3]).unwrap(),
+            ContextHashes::<Fr>::new(&vec![], &vec![2, 3, 0]).unwrap()
+        );
+
+        // Outputs not written by the execution are rejected, even if the
+        // context output hash is the one of the forged outputs.
+        for outputs in [vec![2, 3, 5], vec![2, 3, 0]] {
+            let public_inputs = PublicInputs {
+                context_hashes: ContextHashes::<Fr>::new(&vec![], &outputs).unwrap(),
+                ..circuit.public_inputs(&instances).unwrap()
+            };
+            let columns = loader.instance_columns(&public_inputs).unwrap();
+
+            let prover = with_mutations(vec![Mutation::ContextOutputs { outputs }], || {
+                MockProver::run(K, &circuit, columns).unwrap()
+            });
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_context_hash_overflow() {
+        let outputs = vec![0; CONTEXT_HASH_CAPACITY + 1];
+
+        assert!(matches!(
+            ContextHashes::<Fr>::new(&vec![], &outputs)
+                .unwrap_err()
+                .downcast_ref::<Error>(),
+            Some(Error::Runtime(RuntimeErr::TableOverflow {
+                table: CircuitTable::ContextOutputHelperTable,
+                minimal_k: None,
+                ..
+            }))
+        ));
+    }
+}
@@ -0,0 +1,595 @@
+mod tests {
+    use std::panic;
+    use std::panic::AssertUnwindSafe;
+
+    use halo2_proofs::pairing::bn256::Bn256;
+    use rand::rngs::StdRng;
+    use rand::Rng;
+    use rand::SeedableRng;
+    use wasmi_upstream::Externals;
+    use wasmi_upstream::FuncInstance;
+    use wasmi_upstream::FuncRef;
+    use wasmi_upstream::ImportsBuilder;
+    use wasmi_upstream::ModuleImportResolver;
+    use wasmi_upstream::ModuleInstance;
+    use wasmi_upstream::RuntimeArgs;
+    use wasmi_upstream::RuntimeValue;
+    use wasmi_upstream::Signature;
+    use wasmi_upstream::Trap;
+    use wasmi_upstream::ValueType;
+
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    const K: u32 = 18;
+    const MAX_DEPTH: u32 = 4;
+    const DATA_SIZE: usize = 64;
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Ty {
+        I32,
+        I64,
+    }
+
+    impl Ty {
+        fn name(self) -> &'static str {
+            match self {
+                Ty::I32 => "i32",
+                Ty::I64 => "i64",
+            }
+        }
+
+        fn bits(self) -> u32 {
+            match self {
+                Ty::I32 => 32,
+                Ty::I64 => 64,
+            }
+        }
+
+        fn mask(self, value: u64) -> u64 {
+            match self {
+                Ty::I32 => value as u32 as u64,
+                Ty::I64 => value,
+            }
+        }
+
+        fn signed(self, value: u64) -> i64 {
+            match self {
+                Ty::I32 => value as u32 as i32 as i64,
+                Ty::I64 => value as i64,
+            }
+        }
+    }
+
+    fn eval_binary(ty: Ty, op: &str, a: u64, b: u64) -> u64 {
+        let shift = (b % ty.bits() as u64) as u32;
+
+        let value = match (ty, op) {
+            (_, "add") => a.wrapping_add(b),
+            (_, "sub") => a.wrapping_sub(b),
+            (_, "mul") => a.wrapping_mul(b),
+            (_, "and") => a & b,
+            (_, "or") => a | b,
+            (_, "xor") => a ^ b,
+            (_, "shl") => a << shift,
+            (_, "shr_u") => a >> shift,
+            (_, "shr_s") => (ty.signed(a) >> shift) as u64,
+            (Ty::I32, "rotl") => (a as u32).rotate_left(shift) as u64,
+            (Ty::I32, "rotr") => (a as u32).rotate_right(shift) as u64,
+            (Ty::I64, "rotl") => a.rotate_left(shift),
+            (Ty::I64, "rotr") => a.rotate_right(shift),
+            // The divisors are positive, see `Generator::divisor`.
+            (_, "div_u") => a / b,
+            (_, "rem_u") => a % b,
+            (_, "div_s") => (ty.signed(a) / ty.signed(b)) as u64,
+            (_, "rem_s") => (ty.signed(a) % ty.signed(b)) as u64,
+            _ => unreachable!(),
+        };
+
+        ty.mask(value)
+    }
+
+    fn eval_compare(ty: Ty, op: &str, a: u64, b: u64) -> u64 {
+        let (sa, sb) = (ty.signed(a), ty.signed(b));
+
+        (match op {
+            "eq" => a == b,
+            "ne" => a != b,
+            "lt_s" => sa < sb,
+            "lt_u" => a < b,
+            "gt_s" => sa > sb,
+            "gt_u" => a > b,
+            "le_s" => sa <= sb,
+            "le_u" => a <= b,
+            "ge_s" => sa >= sb,
+            "ge_u" => a >= b,
+            _ => unreachable!(),
+        }) as u64
+    }
+
+    fn eval_unary(ty: Ty, op: &str, a: u64) -> u64 {
+        let bits = ty.bits();
+
+        match op {
+            "clz" => (ty.mask(a).leading_zeros() - (64 - bits)) as u64,
+            "ctz" => (ty.mask(a).trailing_zeros().min(bits)) as u64,
+            "popcnt" => ty.mask(a).count_ones() as u64,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Extend an i32 to `ty`.
+    fn extend(ty: Ty, expr: String, value: u64) -> (String, u64) {
+        match ty {
+            Ty::I32 => (expr, value),
+            Ty::I64 => (format!("(i64.extend_i32_u {})", expr), value),
+        }
+    }
+
+    /// Generates expressions with their values, the value of an i32 is kept
+    /// in the low 32 bits.
+    struct Generator {
+        rng: StdRng,
+        memory: Vec<u8>,
+    }
+
+    impl Generator {
+        fn constant(&mut self, ty: Ty) -> (String, u64) {
+            let value = match self.rng.gen_range(0..6) {
+                0 => 0,
+                1 => 1,
+                2 => u64::MAX,
+                3 => 1 << (ty.bits() - 1),
+                4 => self.rng.gen_range(0..256),
+                _ => self.rng.gen(),
+            };
+            let value = ty.mask(value);
+
+            (format!("({}.const {})", ty.name(), ty.signed(value)), value)
+        }
+
+        /// A positive divisor, so that no division traps.
+        fn divisor(&mut self, ty: Ty, depth: u32) -> (String, u64) {
+            let (expr, value) = self.expr(ty, depth);
+            let maximal_positive = (1u64 << (ty.bits() - 1)) - 1;
+
+            (
+                format!(
+                    "({ty}.or ({ty}.and {} ({ty}.const {})) ({ty}.const 1))",
+                    expr,
+                    maximal_positive,
+                    ty = ty.name()
+                ),
+                (value & maximal_positive) | 1,
+            )
+        }
+
+        fn load(&mut self, ty: Ty) -> (String, u64) {
+            let (suffix, size, signed) = match (ty, self.rng.gen_range(0..7)) {
+                (_, 0) => ("8_u", 1, false),
+                (_, 1) => ("8_s", 1, true),
+                (_, 2) => ("16_u", 2, false),
+                (_, 3) => ("16_s", 2, true),
+                (Ty::I64, 4) => ("32_u", 4, false),
+                (Ty::I64, 5) => ("32_s", 4, true),
+                (Ty::I32, _) => ("", 4, false),
+                (Ty::I64, _) => ("", 8, false),
+            };
+
+            let address = self.rng.gen_range(0..=DATA_SIZE - size);
+            let offset = self.rng.gen_range(0..=address);
+
+            let mut bytes = [0u8; 8];
+            bytes[..size].copy_from_slice(&self.memory[address..address + size]);
+            let mut value = u64::from_le_bytes(bytes);
+            if signed {
+                let shift = 64 - 8 * size as u32;
+                value = (((value << shift) as i64) >> shift) as u64;
+            }
+
+            (
+                format!(
+                    "({}.load{} offset={} (i32.const {}))",
+                    ty.name(),
+                    suffix,
+                    offset,
+                    address - offset
+                ),
+                ty.mask(value),
+            )
+        }
+
+        fn store_then_load(&mut self, ty: Ty, depth: u32) -> (String, u64) {
+            let (suffix, size) = match (ty, self.rng.gen_range(0..4)) {
+                (_, 0) => ("8", 1),
+                (_, 1) => ("16", 2),
+                (Ty::I64, 2) => ("32", 4),
+                (Ty::I32, _) => ("", 4),
+                (Ty::I64, _) => ("", 8),
+            };
+            let address = self.rng.gen_range(0..=DATA_SIZE - size);
+
+            let (value_expr, value) = self.expr(ty, depth);
+            self.memory[address..address + size].copy_from_slice(&value.to_le_bytes()[..size]);
+
+            let (load_expr, loaded) = self.load(ty);
+
+            (
+                format!(
+                    "(block (result {}) ({}.store{} (i32.const {}) {}) {})",
+                    ty.name(),
+                    ty.name(),
+                    suffix,
+                    address,
+                    value_expr,
+                    load_expr
+                ),
+                loaded,
+            )
+        }
+
+        /// Generate both arms, keeping the effects of the taken one only.
+        fn arms(&mut self, ty: Ty, depth: u32, take_first: bool) -> (String, String, u64) {
+            let memory = self.memory.clone();
+
+            let (first, first_value) = self.expr(ty, depth);
+            let memory_after_first = std::mem::replace(&mut self.memory, memory);
+            let (second, second_value) = self.expr(ty, depth);
+
+            if take_first {
+                self.memory = memory_after_first;
+                (first, second, first_value)
+            } else {
+                (first, second, second_value)
+            }
+        }
+
+        fn expr(&mut self, ty: Ty, depth: u32) -> (String, u64) {
+            if depth == 0 {
+                return match self.rng.gen_range(0..4) {
+                    0 => self.load(ty),
+                    _ => self.constant(ty),
+                };
+            }
+
+            let depth = depth - 1;
+            let t = ty.name();
+
+            match self.rng.gen_range(0..16) {
+                0..=4 => {
+                    const OPS: [&str; 15] = [
+                        "add", "sub", "mul", "and", "or", "xor", "shl", "shr_u", "shr_s", "rotl",
+                        "rotr", "div_u", "rem_u", "div_s", "rem_s",
+                    ];
+                    let op = OPS[self.rng.gen_range(0..OPS.len())];
+
+                    let (a, av) = self.expr(ty, depth);
+                    let (b, bv) = if op.starts_with("div") || op.starts_with("rem") {
+                        self.divisor(ty, depth)
+                    } else {
+                        self.expr(ty, depth)
+                    };
+
+                    (
+                        format!("({}.{} {} {})", t, op, a, b),
+                        eval_binary(ty, op, av, bv),
+                    )
+                }
+                5 => {
+                    const OPS: [&str; 3] = ["clz", "ctz", "popcnt"];
+                    let op = OPS[self.rng.gen_range(0..OPS.len())];
+
+                    let (a, av) = self.expr(ty, depth);
+
+                    (
+                        format!("({}.{} {})", t, op, a),
+                        ty.mask(eval_unary(ty, op, av)),
+                    )
+                }
+                6 => {
+                    const OPS: [&str; 10] = [
+                        "eq", "ne", "lt_s", "lt_u", "gt_s", "gt_u", "le_s", "le_u", "ge_s", "ge_u",
+                    ];
+                    let op = OPS[self.rng.gen_range(0..OPS.len())];
+                    let operand_ty = if self.rng.gen() { Ty::I32 } else { Ty::I64 };
+
+                    let (a, av) = self.expr(operand_ty, depth);
+                    let (b, bv) = self.expr(operand_ty, depth);
+                    let (expr, value) = (
+                        format!("({}.{} {} {})", operand_ty.name(), op, a, b),
+                        eval_compare(operand_ty, op, av, bv),
+                    );
+
+                    extend(ty, expr, value)
+                }
+                7 => {
+                    let operand_ty = if self.rng.gen() { Ty::I32 } else { Ty::I64 };
+
+                    let (a, av) = self.expr(operand_ty, depth);
+                    let (expr, value) = (
+                        format!("({}.eqz {})", operand_ty.name(), a),
+                        (av == 0) as u64,
+                    );
+
+                    extend(ty, expr, value)
+                }
+                8 => match ty {
+                    Ty::I32 => {
+                        let (a, av) = self.expr(Ty::I64, depth);
+
+                        (format!("(i32.wrap_i64 {})", a), Ty::I32.mask(av))
+                    }
+                    Ty::I64 => {
+                        let (a, av) = self.expr(Ty::I32, depth);
+
+                        if self.rng.gen() {
+                            (
+                                format!("(i64.extend_i32_s {})", a),
+                                Ty::I32.signed(av) as u64,
+                            )
+                        } else {
+                            (format!("(i64.extend_i32_u {})", a), av)
+                        }
+                    }
+                },
+                9 => {
+                    let (a, av) = self.expr(ty, depth);
+                    let (b, bv) = self.expr(ty, depth);
+                    let (c, cv) = self.expr(Ty::I32, depth);
+
+                    (
+                        format!("(select {} {} {})", a, b, c),
+                        if cv != 0 { av } else { bv },
+                    )
+                }
+                10 => {
+                    let (c, cv) = self.expr(Ty::I32, depth);
+                    let (a, b, value) = self.arms(ty, depth, cv != 0);
+
+                    (
+                        format!("(if (result {}) {} (then {}) (else {}))", t, c, a, b),
+                        value,
+                    )
+                }
+                11 => {
+                    // The first operand is kept by the taken branch and dropped otherwise.
+                    let (a, av) = self.expr(ty, depth);
+                    let (c, cv) = self.expr(Ty::I32, depth);
+                    let memory = self.memory.clone();
+                    let (b, bv) = self.expr(ty, depth);
+
+                    let value = if cv != 0 {
+                        self.memory = memory;
+                        av
+                    } else {
+                        bv
+                    };
+
+                    (
+                        format!("(block (result {}) (drop (br_if 0 {} {})) {})", t, a, c, b),
+                        value,
+                    )
+                }
+                12 => {
+                    let (a, av) = self.expr(ty, depth);
+
+                    match self.rng.gen_range(0..3) {
+                        0 => (format!("(call $id_{} {})", t, a), av),
+                        1 => (
+                            format!(
+                                "(call_indirect (type $t_{}) {} (i32.const {}))",
+                                t,
+                                a,
+                                (ty == Ty::I64) as u32
+                            ),
+                            av,
+                        ),
+                        _ => (format!("(block (result {}) (br 0 {}))", t, a), av),
+                    }
+                }
+                13 => {
+                    let (a, av) = self.expr(ty, depth);
+
+                    if self.rng.gen() {
+                        (format!("(local.tee $l_{} {})", t, a), av)
+                    } else {
+                        (
+                            format!(
+                                "(block (result {}) (global.set $g_{} {}) (global.get $g_{}))",
+                                t, t, a, t
+                            ),
+                            av,
+                        )
+                    }
+                }
+                14 => self.store_then_load(ty, depth),
+                _ => self.constant(ty),
+            }
+        }
+
+        /// A module with its expected outputs.
+        fn module(&mut self) -> (String, Vec<u64>) {
+            let data = self.memory.clone();
+
+            let mut body = String::new();
+            let mut outputs = vec![];
+            for _ in 0..self.rng.gen_range(1..=6) {
+                let ty = if self.rng.gen() { Ty::I32 } else { Ty::I64 };
+                let (expr, value) = self.expr(ty, MAX_DEPTH);
+                let (expr, value) = extend(ty, expr, value);
+
+                body.push_str(&format!("(call $wasm_output {})\n", expr));
+                outputs.push(value);
+            }
+
+            let data = data
+                .iter()
+                .map(|byte| format!("\\{:02x}", byte))
+                .collect::<String>();
+
+            let module = format!(
+                r#"
+                (module
+                    (import "env" "wasm_output" (func $wasm_output (param i64)))
+                    (type $t_i32 (func (param i32) (result i32)))
+                    (type $t_i64 (func (param i64) (result i64)))
+                    (memory $0 1)
+                    (data (i32.const 0) "{}")
+                    (global $g_i32 (mut i32) (i32.const 0))
+                    (global $g_i64 (mut i64) (i64.const 0))
+                    (table 2 funcref)
+                    (elem (i32.const 0) $id_i32 $id_i64)
+                    (func $id_i32 (type $t_i32) (local.get 0))
+                    (func $id_i64 (type $t_i64) (local.get 0))
+                    (func (export "zkmain")
+                      (local $l_i32 i32) (local $l_i64 i64)
+                      {}
+                    )
+                )
+                "#,
+                data, body
+            );
+
+            (module, outputs)
+        }
+    }
+
+    fn env_u64(name: &str, default: u64) -> u64 {
+        std::env::var(name).map_or(default, |value| value.parse().unwrap())
+    }
+
+    const WASM_INPUT_INDEX: usize = 0;
+    const WASM_OUTPUT_INDEX: usize = 1;
+
+    /// `wasm_input` and `wasm_output` for upstream wasmi, independent of the
+    /// host env of the traced execution.
+    #[derive(Default)]
+    struct StockEnv {
+        public_inputs: Vec<u64>,
+        private_inputs: Vec<u64>,
+        outputs: Vec<u64>,
+    }
+
+    impl ModuleImportResolver for StockEnv {
+        fn resolve_func(
+            &self,
+            field_name: &str,
+            signature: &Signature,
+        ) -> Result<FuncRef, wasmi_upstream::Error> {
+            let (expected, index) = match field_name {
+                "wasm_input" => (
+                    Signature::new(&[ValueType::I32][..], Some(ValueType::I64)),
+                    WASM_INPUT_INDEX,
+                ),
+                "wasm_output" => (
+                    Signature::new(&[ValueType::I64][..], None),
+                    WASM_OUTPUT_INDEX,
+                ),
+                _ => {
+                    return Err(wasmi_upstream::Error::Instantiation(format!(
+                        "Export {} not found",
+                        field_name
+                    )))
+                }
+            };
+
+            if signature != &expected {
+                return Err(wasmi_upstream::Error::Instantiation(format!(
+                    "Export {} has a bad signature {:?}",
+                    field_name, signature
+                )));
+            }
+
+            Ok(FuncInstance::alloc_host(expected, index))
+        }
+    }
+
+    impl Externals for StockEnv {
+        fn invoke_index(
+            &mut self,
+            index: usize,
+            args: RuntimeArgs,
+        ) -> Result<Option<RuntimeValue>, Trap> {
+            match index {
+                WASM_INPUT_INDEX => {
+                    let is_public = args.nth_checked::<i32>(0)? == 1;
+                    let inputs = if is_public {
+                        &mut self.public_inputs
+                    } else {
+                        &mut self.private_inputs
+                    };
+                    assert!(!inputs.is_empty(), "no more inputs");
+
+                    Ok(Some(RuntimeValue::I64(inputs.remove(0) as i64)))
+                }
+                WASM_OUTPUT_INDEX => {
+                    self.outputs.push(args.nth_checked::<u64>(0)?);
+
+                    Ok(None)
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    /// The outputs of the module run by upstream wasmi, which shares neither
+    /// the tracer nor the instrumentation of the traced execution.
+    fn stock_wasmi_outputs(wasm: &[u8]) -> Vec<u64> {
+        let module = wasmi_upstream::Module::from_buffer(wasm).expect("failed to load wasm");
+
+        let mut env = StockEnv::default();
+        let instance = {
+            let imports = ImportsBuilder::new().with_resolver("env", &env);
+            ModuleInstance::new(&module, &imports).unwrap()
+        };
+        let instance = instance.run_start(&mut env).unwrap();
+        instance.invoke_export("zkmain", &[], &mut env).unwrap();
+
+        env.outputs
+    }
+
+    fn check(textual_repr: &str, expected_outputs: &[u64]) {
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        assert_eq!(stock_wasmi_outputs(&wasm), expected_outputs);
+
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(K, wasm, vec![])
+                .unwrap()
+                .with_audit(true);
+
+        // The trace is checked by the auditor before the circuit is built.
+        let (circuit, instances, outputs) = loader
+            .circuit_with_witness(ExecutionArg::default())
+            .unwrap();
+        assert_eq!(outputs, expected_outputs);
+
+        loader.mock_test(&circuit, &instances).unwrap();
+    }
+
+    /// Differential fuzzing between the execution and the circuit: random
+    /// integer-only modules are generated together with their outputs, which
+    /// are evaluated by a reference model of the wasm semantics. The outputs of
+    /// upstream wasmi and of the traced execution must match, and the trace must
+    /// pass the auditor and `MockProver`. Set `ZKWASM_FUZZ_SEED` and
+    /// `ZKWASM_FUZZ_ITERATIONS` to explore other modules, the seed and the
+    /// module are printed on failure.
+    #[test]
+    fn test_fuzz_differential() {
+        let seed = env_u64("ZKWASM_FUZZ_SEED", 0);
+        let iterations = env_u64("ZKWASM_FUZZ_ITERATIONS", 32);
+
+        for seed in seed..seed + iterations {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let memory = (0..DATA_SIZE).map(|_| rng.gen()).collect();
+            let (textual_repr, expected_outputs) = Generator { rng, memory }.module();
+
+            let checked =
+                panic::catch_unwind(AssertUnwindSafe(|| check(&textual_repr, &expected_outputs)));
+            if let Err(panic) = checked {
+                eprintln!("seed {}: {}", seed, textual_repr);
+                panic::resume_unwind(panic);
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+mod tests {
+    use anyhow::Result;
+
+    use halo2_proofs::pairing::bn256::Bn256;
+
+    use crate::circuits::config::compute_maximal_pages;
+    use crate::circuits::config::max_image_table_rows;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    const K: u32 = 18;
+
+    /// Grow the memory declared by `memory` and return the output of `memory.grow`.
+    fn run_memory_grow(memory: &str, grow_size: u32) -> Result<Vec<u64>> {
+        let textual_repr = format!(
+            r#"
+            (module
+                (import "env" "wasm_output" (func $wasm_output (param i64)))
+                {}
+
+                (func (export "zkmain")
+                  (call $wasm_output (i64.extend_i32_s (memory.grow (i32.const {}))))
+                )
+            )
+            "#,
+            memory, grow_size
+        );
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(K, wasm, vec![])?;
+
+        let (circuit, instances, outputs) = loader.circuit_with_witness(ExecutionArg::default())?;
+        loader.mock_test(&circuit, &instances)?;
+
+        Ok(outputs)
+    }
+
+    #[test]
+    fn test_memory_grow_within_k() {
+        let maximal_pages = compute_maximal_pages(K);
+
+        let outputs = run_memory_grow("(memory 1)", maximal_pages - 1).unwrap();
+        assert_eq!(outputs, vec![1]);
+    }
+
+    #[test]
+    fn test_memory_grow_beyond_k() {
+        let maximal_pages = compute_maximal_pages(K);
+
+        // The declared maximum is clamped to the pages addressable under K.
+        let outputs = run_memory_grow("(memory 1 65536)", maximal_pages).unwrap();
+        assert_eq!(outputs, vec![u64::MAX]);
+
+        let outputs = run_memory_grow("(memory 1)", u32::MAX).unwrap();
+        assert_eq!(outputs, vec![u64::MAX]);
+    }
+
+    #[test]
+    fn test_init_memory_beyond_k() {
+        let maximal_pages = compute_maximal_pages(K);
+
+        assert!(run_memory_grow(&format!("(memory {})", maximal_pages + 1), 0).is_err());
+    }
+
+    #[test]
+    fn test_data_segment_beyond_image_table() {
+        // Distinct words so that the init memory entries are not merged.
+        let words = 10000u64;
+        let data = (0..words)
+            .flat_map(|i| i.to_le_bytes())
+            .map(|byte| format!("\\{:02x}", byte))
+            .collect::<String>();
+
+        let textual_repr = format!(
+            r#"
+            (module
+                (memory 2)
+                (data (i32.const 0) "{}")
+
+                (func (export "zkmain")
+                  (drop (i64.load (i32.const 8)))
+                )
+            )
+            "#,
+            data
+        );
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(K, wasm, vec![])
+                .unwrap();
+
+        let (circuit, instances, _) = loader
+            .circuit_with_witness(ExecutionArg::default())
+            .unwrap();
+
+        assert!(
+            circuit.tables.compilation_tables.imtable.entries().len()
+                > max_image_table_rows() as usize
+        );
+
+        loader.mock_test(&circuit, &instances).unwrap();
+    }
+}
@@ -0,0 +1,95 @@
+mod tests {
+    use halo2_proofs::pairing::bn256::Bn256;
+    use specs::mtable::MTable;
+
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+    use crate::runtime::memory_event_of_step;
+    use crate::runtime::mtable_builder::MTableBuilder;
+
+    const K: u32 = 18;
+
+    #[test]
+    fn test_mtable_builder_spilled_runs() {
+        let textual_repr = r#"
+            (module
+                (memory $0 1)
+                (data (i32.const 0) "\01\00\00\00\00\00\00\00")
+                (global $g (mut i64) (i64.const 3))
+
+                (func (export "zkmain")
+                  (local $n i32)
+                  (local.set $n (i32.const 20))
+                  (block
+                    (loop
+                      (br_if 1 (i32.eqz (local.get $n)))
+                      (i64.store
+                        (i32.mul (local.get $n) (i32.const 8))
+                        (i64.add (i64.load (i32.const 0)) (global.get $g)))
+                      (global.set $g (i64.load (i32.mul (local.get $n) (i32.const 8))))
+                      (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+                      (br 0)
+                    )
+                  )
+                )
+            )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(K, wasm, vec![])
+                .unwrap();
+
+        let execution_result = loader.run(ExecutionArg::default(), false, false).unwrap();
+        let tables = execution_result.tables;
+        let imtable = &tables.compilation_tables.imtable;
+
+        let mentries = tables
+            .execution_tables
+            .etable
+            .entries()
+            .iter()
+            .map(|eentry| memory_event_of_step(eentry, &mut 1))
+            .collect::<Vec<_>>();
+        let expected = MTable::new(mentries.concat(), imtable);
+
+        // A tiny chunk spills many runs which are merged back.
+        let mut builder = MTableBuilder::new(std::env::temp_dir(), 7);
+        for entries in mentries.iter() {
+            builder.push(entries.clone()).unwrap();
+        }
+        let (mtable, memory_events) = builder.finalize(imtable).unwrap();
+
+        assert_eq!(mtable.entries(), expected.entries());
+        assert_eq!(tables.execution_tables.mtable.entries(), expected.entries());
+
+        // Each event of a step is linked to its row in the memory table.
+        for (step, entries) in mentries.iter().enumerate() {
+            assert_eq!(
+                memory_events
+                    .of_step(step, &mtable)
+                    .cloned()
+                    .collect::<Vec<_>>(),
+                *entries
+            );
+
+            for (entry, row) in entries.iter().zip(memory_events.mtable_rows_of_step(step)) {
+                assert_eq!(&mtable.entries()[*row], entry);
+            }
+        }
+
+        // The spilled runs of a builder which is not finalized are removed on drop.
+        let spill_root =
+            std::env::temp_dir().join(format!("test_mtable_builder_drop_{}", std::process::id()));
+        let mut builder = MTableBuilder::new(spill_root.clone(), 7);
+        for entries in mentries.iter() {
+            builder.push(entries.clone()).unwrap();
+        }
+        assert_eq!(std::fs::read_dir(&spill_root).unwrap().count(), 1);
+
+        drop(builder);
+        assert_eq!(std::fs::read_dir(&spill_root).unwrap().count(), 0);
+        std::fs::remove_dir(&spill_root).unwrap();
+    }
+}
@@ -0,0 +1,96 @@
+mod tests {
+    use halo2_proofs::pairing::bn256::Bn256;
+    use num_bigint::BigUint;
+    use specs::encode::instruction_table::decode_instruction_table_entry;
+    use specs::itable::Opcode;
+    use specs::itable::OPCODE_SHIFT;
+
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+    use crate::runtime::host::HostEnvBuilder;
+
+    const K: u32 = 18;
+
+    #[test]
+    fn test_opcode_decode_round_trip() {
+        let textual_repr = r#"
+            (module
+                (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+                (import "env" "wasm_output" (func $wasm_output (param i64)))
+                (type $t (func (param i64) (result i64)))
+                (memory $0 1)
+                (data (i32.const 0) "\01\02\03\04\05\06\07\08")
+                (global $g (mut i64) (i64.const 7))
+                (table 1 funcref)
+                (elem (i32.const 0) $double)
+
+                (func $double (type $t)
+                  (i64.shl (local.get 0) (i64.const 1))
+                )
+
+                (func $pick (param $i i32) (result i64)
+                  (block
+                    (block
+                      (br_table 0 1 (local.get $i))
+                    )
+                    (return (i64.const 1))
+                  )
+                  (i64.const 2)
+                )
+
+                (func (export "zkmain")
+                  (local $x i64) (local $y i32)
+                  (local.set $x (call $wasm_input (i32.const 1)))
+                  (local.set $y (i32.wrap_i64 (local.tee $x (i64.add (local.get $x) (global.get $g)))))
+                  (global.set $g (i64.extend_i32_s (i32.rotl (local.get $y) (i32.const 3))))
+                  (i64.store16 offset=2 (i32.const 0) (i64.load32_u (i32.const 1)))
+                  (drop (memory.grow (i32.const 0)))
+                  (if (i32.eqz (i32.lt_u (local.get $y) (memory.size)))
+                    (then (local.set $x (i64.popcnt (local.get $x))))
+                  )
+                  (call $wasm_output
+                    (select
+                      (call_indirect (type $t) (local.get $x) (i32.const 0))
+                      (call $pick (i32.and (local.get $y) (i32.const 1)))
+                      (i64.ne (i64.rem_s (local.get $x) (i64.const 3)) (i64.const 0))
+                    )
+                  )
+                )
+            )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(K, wasm, vec![])
+                .unwrap();
+        let (env, _) = DefaultHostEnvBuilder::create_env_without_value();
+        let tables = loader.compile(&env, true).unwrap();
+
+        for entry in tables.itable.iter() {
+            let encode: BigUint = (&entry.opcode).into();
+            assert_eq!(
+                decode_instruction_table_entry(&entry.encode),
+                (entry.fid, entry.iid, encode.clone())
+            );
+
+            let decoded = Opcode::try_from(&encode).unwrap();
+            assert_eq!(Into::<BigUint>::into(&decoded), encode);
+
+            // The fields of the others are only partially committed.
+            match entry.opcode {
+                Opcode::Return { .. }
+                | Opcode::Br { .. }
+                | Opcode::BrIf { .. }
+                | Opcode::BrIfEqz { .. }
+                | Opcode::BrTable { .. }
+                | Opcode::InternalHostCall { .. } => (),
+                _ => assert_eq!(decoded, entry.opcode),
+            }
+        }
+
+        let drop: BigUint = (&Opcode::Drop).into();
+        assert!(Opcode::try_from(&(drop + 1u64)).is_err());
+        assert!(Opcode::try_from(&(BigUint::from(1u64) << OPCODE_SHIFT)).is_err());
+    }
+}
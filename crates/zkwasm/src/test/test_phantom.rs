@@ -0,0 +1,296 @@
+mod tests {
+    use halo2_proofs::pairing::bn256::Bn256;
+    use wabt::Wat2Wasm;
+
+    use crate::loader::err::Error;
+    use crate::loader::err::Impurity;
+    use crate::loader::err::PreCheckErr;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    fn load(textual_repr: &str, phantom_functions: Vec<String>) -> anyhow::Result<()> {
+        let wasm = Wat2Wasm::new()
+            .write_debug_names(true)
+            .convert(textual_repr)
+            .expect("failed to parse wat");
+
+        let loader = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(
+            18,
+            wasm.as_ref().to_vec(),
+            phantom_functions,
+        )?;
+
+        let report = loader.phantom_functions_report()?;
+        assert!(report.iter().all(|matched| !matched.functions.is_empty()));
+
+        Ok(())
+    }
+
+    /// Returns the impure callee and the reason reported for the phantom functions.
+    fn impurity(textual_repr: &str, phantom_functions: Vec<String>) -> (String, Impurity) {
+        match load(textual_repr, phantom_functions)
+            .unwrap_err()
+            .downcast::<Error>()
+            .unwrap()
+        {
+            Error::PreCheck(PreCheckErr::PhantomFunctionNotPure { callee, reason, .. }) => {
+                (callee, reason)
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_pure_phantom_function() {
+        let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (func $search (param i32)
+              (local.get 0)
+              (i32.load offset=0)
+              (call $helper)
+            )
+
+            (func $helper (param i32)
+              (local.get 0)
+              (drop)
+            )
+
+            (func (export "zkmain")
+              (i32.const 0)
+              (call $search)
+            )
+           )
+        "#;
+
+        if cfg!(feature = "phantom-hint") {
+            load(textual_repr, vec!["search".to_owned()]).unwrap();
+        } else {
+            let (callee, reason) = impurity(textual_repr, vec!["search".to_owned()]);
+            assert_eq!(callee, "search");
+            assert!(matches!(reason, Impurity::HasReturnValue));
+        }
+    }
+
+    #[test]
+    fn test_phantom_function_not_exists() {
+        let textual_repr = r#"
+        (module
+            (func (export "zkmain"))
+           )
+        "#;
+
+        assert!(matches!(
+            load(textual_repr, vec!["search".to_owned()])
+                .unwrap_err()
+                .downcast::<Error>()
+                .unwrap(),
+            Error::PreCheck(PreCheckErr::PhantomFunctionNotExists(regex)) if regex == "search"
+        ));
+    }
+
+    #[test]
+    fn test_phantom_function_write_memory() {
+        let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (func $search (param i32)
+              (call $helper)
+            )
+
+            (func $helper
+              (i32.const 0)
+              (i32.const 0)
+              (i32.store offset=0)
+            )
+
+            (func (export "zkmain")
+              (i32.const 0)
+              (call $search)
+            )
+           )
+        "#;
+
+        let (callee, reason) = impurity(textual_repr, vec!["search".to_owned()]);
+        assert_eq!(callee, "helper");
+        assert!(matches!(reason, Impurity::WriteMemory { iid: 2 }));
+    }
+
+    #[test]
+    fn test_phantom_function_write_global() {
+        let textual_repr = r#"
+        (module
+            (global $g (mut i32) (i32.const 0))
+            (func $search
+              (i32.const 1)
+              (global.set $g)
+            )
+
+            (func (export "zkmain")
+              (call $search)
+            )
+           )
+        "#;
+
+        let (callee, reason) = impurity(textual_repr, vec!["search".to_owned()]);
+        assert_eq!(callee, "search");
+        assert!(matches!(
+            reason,
+            Impurity::WriteGlobal { iid: 1, global: 0 }
+        ));
+    }
+
+    #[test]
+    fn test_phantom_function_grow_memory() {
+        let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (func $search
+              (i32.const 1)
+              (memory.grow)
+              (drop)
+            )
+
+            (func (export "zkmain")
+              (call $search)
+            )
+           )
+        "#;
+
+        let (callee, reason) = impurity(textual_repr, vec!["search".to_owned()]);
+        assert_eq!(callee, "search");
+        assert!(matches!(reason, Impurity::GrowMemory { iid: 1 }));
+    }
+
+    #[test]
+    fn test_phantom_function_call_indirect() {
+        let textual_repr = r#"
+        (module
+            (type $t (func))
+            (table 1 funcref)
+            (elem (i32.const 0) $helper)
+            (func $search
+              (i32.const 0)
+              (call_indirect (type $t))
+            )
+
+            (func $helper)
+
+            (func (export "zkmain")
+              (call $search)
+            )
+           )
+        "#;
+
+        let (callee, reason) = impurity(textual_repr, vec!["search".to_owned()]);
+        assert_eq!(callee, "search");
+        assert!(matches!(reason, Impurity::CallIndirect { iid: 1 }));
+    }
+
+    #[test]
+    fn test_phantom_function_call_host_function() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (func $search
+              (i32.const 1)
+              (call $wasm_input)
+              (drop)
+            )
+
+            (func (export "zkmain")
+              (call $search)
+            )
+           )
+        "#;
+
+        let (callee, reason) = impurity(textual_repr, vec!["search".to_owned()]);
+        assert_eq!(callee, "env.wasm_input");
+        assert!(matches!(reason, Impurity::CallHostFunction));
+    }
+
+    #[test]
+    fn test_phantom_function_with_return_value() {
+        // The return value is a hint checked by the caller, which is only
+        // allowed with the `phantom-hint` feature.
+        let textual_repr = r#"
+        (module
+            (func $search (param i32) (result i32)
+              (local.get 0)
+              (i32.const 1)
+              (i32.sub)
+            )
+
+            (func (export "zkmain")
+              (i32.const 1)
+              (call $search)
+              (i32.eqz)
+              (br_if 0)
+              (unreachable)
+            )
+           )
+        "#;
+
+        load(textual_repr, vec!["search".to_owned()]).unwrap();
+    }
+
+    #[test]
+    fn test_suggest_phantom_functions() {
+        let textual_repr = r#"
+        (module
+            (global $g (mut i32) (i32.const 0))
+            (func $search (param i32)
+              (local.get 0)
+              (i32.const 1)
+              (i32.add)
+              (drop)
+            )
+
+            (func $update
+              (i32.const 1)
+              (global.set $g)
+            )
+
+            (func $hint (result i32)
+              (i32.const 0)
+            )
+
+            (func (export "zkmain")
+              (i32.const 0)
+              (call $search)
+              (call $update)
+              (call $hint)
+              (drop)
+            )
+           )
+        "#;
+
+        let wasm = Wat2Wasm::new()
+            .write_debug_names(true)
+            .convert(textual_repr)
+            .expect("failed to parse wat");
+
+        let loader = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(
+            18,
+            wasm.as_ref().to_vec(),
+            vec![],
+        )
+        .unwrap();
+
+        let suggestions = loader
+            .suggest_phantom_functions(ExecutionArg::default())
+            .unwrap();
+
+        assert_eq!(suggestions[0].name, "search");
+        assert!(suggestions[0].saved_rows > 0);
+        assert!(!suggestions[0].has_return_value);
+        if cfg!(feature = "phantom-hint") {
+            assert_eq!(suggestions.len(), 2);
+            assert_eq!(suggestions[1].name, "hint");
+            assert!(suggestions[1].has_return_value);
+        } else {
+            assert_eq!(suggestions.len(), 1);
+        }
+    }
+}
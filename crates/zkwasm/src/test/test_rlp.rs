@@ -1,6 +1,3 @@
-use std::sync::Arc;
-use std::sync::Mutex;
-
 use crate::circuits::TestCircuit;
 use crate::loader::ZkWasmLoader;
 use crate::runtime::host::default_env::DefaultHostEnvBuilder;
@@ -159,8 +156,7 @@ fn build_circuit() -> Result<(
     let (circuit, instances, _) = loader.circuit_with_witness(ExecutionArg {
         public_inputs,
         private_inputs,
-        context_inputs: vec![],
-        context_outputs: Arc::new(Mutex::new(vec![])),
+        ..Default::default()
     })?;
 
     Ok((loader, circuit, instances))
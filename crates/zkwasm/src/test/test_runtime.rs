@@ -0,0 +1,183 @@
+mod tests {
+    use std::rc::Rc;
+
+    use anyhow::Result;
+    use halo2_proofs::pairing::bn256::Bn256;
+    use parity_wasm::elements::Module;
+    use specs::external_host_call_table::ExternalHostCallSignature;
+    use specs::CompilationTable;
+    use specs::ExecutionTable;
+    use specs::Tables;
+    use wasmi::RuntimeArgs;
+    use wasmi::RuntimeValue;
+
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+    use crate::runtime::host::host_env::HostEnv;
+    use crate::runtime::host::ForeignContext;
+    use crate::runtime::step_counter::StepCounts;
+    use crate::runtime::wasmi_interpreter::WasmiRuntime;
+    use crate::runtime::DryRunResult;
+    use crate::runtime::ExecutionResult;
+    use crate::runtime::WasmRuntime;
+    use crate::runtime::WasmRuntimeIO;
+    use crate::runtime::WasmValue;
+
+    const K: u32 = 18;
+
+    /// A runtime which does not interpret the image: it reads the first public
+    /// input through the host functions and returns it incremented, as `zkmain`
+    /// of `image` does.
+    struct MockRuntime;
+
+    impl MockRuntime {
+        fn execute(env: &mut HostEnv) -> Result<Option<WasmValue>> {
+            let wasm_input = env.resolve_function("wasm_input").unwrap();
+
+            match env.invoke(wasm_input, &[WasmValue::I32(1)])? {
+                Some(WasmValue::I64(input)) => Ok(Some(WasmValue::I64(input + 1))),
+                _ => Ok(None),
+            }
+        }
+    }
+
+    impl WasmRuntime for MockRuntime {
+        type Module = Module;
+
+        fn load(module: &Module) -> Result<Module> {
+            Ok(module.clone())
+        }
+
+        fn compile(
+            module: &Module,
+            env: &HostEnv,
+            entry: &str,
+            dryrun: bool,
+            phantom_functions: &Vec<String>,
+        ) -> Result<CompilationTable> {
+            WasmiRuntime::compile(
+                &WasmiRuntime::load(module)?,
+                env,
+                entry,
+                dryrun,
+                phantom_functions,
+            )
+        }
+
+        fn run(
+            module: &Module,
+            env: &mut HostEnv,
+            wasm_io: WasmRuntimeIO,
+            entry: &str,
+            dryrun: bool,
+            phantom_functions: &Vec<String>,
+        ) -> Result<ExecutionResult<WasmValue>> {
+            let compilation_tables = Self::compile(module, env, entry, dryrun, phantom_functions)?;
+            let result = Self::execute(env)?;
+
+            Ok(ExecutionResult {
+                tables: Tables {
+                    compilation_tables,
+                    execution_tables: ExecutionTable::default(),
+                },
+                result,
+                consumed_steps: 0,
+                public_inputs_and_outputs: wasm_io.public_inputs_and_outputs.borrow().clone(),
+                outputs: wasm_io.outputs.borrow().clone(),
+            })
+        }
+
+        fn dry_run(
+            _module: &Module,
+            env: &mut HostEnv,
+            wasm_io: WasmRuntimeIO,
+            _entry: &str,
+            _phantom_functions: &Vec<String>,
+        ) -> Result<DryRunResult> {
+            Self::execute(env)?;
+
+            Ok(DryRunResult {
+                step_counts: StepCounts::default(),
+                public_inputs_and_outputs: wasm_io.public_inputs_and_outputs.borrow().clone(),
+                outputs: wasm_io.outputs.borrow().clone(),
+            })
+        }
+    }
+
+    fn image() -> Vec<u8> {
+        let textual_repr = r#"
+            (module
+                (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+
+                (func (export "zkmain") (result i64)
+                  (i64.add (call $wasm_input (i32.const 1)) (i64.const 1))
+                )
+            )
+        "#;
+
+        wabt::wat2wasm(textual_repr).expect("failed to parse wat")
+    }
+
+    fn arg() -> ExecutionArg {
+        ExecutionArg {
+            public_inputs: vec![41],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_mock_runtime() {
+        let loader = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder, MockRuntime>::new(
+            K,
+            image(),
+            vec![],
+        )
+        .unwrap();
+        let wasmi_loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(K, image(), vec![])
+                .unwrap();
+
+        let result = loader.run(arg(), false, false).unwrap();
+        let expected = wasmi_loader.run(arg(), false, false).unwrap();
+
+        assert_eq!(result.result, Some(WasmValue::I64(42)));
+        assert_eq!(result.result, expected.result);
+        assert_eq!(
+            result.public_inputs_and_outputs,
+            expected.public_inputs_and_outputs
+        );
+        assert_eq!(
+            result.tables.compilation_tables.fid_of_entry,
+            expected.tables.compilation_tables.fid_of_entry
+        );
+
+        let dry_run = loader.dry_run(arg()).unwrap();
+        assert_eq!(dry_run.public_inputs_and_outputs, vec![41]);
+    }
+
+    struct Context;
+    impl ForeignContext for Context {}
+
+    #[test]
+    fn test_invoke_non_integer_return() {
+        let mut env = HostEnv::new();
+        let plugin = env
+            .external_env
+            .register_plugin("foreign_float", Box::new(Context));
+        env.external_env.register_function(
+            "foreign_float",
+            0,
+            ExternalHostCallSignature::Return,
+            plugin,
+            Rc::new(|_: &mut dyn ForeignContext, _: RuntimeArgs| {
+                Some(RuntimeValue::F32(1.0f32.into()))
+            }),
+        );
+        env.finalize();
+
+        // The value is reported as an error rather than panicking.
+        let index = env.resolve_function("foreign_float").unwrap();
+        assert!(env.invoke(index, &[]).is_err());
+    }
+}
@@ -0,0 +1,489 @@
+mod tests {
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::pairing::bn256::Bn256;
+    use specs::etable::EventTableEntry;
+    use specs::itable::OpcodeClass;
+    use specs::step::StepInfo;
+    use specs::Tables;
+    use strum::IntoEnumIterator;
+
+    use crate::circuits::tamper::op_constraint_names;
+    use crate::circuits::tamper::with_mutations;
+    use crate::circuits::tamper::Mutation;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    const K: u32 = 18;
+
+    /// The honest trace must pass, and the trace tampered by `mutations` must be
+    /// rejected, the failures are returned in their debug format.
+    fn rejections(textual_repr: &str, mutations: impl FnOnce(&Tables) -> Vec<Mutation>) -> String {
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(K, wasm, vec![])
+                .unwrap();
+
+        let (circuit, instances, _) = loader
+            .circuit_with_witness(ExecutionArg::default())
+            .unwrap();
+        let instances = circuit.instance_columns(&instances).unwrap();
+
+        let prover = MockProver::run(K, &circuit, instances.clone()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let mutations = mutations(&circuit.tables);
+        let prover = with_mutations(mutations.clone(), || {
+            MockProver::run(K, &circuit, instances).unwrap()
+        });
+        let failures = prover
+            .verify()
+            .expect_err(&format!("{:?} is not rejected", mutations));
+
+        format!("{:?}", failures)
+    }
+
+    fn assert_rejected(
+        textual_repr: &str,
+        mutations: impl FnOnce(&Vec<EventTableEntry>) -> Vec<Mutation>,
+    ) {
+        rejections(textual_repr, |tables| {
+            mutations(tables.execution_tables.etable.entries())
+        });
+    }
+
+    fn find_step(
+        entries: &Vec<EventTableEntry>,
+        predicate: impl Fn(&StepInfo) -> bool,
+    ) -> &EventTableEntry {
+        entries
+            .iter()
+            .find(|entry| predicate(&entry.step_info))
+            .unwrap()
+    }
+
+    const BIN: &str = r#"
+        (module
+            (func (export "zkmain")
+              (drop (i32.add (i32.const 1) (i32.const 2)))
+            )
+        )
+    "#;
+
+    #[test]
+    fn test_soundness_wrong_bin_result() {
+        assert_rejected(BIN, |entries| {
+            let entry = find_step(entries, |step| matches!(step, StepInfo::I32BinOp { .. }));
+
+            let mut step_info = entry.step_info.clone();
+            if let StepInfo::I32BinOp { value, .. } = &mut step_info {
+                *value = 4;
+            }
+
+            vec![
+                Mutation::StepInfo {
+                    eid: entry.eid,
+                    step_info,
+                },
+                Mutation::WrittenValue {
+                    eid: entry.eid,
+                    value: 4,
+                },
+            ]
+        });
+    }
+
+    #[test]
+    fn test_soundness_wrong_branch_target() {
+        let textual_repr = r#"
+            (module
+                (func (export "zkmain")
+                  (local i32)
+                  (block
+                    (br_if 0 (i32.const 1))
+                    (local.set 0 (i32.const 2))
+                  )
+                )
+            )
+        "#;
+
+        assert_rejected(textual_repr, |entries| {
+            let entry = find_step(entries, |step| {
+                matches!(
+                    step,
+                    StepInfo::BrIfNez { .. } | StepInfo::BrIfEqz { .. } | StepInfo::Br { .. }
+                )
+            });
+
+            let mut step_info = entry.step_info.clone();
+            match &mut step_info {
+                StepInfo::BrIfNez { dst_pc, .. }
+                | StepInfo::BrIfEqz { dst_pc, .. }
+                | StepInfo::Br { dst_pc, .. } => *dst_pc += 1,
+                _ => unreachable!(),
+            }
+
+            vec![Mutation::StepInfo {
+                eid: entry.eid,
+                step_info,
+            }]
+        });
+    }
+
+    #[test]
+    fn test_soundness_wrong_loaded_value() {
+        let textual_repr = r#"
+            (module
+                (memory $0 1)
+                (data (i32.const 0) "\2a")
+                (func (export "zkmain")
+                  (drop (i32.load (i32.const 0)))
+                )
+            )
+        "#;
+
+        assert_rejected(textual_repr, |entries| {
+            let entry = find_step(entries, |step| matches!(step, StepInfo::Load { .. }));
+
+            let mut step_info = entry.step_info.clone();
+            let tampered_value = match &mut step_info {
+                StepInfo::Load { value, .. } => {
+                    *value += 1;
+                    *value
+                }
+                _ => unreachable!(),
+            };
+
+            vec![
+                Mutation::StepInfo {
+                    eid: entry.eid,
+                    step_info,
+                },
+                Mutation::WrittenValue {
+                    eid: entry.eid,
+                    value: tampered_value,
+                },
+            ]
+        });
+    }
+
+    #[test]
+    fn test_soundness_dropped_mtable_entry() {
+        assert_rejected(BIN, |entries| {
+            let entry = find_step(entries, |step| matches!(step, StepInfo::I32Const { .. }));
+
+            vec![Mutation::DropWrite { eid: entry.eid }]
+        });
+    }
+
+    /// Tamper the result of the step, or the operand it depends on if the step
+    /// has no result. The tampered result is also written to the memory table,
+    /// so that the memory lookups of the written entries still hold.
+    fn tamper_step(entry: &EventTableEntry) -> Vec<Mutation> {
+        let mut step_info = entry.step_info.clone();
+
+        let written_value = match &mut step_info {
+            StepInfo::I32Const { value }
+            | StepInfo::I32BinOp { value, .. }
+            | StepInfo::I32BinShiftOp { value, .. }
+            | StepInfo::I32BinBitOp { value, .. } => {
+                *value += 1;
+                Some(*value as u32 as u64)
+            }
+            StepInfo::I64Const { value }
+            | StepInfo::I64BinOp { value, .. }
+            | StepInfo::I64BinShiftOp { value, .. }
+            | StepInfo::I64BinBitOp { value, .. } => {
+                *value += 1;
+                Some(*value as u64)
+            }
+            StepInfo::I32Comp { value, .. } | StepInfo::I64Comp { value, .. } => {
+                *value = !*value;
+                Some(*value as u64)
+            }
+            StepInfo::Test { result, .. } => {
+                *result ^= 1;
+                Some(*result as u32 as u64)
+            }
+            StepInfo::I32WrapI64 { result, .. }
+            | StepInfo::I32SignExtendI8 { result, .. }
+            | StepInfo::I32SignExtendI16 { result, .. }
+            | StepInfo::MemoryGrow { result, .. } => {
+                *result += 1;
+                Some(*result as u32 as u64)
+            }
+            StepInfo::I64ExtendI32 { result, .. }
+            | StepInfo::I64SignExtendI8 { result, .. }
+            | StepInfo::I64SignExtendI16 { result, .. }
+            | StepInfo::I64SignExtendI32 { result, .. } => {
+                *result += 1;
+                Some(*result as u64)
+            }
+            StepInfo::UnaryOp { result, .. } | StepInfo::Select { result, .. } => {
+                *result += 1;
+                Some(*result)
+            }
+            StepInfo::GetLocal { value, .. }
+            | StepInfo::SetLocal { value, .. }
+            | StepInfo::TeeLocal { value, .. }
+            | StepInfo::GetGlobal { value, .. }
+            | StepInfo::SetGlobal { value, .. }
+            | StepInfo::Load { value, .. } => {
+                *value += 1;
+                Some(*value)
+            }
+            StepInfo::Return { keep_values, .. } => {
+                keep_values[0] += 1;
+                Some(keep_values[0])
+            }
+            StepInfo::Store { value, .. } => {
+                *value += 1;
+                None
+            }
+            StepInfo::Br { dst_pc, .. }
+            | StepInfo::BrIfEqz { dst_pc, .. }
+            | StepInfo::BrIfNez { dst_pc, .. }
+            | StepInfo::BrTable { dst_pc, .. } => {
+                *dst_pc += 1;
+                None
+            }
+            StepInfo::Call { index } => {
+                *index += 1;
+                None
+            }
+            StepInfo::CallIndirect { func_index, .. } => {
+                *func_index += 1;
+                None
+            }
+            // The memory size is not in the step info but in the common cells.
+            StepInfo::MemorySize => {
+                return vec![Mutation::WrittenValue {
+                    eid: entry.eid,
+                    value: entry.allocated_memory_pages as u64 + 1,
+                }]
+            }
+            step_info => panic!("no mutation of {:?}", step_info),
+        };
+
+        let mut mutations = vec![Mutation::StepInfo {
+            eid: entry.eid,
+            step_info,
+        }];
+        if let Some(value) = written_value {
+            mutations.push(Mutation::WrittenValue {
+                eid: entry.eid,
+                value,
+            });
+        }
+
+        mutations
+    }
+
+    const ITABLE_LOOKUP: [&str; 2] = ["c7. itable_lookup_encode", "c8a. itable_lookup in itable"];
+    const MTABLE_LOOKUP: [&str; 1] = ["c8e. mtable_lookup in mtable"];
+
+    /// A module whose `zkmain` executes a step of `class`, and the constraints
+    /// outside of the op config of `class` which check the tampered step: the
+    /// instruction lookup for operands encoded in the opcode, the memory table
+    /// lookup for values read from memory and the transitions to the next step.
+    struct OpConfigCase {
+        class: OpcodeClass,
+        textual_repr: &'static str,
+        shared_constraints: &'static [&'static str],
+    }
+
+    const OP_CONFIG_CASES: &[OpConfigCase] = &[
+        OpConfigCase {
+            class: OpcodeClass::LocalGet,
+            textual_repr: r#"(module (func (export "zkmain") (local i32) (drop (local.get 0))))"#,
+            shared_constraints: &MTABLE_LOOKUP,
+        },
+        OpConfigCase {
+            class: OpcodeClass::LocalSet,
+            textual_repr: r#"(module (func (export "zkmain") (local i32) (local.set 0 (i32.const 1))))"#,
+            shared_constraints: &MTABLE_LOOKUP,
+        },
+        OpConfigCase {
+            class: OpcodeClass::LocalTee,
+            textual_repr: r#"(module (func (export "zkmain") (local i32) (drop (local.tee 0 (i32.const 1)))))"#,
+            shared_constraints: &MTABLE_LOOKUP,
+        },
+        OpConfigCase {
+            class: OpcodeClass::GlobalGet,
+            textual_repr: r#"(module (global (mut i32) (i32.const 1)) (func (export "zkmain") (drop (global.get 0))))"#,
+            shared_constraints: &MTABLE_LOOKUP,
+        },
+        OpConfigCase {
+            class: OpcodeClass::GlobalSet,
+            textual_repr: r#"(module (global (mut i32) (i32.const 1)) (func (export "zkmain") (global.set 0 (i32.const 2))))"#,
+            shared_constraints: &MTABLE_LOOKUP,
+        },
+        OpConfigCase {
+            class: OpcodeClass::Const,
+            textual_repr: r#"(module (func (export "zkmain") (drop (i32.const 1))))"#,
+            shared_constraints: &ITABLE_LOOKUP,
+        },
+        OpConfigCase {
+            class: OpcodeClass::Select,
+            textual_repr: r#"(module (func (export "zkmain") (drop (select (i32.const 1) (i32.const 2) (i32.const 1)))))"#,
+            shared_constraints: &[],
+        },
+        OpConfigCase {
+            class: OpcodeClass::Return,
+            textual_repr: r#"(module (func $f (result i32) (i32.const 1)) (func (export "zkmain") (drop (call $f))))"#,
+            shared_constraints: &MTABLE_LOOKUP,
+        },
+        OpConfigCase {
+            class: OpcodeClass::Bin,
+            textual_repr: r#"(module (func (export "zkmain") (drop (i32.add (i32.const 1) (i32.const 2)))))"#,
+            shared_constraints: &[],
+        },
+        OpConfigCase {
+            class: OpcodeClass::Unary,
+            textual_repr: r#"(module (func (export "zkmain") (drop (i32.clz (i32.const 1)))))"#,
+            shared_constraints: &[],
+        },
+        OpConfigCase {
+            class: OpcodeClass::BinShift,
+            textual_repr: r#"(module (func (export "zkmain") (drop (i32.shl (i32.const 1) (i32.const 2)))))"#,
+            shared_constraints: &[],
+        },
+        OpConfigCase {
+            class: OpcodeClass::BinBit,
+            textual_repr: r#"(module (func (export "zkmain") (drop (i32.and (i32.const 3) (i32.const 5)))))"#,
+            shared_constraints: &[],
+        },
+        OpConfigCase {
+            class: OpcodeClass::Test,
+            textual_repr: r#"(module (func (export "zkmain") (drop (i32.eqz (i32.const 1)))))"#,
+            shared_constraints: &[],
+        },
+        OpConfigCase {
+            class: OpcodeClass::Rel,
+            textual_repr: r#"(module (func (export "zkmain") (drop (i32.lt_u (i32.const 1) (i32.const 2)))))"#,
+            shared_constraints: &[],
+        },
+        OpConfigCase {
+            class: OpcodeClass::Br,
+            textual_repr: r#"(module (func (export "zkmain") (block (br 0))))"#,
+            shared_constraints: &["c6c. iid change", ITABLE_LOOKUP[0], ITABLE_LOOKUP[1]],
+        },
+        OpConfigCase {
+            class: OpcodeClass::BrIf,
+            textual_repr: r#"(module (func (export "zkmain") (block (br_if 0 (i32.const 1)))))"#,
+            shared_constraints: &["c6c. iid change", ITABLE_LOOKUP[0], ITABLE_LOOKUP[1]],
+        },
+        OpConfigCase {
+            class: OpcodeClass::BrIfEqz,
+            textual_repr: r#"(module (func (export "zkmain") (if (i32.const 1) (then (nop)))))"#,
+            shared_constraints: &["c6c. iid change", ITABLE_LOOKUP[0], ITABLE_LOOKUP[1]],
+        },
+        OpConfigCase {
+            class: OpcodeClass::BrTable,
+            textual_repr: r#"(module (func (export "zkmain") (block (br_table 0 (i32.const 0)))))"#,
+            shared_constraints: &["c6c. iid change", "c8b. brtable_lookup in brtable"],
+        },
+        OpConfigCase {
+            class: OpcodeClass::Call,
+            textual_repr: r#"(module (func $f) (func (export "zkmain") (call $f)))"#,
+            shared_constraints: &[
+                "c6b. fid change",
+                ITABLE_LOOKUP[0],
+                ITABLE_LOOKUP[1],
+                "c8c. jtable_lookup in jtable",
+            ],
+        },
+        OpConfigCase {
+            class: OpcodeClass::CallIndirect,
+            textual_repr: r#"
+                (module
+                    (type $t (func))
+                    (table 1 funcref)
+                    (elem (i32.const 0) $f)
+                    (func $f (type $t))
+                    (func (export "zkmain") (call_indirect (type $t) (i32.const 0)))
+                )
+            "#,
+            shared_constraints: &["c6b. fid change", "c8c. jtable_lookup in jtable"],
+        },
+        OpConfigCase {
+            class: OpcodeClass::Load,
+            textual_repr: r#"(module (memory 1) (data (i32.const 0) "\2a") (func (export "zkmain") (drop (i32.load (i32.const 0)))))"#,
+            shared_constraints: &[],
+        },
+        OpConfigCase {
+            class: OpcodeClass::Store,
+            textual_repr: r#"(module (memory 1) (func (export "zkmain") (i32.store (i32.const 0) (i32.const 1))))"#,
+            shared_constraints: &MTABLE_LOOKUP,
+        },
+        OpConfigCase {
+            class: OpcodeClass::MemorySize,
+            textual_repr: r#"(module (memory 1) (func (export "zkmain") (drop (memory.size))))"#,
+            shared_constraints: &MTABLE_LOOKUP,
+        },
+        OpConfigCase {
+            class: OpcodeClass::MemoryGrow,
+            textual_repr: r#"(module (memory 1 2) (func (export "zkmain") (drop (memory.grow (i32.const 1)))))"#,
+            shared_constraints: &[],
+        },
+        OpConfigCase {
+            class: OpcodeClass::Conversion,
+            textual_repr: r#"(module (func (export "zkmain") (drop (i64.extend_i32_u (i32.const 1)))))"#,
+            shared_constraints: &[],
+        },
+    ];
+
+    /// The first step of each opcode class is tampered, and the trace must be
+    /// rejected by the op config of the class.
+    #[test]
+    fn test_soundness_every_op_config() {
+        for class in OpcodeClass::iter() {
+            let case = match class {
+                // `Drop` has no witness but the common sp transition, an
+                // `Unreachable` step never terminates and the host calls are
+                // covered with an external host env by `op_call_host`.
+                OpcodeClass::Drop
+                | OpcodeClass::Unreachable
+                | OpcodeClass::CallHost
+                | OpcodeClass::ForeignPluginStart => continue,
+                _ => OP_CONFIG_CASES
+                    .iter()
+                    .find(|case| case.class == class)
+                    .unwrap_or_else(|| panic!("no case of {:?}", class)),
+            };
+
+            let failures = rejections(case.textual_repr, |tables| {
+                let entry = tables
+                    .execution_tables
+                    .etable
+                    .entries()
+                    .iter()
+                    .find(|entry| {
+                        let opcode = &entry
+                            .get_instruction(&tables.compilation_tables.itable)
+                            .opcode;
+                        let entry_class: OpcodeClass = opcode.into();
+
+                        entry_class == class
+                    })
+                    .unwrap_or_else(|| panic!("no step of {:?}", class));
+
+                tamper_step(entry)
+            });
+
+            let names = op_constraint_names(class)
+                .into_iter()
+                .chain(case.shared_constraints.iter().cloned())
+                .collect::<Vec<_>>();
+            assert!(
+                names
+                    .iter()
+                    .any(|name| failures.contains(&format!("{:?}", name))),
+                "{:?} is not rejected by its op config {:?}: {}",
+                class,
+                names,
+                failures
+            );
+        }
+    }
+}
@@ -1,7 +1,4 @@
 mod tests {
-    use std::sync::Arc;
-    use std::sync::Mutex;
-
     use halo2_proofs::pairing::bn256::Bn256;
 
     use crate::loader::ZkWasmLoader;
@@ -40,12 +37,7 @@ mod tests {
                 .unwrap();
 
         let (circuit, instances, _) = loader
-            .circuit_with_witness(ExecutionArg {
-                public_inputs: vec![],
-                private_inputs: vec![],
-                context_inputs: vec![],
-                context_outputs: Arc::new(Mutex::new(vec![])),
-            })
+            .circuit_with_witness(ExecutionArg::default())
             .unwrap();
 
         loader.mock_test(&circuit, &instances).unwrap()
@@ -0,0 +1,194 @@
+mod tests {
+    #[cfg(feature = "public-eid")]
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::pairing::bn256::Bn256;
+    #[cfg(feature = "public-eid")]
+    use halo2_proofs::pairing::bn256::G1Affine;
+    #[cfg(feature = "public-eid")]
+    use halo2_proofs::poly::commitment::Params;
+    use wabt::Wat2Wasm;
+
+    #[cfg(feature = "public-eid")]
+    use crate::circuits::termination_eid;
+    #[cfg(feature = "public-eid")]
+    use crate::circuits::PublicInputs;
+    use crate::loader::err::Error;
+    use crate::loader::err::RuntimeErr;
+    #[cfg(feature = "public-eid")]
+    use crate::loader::err::VerifyErr;
+    #[cfg(feature = "public-eid")]
+    use crate::loader::TranscriptHash;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    const K: u32 = 18;
+
+    fn sum_loader() -> ZkWasmLoader<Bn256, ExecutionArg, DefaultHostEnvBuilder> {
+        let textual_repr = r#"
+            (module
+                (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+                (import "env" "wasm_output" (func $wasm_output (param i64)))
+
+                (func (export "zkmain")
+                  (local $n i64) (local $sum i64)
+                  (local.set $n (call $wasm_input (i32.const 1)))
+                  (block
+                    (loop
+                      (br_if 1 (i64.eqz (local.get $n)))
+                      (local.set $sum (i64.add (local.get $sum) (local.get $n)))
+                      (local.set $n (i64.sub (local.get $n) (i64.const 1)))
+                      (br 0)
+                    )
+                  )
+                  (call $wasm_output (local.get $sum))
+                )
+            )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+
+        ZkWasmLoader::new(K, wasm, vec![]).unwrap()
+    }
+
+    fn arg(step_limit: Option<u64>) -> ExecutionArg {
+        ExecutionArg {
+            public_inputs: vec![100],
+            step_limit,
+            ..Default::default()
+        }
+    }
+
+    fn assert_out_of_fuel(result: anyhow::Result<impl Sized>, limit: u64) {
+        match result.err().unwrap().downcast_ref::<Error>() {
+            Some(Error::Runtime(RuntimeErr::OutOfFuel { step_limit })) => {
+                assert_eq!(*step_limit, limit)
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_step_limit_within_budget() {
+        let loader = sum_loader();
+        let step_limit = loader.dry_run(arg(None)).unwrap().step_counts.etable;
+
+        let (circuit, instances, outputs) =
+            loader.circuit_with_witness(arg(Some(step_limit))).unwrap();
+
+        // The inputs are consumed once as host calls are replayed by the traced execution.
+        assert_eq!(instances.len(), 2);
+        assert_eq!(outputs, vec![5050]);
+        assert!(circuit.consumed_steps() <= step_limit);
+
+        loader.mock_test(&circuit, &instances).unwrap();
+    }
+
+    #[test]
+    fn test_step_limit_exhausted() {
+        let loader = sum_loader();
+        let step_limit = loader.dry_run(arg(None)).unwrap().step_counts.etable / 2;
+
+        assert_out_of_fuel(loader.run(arg(Some(step_limit)), false, false), step_limit);
+        assert_out_of_fuel(loader.dry_run(arg(Some(step_limit))), step_limit);
+    }
+
+    #[test]
+    fn test_step_limit_infinite_loop() {
+        let textual_repr = r#"
+            (module
+                (func (export "zkmain")
+                  (loop (br 0))
+                )
+            )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(K, wasm, vec![])
+                .unwrap();
+
+        assert_out_of_fuel(loader.run(arg(Some(10000)), false, false), 10000);
+    }
+
+    #[test]
+    fn test_step_limit_phantom_infinite_loop() {
+        let textual_repr = r#"
+            (module
+                (func $spin
+                  (loop (br 0))
+                )
+
+                (func (export "zkmain")
+                  (call $spin)
+                )
+            )
+        "#;
+
+        let wasm = Wat2Wasm::new()
+            .write_debug_names(true)
+            .convert(textual_repr)
+            .expect("failed to parse wat");
+        let loader = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(
+            K,
+            wasm.as_ref().to_vec(),
+            vec!["spin".to_owned()],
+        )
+        .unwrap();
+
+        // The phantom body is not traced, so it is limited by its own counter.
+        assert_out_of_fuel(loader.run(arg(Some(10000)), false, false), 10000);
+        assert_out_of_fuel(loader.dry_run(arg(Some(10000))), 10000);
+    }
+
+    #[cfg(feature = "public-eid")]
+    #[test]
+    fn test_public_eid() {
+        let loader = sum_loader();
+
+        let (circuit, instances, _) = loader.circuit_with_witness(arg(None)).unwrap();
+        let public_inputs = circuit.public_inputs(&instances).unwrap();
+        let consumed_steps = public_inputs.consumed_steps;
+
+        loader.mock_test(&circuit, &instances).unwrap();
+
+        // The termination eid is the last instance column.
+        let mut columns = circuit.instance_columns(&instances).unwrap();
+        *columns.last_mut().unwrap() = vec![termination_eid(consumed_steps + 1)];
+        let prover = MockProver::run(K, &circuit, columns).unwrap();
+        assert!(prover.verify().is_err());
+
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let vkey = loader.create_vkey(&params).unwrap();
+        let proof = loader
+            .create_proof(
+                &params,
+                vkey.clone(),
+                circuit,
+                &instances,
+                TranscriptHash::Poseidon,
+            )
+            .unwrap();
+
+        let verify = |consumed_steps: u64| {
+            loader.verify_proof(
+                &params,
+                vkey.clone(),
+                PublicInputs {
+                    consumed_steps,
+                    ..public_inputs.clone()
+                },
+                proof.clone(),
+                TranscriptHash::Poseidon,
+            )
+        };
+
+        verify(consumed_steps).unwrap();
+        assert!(matches!(
+            verify(consumed_steps + 1)
+                .unwrap_err()
+                .downcast_ref::<Error>(),
+            Some(Error::Verify(VerifyErr::InvalidProof))
+        ));
+    }
+}
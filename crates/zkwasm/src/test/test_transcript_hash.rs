@@ -0,0 +1,80 @@
+mod tests {
+    use halo2_proofs::pairing::bn256::Bn256;
+    use halo2_proofs::pairing::bn256::Fr;
+    use halo2_proofs::pairing::bn256::G1Affine;
+    use halo2_proofs::poly::commitment::Params;
+
+    use crate::circuits::PublicInputs;
+    use crate::loader::err::Error;
+    use crate::loader::err::VerifyErr;
+    use crate::loader::TranscriptHash;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    const K: u32 = 18;
+
+    /// The proof, instances, context hashes and consumed steps.
+    type Proof = (Vec<u8>, PublicInputs<Fr>);
+
+    #[test]
+    fn test_transcript_hash_round_trip() {
+        let textual_repr = r#"
+            (module
+                (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+                (import "env" "wasm_output" (func $wasm_output (param i64)))
+
+                (func (export "zkmain")
+                  (call $wasm_output (i64.add (call $wasm_input (i32.const 1)) (i64.const 1)))
+                )
+            )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(K, wasm, vec![])
+                .unwrap();
+
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let vkey = loader.create_vkey(&params).unwrap();
+
+        let prove = |hash: TranscriptHash| -> Proof {
+            let (circuit, instances, _) = loader
+                .circuit_with_witness(ExecutionArg {
+                    public_inputs: vec![41],
+                    ..Default::default()
+                })
+                .unwrap();
+            let public_inputs = circuit.public_inputs(&instances).unwrap();
+
+            let proof = loader
+                .create_proof(&params, vkey.clone(), circuit, &instances, hash)
+                .unwrap();
+
+            (proof, public_inputs)
+        };
+
+        let verify = |proof: &Proof, hash: TranscriptHash| {
+            let (proof, public_inputs) = proof.clone();
+
+            loader.verify_proof(&params, vkey.clone(), public_inputs, proof, hash)
+        };
+
+        let assert_invalid = |result: anyhow::Result<()>| {
+            assert!(matches!(
+                result.unwrap_err().downcast_ref::<Error>(),
+                Some(Error::Verify(VerifyErr::InvalidProof))
+            ));
+        };
+
+        let sha_proof = prove(TranscriptHash::Sha);
+        let poseidon_proof = prove(TranscriptHash::Poseidon);
+
+        verify(&sha_proof, TranscriptHash::Sha).unwrap();
+        verify(&poseidon_proof, TranscriptHash::Poseidon).unwrap();
+
+        // A proof only verifies with the transcript hash it is created with.
+        assert_invalid(verify(&poseidon_proof, TranscriptHash::Sha));
+        assert_invalid(verify(&sha_proof, TranscriptHash::Poseidon));
+    }
+}
@@ -1,8 +1,9 @@
 use crate::circuits::TestCircuit;
 use crate::circuits::ZkWasmCircuitBuilder;
 use crate::runtime::host::host_env::HostEnv;
-use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
 use crate::runtime::ExecutionResult;
+use crate::runtime::WasmRuntimeIO;
+use crate::runtime::WasmValue;
 use anyhow::Result;
 use halo2_proofs::pairing::bn256::Bn256;
 use halo2_proofs::pairing::bn256::Fr;
@@ -11,7 +12,6 @@ use halo2_proofs::plonk::keygen_pk;
 use halo2_proofs::plonk::keygen_vk;
 use halo2_proofs::plonk::ProvingKey;
 use halo2_proofs::poly::commitment::Params;
-use wasmi::RuntimeValue;
 
 use super::test_circuit_with_env;
 
@@ -60,7 +60,7 @@ fn setup_uniform_verifier() -> Result<(Params<G1Affine>, ProvingKey<G1Affine>)>
        return fib(input);
    }
 */
-fn build_test() -> Result<(ExecutionResult<RuntimeValue>, i32)> {
+fn build_test() -> Result<(ExecutionResult<WasmValue>, i32)> {
     let textual_repr = r#"
     (module
         (type (;0;) (func (param i32) (result i32)))
@@ -108,6 +108,7 @@ fn build_test() -> Result<(ExecutionResult<RuntimeValue>, i32)> {
 }
 
 mod tests {
+    use halo2_proofs::dev::MockProver;
     use halo2_proofs::plonk::create_proof;
     use halo2_proofs::plonk::verify_proof;
     use halo2_proofs::plonk::SingleVerifier;
@@ -118,7 +119,16 @@ mod tests {
     use rand::rngs::OsRng;
 
     use super::*;
+    use crate::circuits::config::compute_max_init_memory_table_rows;
     use crate::circuits::ZkWasmCircuitBuilder;
+    use crate::loader::check_image_commitment;
+    use crate::loader::err::Error;
+    use crate::loader::err::PreCheckErr;
+    use crate::loader::err::VerifyErr;
+    use crate::loader::TranscriptHash;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
 
     #[test]
     fn test_uniform_verifier() {
@@ -128,15 +138,17 @@ mod tests {
 
         assert_eq!(
             execution_result.result.unwrap(),
-            RuntimeValue::I32(expected_value)
+            WasmValue::I32(expected_value)
         );
 
-        let instances = vec![];
-
         let builder = ZkWasmCircuitBuilder {
             tables: execution_result.tables,
             public_inputs_and_outputs: execution_result.public_inputs_and_outputs,
         };
+        let circuit: TestCircuit<Fr> = builder.build_circuit();
+
+        let instances = circuit.instance_columns(&vec![]).unwrap();
+        let instances = instances.iter().map(|v| &v[..]).collect::<Vec<_>>();
 
         let proof = {
             let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
@@ -144,8 +156,8 @@ mod tests {
             create_proof(
                 &params,
                 &uniform_verifier_pk,
-                &[builder.build_circuit()],
-                &[&[&instances]],
+                &[circuit],
+                &[&instances[..]],
                 OsRng,
                 &mut transcript,
             )
@@ -167,10 +179,213 @@ mod tests {
                 &params_verifier,
                 uniform_verifier_pk.get_vk(),
                 strategy,
-                &[&[&instances]],
+                &[&instances[..]],
                 &mut transcript,
             )
             .unwrap();
         }
     }
+
+    #[test]
+    fn test_uniform_vkey_with_checksum() {
+        let fib = r#"
+        (module
+            (memory 1)
+            (data (i32.const 0) "fib")
+            (global $n (mut i32) (i32.const 10))
+            (global i64 (i64.const 55))
+            (func $fib (param i32) (result i32)
+              (if (result i32) (i32.le_u (local.get 0) (i32.const 1))
+                (then (local.get 0))
+                (else
+                  (i32.add
+                    (call $fib (i32.sub (local.get 0) (i32.const 1)))
+                    (call $fib (i32.sub (local.get 0) (i32.const 2)))))))
+            (func (export "zkmain")
+              (drop (call $fib (global.get $n))))
+        )
+        "#;
+        let other = r#"
+        (module
+            (func (export "zkmain")
+              (drop (i32.const 1)))
+        )
+        "#;
+
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let vkey = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::create_uniform_vkey(
+            K, &params,
+        )
+        .unwrap();
+
+        let loader = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(
+            K,
+            wabt::wat2wasm(fib).unwrap(),
+            vec![],
+        )
+        .unwrap();
+        let other_loader = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(
+            K,
+            wabt::wat2wasm(other).unwrap(),
+            vec![],
+        )
+        .unwrap();
+
+        // The vkey does not depend on the image.
+        {
+            let mut expected = vec![];
+            vkey.write(&mut expected).unwrap();
+
+            let mut buf = vec![];
+            loader
+                .create_vkey(&params)
+                .unwrap()
+                .write(&mut buf)
+                .unwrap();
+
+            assert_eq!(buf, expected);
+        }
+
+        let (circuit, instances, _) = loader
+            .circuit_with_witness(ExecutionArg::default())
+            .unwrap();
+        let public_inputs = circuit.public_inputs(&instances).unwrap();
+        let proof = loader
+            .create_proof(
+                &params,
+                vkey.clone(),
+                circuit,
+                &instances,
+                TranscriptHash::Poseidon,
+            )
+            .unwrap();
+
+        loader
+            .verify_proof(
+                &params,
+                vkey.clone(),
+                public_inputs,
+                proof.clone(),
+                TranscriptHash::Poseidon,
+            )
+            .unwrap();
+
+        let checksum = loader.checksum(&params).unwrap();
+        let other_checksum = other_loader.checksum(&params).unwrap();
+
+        check_image_commitment::<Bn256>(&vkey, &proof, &checksum, TranscriptHash::Poseidon)
+            .unwrap();
+        assert!(check_image_commitment::<Bn256>(
+            &vkey,
+            &proof,
+            &other_checksum,
+            TranscriptHash::Poseidon
+        )
+        .is_err());
+
+        // A truncated proof is rejected without panicking.
+        let error = check_image_commitment::<Bn256>(
+            &vkey,
+            &proof[..16].to_vec(),
+            &checksum,
+            TranscriptHash::Poseidon,
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::Verify(VerifyErr::InvalidProof))
+        ));
+    }
+
+    /// A module with `globals` globals and a data segment of `data`.
+    fn image_with_init_memory(globals: usize, data: &str) -> Vec<u8> {
+        let globals = (0..globals)
+            .map(|i| format!("(global (mut i64) (i64.const {}))", i))
+            .collect::<String>();
+
+        let textual_repr = format!(
+            r#"
+            (module
+                (memory 1)
+                (data (i32.const 0) "{}")
+                {}
+                (func (export "zkmain"))
+            )
+            "#,
+            data, globals
+        );
+
+        wabt::wat2wasm(&textual_repr).expect("failed to parse wat")
+    }
+
+    #[test]
+    fn test_uniform_vkey_with_init_memory() {
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let vkey = |image: Vec<u8>| {
+            let mut buf = vec![];
+
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(K, image, vec![])
+                .unwrap()
+                .create_vkey(&params)
+                .unwrap()
+                .write(&mut buf)
+                .unwrap();
+
+            buf
+        };
+
+        // The init memory is padded, so the data segments and globals do not
+        // change the vkey.
+        assert_eq!(
+            vkey(image_with_init_memory(0, "")),
+            vkey(image_with_init_memory(16, "uniform circuit"))
+        );
+    }
+
+    #[test]
+    fn test_init_memory_overflow() {
+        let available_rows = compute_max_init_memory_table_rows(K) as usize;
+
+        let error = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(
+            K,
+            image_with_init_memory(available_rows, ""),
+            vec![],
+        )
+        .err()
+        .unwrap();
+
+        match error.downcast_ref::<Error>() {
+            Some(Error::PreCheck(PreCheckErr::InitMemoryTableOverflow {
+                required_rows,
+                minimal_k,
+                ..
+            })) => {
+                assert!(*required_rows > available_rows);
+                assert_eq!(*minimal_k, Some(K + 1));
+            }
+            _ => panic!("unexpected error: {:?}", error),
+        }
+    }
+
+    #[test]
+    fn test_image_hash_instance() {
+        let (execution_result, _) = build_test().unwrap();
+
+        let builder = ZkWasmCircuitBuilder {
+            tables: execution_result.tables,
+            public_inputs_and_outputs: execution_result.public_inputs_and_outputs,
+        };
+        let circuit: TestCircuit<Fr> = builder.build_circuit();
+
+        let instances = circuit.instance_columns(&vec![]).unwrap();
+        let prover = MockProver::run(K, &circuit, instances.clone()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The image hash must match the image table.
+        let mut tampered = instances;
+        tampered[1][0] += Fr::from(1u64);
+        let prover = MockProver::run(K, &circuit, tampered).unwrap();
+        assert!(prover.verify().is_err());
+    }
 }
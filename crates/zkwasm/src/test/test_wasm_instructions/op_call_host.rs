@@ -3,7 +3,7 @@ use std::rc::Rc;
 
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::ForeignContext;
-use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
+use crate::runtime::WasmRuntimeIO;
 use crate::test::test_circuit_with_env;
 
 #[derive(Default)]